@@ -1,13 +1,41 @@
-import type { ApiEvent } from '@/types/event'
+import type { ApiEvent, AttendeeCounts, AttendingEvent, EventPage, RsvpStatus } from '@/types/event'
 
 export async function fetchEvents(): Promise<ApiEvent[]> {
-  const res = await fetch('/api/events')
+  const res = await fetch('/api/v1/events')
   if (!res.ok) throw new Error(`Failed to load events: ${res.status}`)
   return res.json()
 }
 
 export async function fetchEvent(id: string): Promise<ApiEvent> {
-  const res = await fetch(`/api/event/${id}`)
+  const res = await fetch(`/api/v1/events/${id}`)
   if (!res.ok) throw new Error(`Failed to load event ${id}`)
   return res.json()
 }
+
+export async function rsvpEvent(id: string, status: RsvpStatus): Promise<AttendeeCounts> {
+  const res = await fetch(`/api/v1/events/${id}/rsvp`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ status }),
+  })
+  if (!res.ok) throw new Error(`Failed to RSVP to event ${id}: ${res.status}`)
+  return res.json()
+}
+
+export async function cancelRsvp(id: string): Promise<AttendeeCounts> {
+  const res = await fetch(`/api/v1/events/${id}/rsvp`, { method: 'DELETE' })
+  if (!res.ok) throw new Error(`Failed to cancel RSVP for event ${id}: ${res.status}`)
+  return res.json()
+}
+
+export async function fetchMyEvents(): Promise<AttendingEvent[]> {
+  const res = await fetch('/api/v1/me/events')
+  if (!res.ok) throw new Error(`Failed to load your events: ${res.status}`)
+  return res.json()
+}
+
+export async function fetchMyFeed(offset = 0, limit = 50): Promise<EventPage> {
+  const res = await fetch(`/api/v1/me/feed?offset=${offset}&limit=${limit}`)
+  if (!res.ok) throw new Error(`Failed to load your feed: ${res.status}`)
+  return res.json()
+}
@@ -1,7 +1,23 @@
 import type { ApiGroup } from '@/types/group'
 
 export async function getGroup(id: string): Promise<ApiGroup> {
-  const res = await fetch(`/api/group/${id}`)
+  const res = await fetch(`/api/v1/groups/${id}`)
   if (!res.ok) throw new Error(`Failed to load events: ${res.status}`)
   return res.json()
-}
\ No newline at end of file
+}
+
+export async function followGroup(id: string): Promise<void> {
+  const res = await fetch(`/api/v1/groups/${id}/follow`, { method: 'POST' })
+  if (!res.ok) throw new Error(`Failed to follow group ${id}: ${res.status}`)
+}
+
+export async function unfollowGroup(id: string): Promise<void> {
+  const res = await fetch(`/api/v1/groups/${id}/follow`, { method: 'DELETE' })
+  if (!res.ok) throw new Error(`Failed to unfollow group ${id}: ${res.status}`)
+}
+
+export async function fetchFollowedGroups(): Promise<ApiGroup[]> {
+  const res = await fetch('/api/v1/me/groups')
+  if (!res.ok) throw new Error(`Failed to load followed groups: ${res.status}`)
+  return res.json()
+}
@@ -1,7 +1,7 @@
 import type { DiscordUser } from '@/types/discord_user'
 
 export async function getInfo(): Promise<DiscordUser | null> {
-  const res = await fetch("/api/auth/me")
+  const res = await fetch("/api/v1/auth/me")
   if (res.ok) {
     return res.json()
   }
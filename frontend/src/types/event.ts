@@ -5,10 +5,55 @@ export interface ApiEvent {
   description: string
   starts_at: string
   ends_at: string
-  category: string,
-  access_type: string,
-  platforms: string[],
+  category: EventCategory,
+  access_type: EventAccessType,
+  platforms: EventPlatform[],
   image_url?: string,
   tags?: string[],
   created_at: string
+  going_count: number
+  interested_count: number
+  version: number
+  updated_at: string
+  deleted_at?: string
+}
+
+// the server passes values it does not know through unchanged, hence the `string` fallback
+export type EventCategory =
+  | 'arts'
+  | 'avatars'
+  | 'dance'
+  | 'education'
+  | 'exploring'
+  | 'film_media'
+  | 'gaming'
+  | 'hangout'
+  | 'music'
+  | 'other'
+  | 'performance'
+  | 'roleplaying'
+  | 'wellness'
+  | (string & {})
+
+export type EventAccessType = 'public' | 'group' | (string & {})
+
+export type EventPlatform = 'standalonewindows' | 'android' | 'ios' | (string & {})
+
+export type RsvpStatus = 'going' | 'interested'
+
+export interface AttendingEvent extends ApiEvent {
+  rsvp_status: RsvpStatus
+}
+
+export interface AttendeeCounts {
+  vrc_event_id: string
+  going_count: number
+  interested_count: number
+}
+
+export interface EventPage {
+  events: ApiEvent[]
+  limit: number
+  offset: number
+  next_offset: number | null
 }
\ No newline at end of file
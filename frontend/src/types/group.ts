@@ -1,5 +1,19 @@
+export type GroupVisibility = 'public' | 'unlisted' | 'private'
+
 export interface ApiGroup {
-  vrc_event_id: string
+  vrc_group_id: string
   name: string
   created_at: string
-}
\ No newline at end of file
+  description?: string
+  icon_url?: string
+  banner_url?: string
+  short_code?: string
+  discord_invite_url?: string
+  website_url?: string
+  timezone?: string
+  language?: string
+  visibility: GroupVisibility
+  version: number
+  updated_at: string
+  deleted_at?: string
+}
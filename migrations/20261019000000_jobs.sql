@@ -0,0 +1,17 @@
+-- Add migration script here
+create table jobs(
+    id uuid primary key default gen_random_uuid(),
+    kind text not null,
+    dedupe_key text unique,
+    payload text not null default '{}',
+    status text not null default 'pending',
+    run_at timestamptz not null,
+    attempts int not null default 0,
+    max_attempts int not null default 5,
+    last_error text,
+    locked_at timestamptz,
+    created_at timestamptz not null default now(),
+    constraint check_job_status check (status in ('pending', 'running', 'done', 'dead'))
+);
+
+create index jobs_due on jobs(run_at) where status in ('pending', 'running');
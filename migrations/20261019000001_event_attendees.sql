@@ -0,0 +1,23 @@
+-- Add migration script here
+create table users(
+    id text primary key,
+    username text not null,
+    global_name text,
+    avatar text,
+    created_at timestamptz not null default now()
+);
+
+create table event_attendees(
+    vrc_event_id text not null references events(vrc_event_id) on delete cascade,
+    user_id text not null references users(id) on delete cascade,
+    status text not null,
+    created_at timestamptz not null default now(),
+    primary key (vrc_event_id, user_id),
+    constraint check_attendee_status check (status in ('going', 'interested'))
+);
+
+create index event_attendees_user_id on event_attendees(user_id);
+
+alter table events
+    add column going_count int not null default 0,
+    add column interested_count int not null default 0;
@@ -0,0 +1,2 @@
+-- Add migration script here
+alter table users add column feed_token text unique;
@@ -0,0 +1,9 @@
+-- Add migration script here
+create table group_follows(
+    user_id text not null references users(id) on delete cascade,
+    vrc_group_id text not null references groups(vrc_group_id) on delete cascade,
+    created_at timestamptz not null default now(),
+    primary key (user_id, vrc_group_id)
+);
+
+create index events_group_starts_at on events(vrc_group_id, starts_at);
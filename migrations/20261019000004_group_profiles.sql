@@ -0,0 +1,12 @@
+-- Add migration script here
+alter table groups
+    add column description text,
+    add column icon_url text,
+    add column banner_url text,
+    add column short_code text unique,
+    add column discord_invite_url text,
+    add column website_url text,
+    add column timezone text,
+    add column language text,
+    add column visibility text not null default 'public',
+    add constraint check_group_visibility check (visibility in ('public', 'unlisted', 'private'));
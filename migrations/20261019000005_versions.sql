@@ -0,0 +1,8 @@
+-- Add migration script here
+alter table events
+    add column version int not null default 1,
+    add column updated_at timestamptz not null default now();
+
+alter table groups
+    add column version int not null default 1,
+    add column updated_at timestamptz not null default now();
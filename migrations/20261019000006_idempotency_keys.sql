@@ -0,0 +1,16 @@
+-- Add migration script here
+create table idempotency_keys(
+    api_key text not null references api_users(api_key) on delete cascade,
+    idempotency_key text not null,
+    -- sha-256 of the method, path and body, so a reused key with a different request can be rejected
+    request_hash text not null,
+    -- the response columns are null while the first request is still being handled
+    response_status int,
+    response_content_type text,
+    response_location text,
+    response_body bytea,
+    created_at timestamptz not null default now(),
+    primary key (api_key, idempotency_key)
+);
+
+create index idempotency_keys_created_at on idempotency_keys(created_at);
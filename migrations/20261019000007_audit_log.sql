@@ -0,0 +1,19 @@
+-- Add migration script here
+create table audit_log(
+    id bigint generated always as identity primary key,
+    entity_type text not null check (entity_type in ('event', 'group')),
+    entity_id text not null,
+    action text not null check (action in ('create', 'update', 'delete')),
+    -- 'api' actors are identified by their user agent, 'user' actors by their Discord ID
+    actor_type text not null check (actor_type in ('api', 'user')),
+    actor text not null,
+    request_id text,
+    before jsonb,
+    after jsonb,
+    -- changed fields of an update, as {"field": {"before": ..., "after": ...}}
+    diff jsonb,
+    created_at timestamptz not null default now()
+);
+
+create index audit_log_entity on audit_log(entity_type, entity_id, created_at);
+create index audit_log_created_at on audit_log(created_at);
@@ -0,0 +1,10 @@
+-- Add migration script here
+alter table events add column deleted_at timestamptz;
+alter table groups add column deleted_at timestamptz;
+
+-- the purge job looks for rows deleted before the retention cutoff
+create index events_deleted_at on events(deleted_at) where deleted_at is not null;
+create index groups_deleted_at on groups(deleted_at) where deleted_at is not null;
+
+alter table audit_log drop constraint audit_log_action_check;
+alter table audit_log add constraint audit_log_action_check check (action in ('create', 'update', 'delete', 'restore'));
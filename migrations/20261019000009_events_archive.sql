@@ -0,0 +1,12 @@
+-- Add migration script here
+-- ended events are moved here by the archive job, keeping the events table small. the columns mirror events
+-- in order, followed by archived_at
+create table events_archive (
+    like events including defaults including constraints,
+    archived_at timestamptz not null default now(),
+    primary key (vrc_event_id)
+);
+
+create index events_archive_vrc_group_id on events_archive(vrc_group_id);
+create index events_archive_starts_at on events_archive(starts_at);
+create index events_ends_at on events(ends_at);
@@ -0,0 +1,58 @@
+-- Add migration script here
+-- normalizes free-form values to the ones EventCategory, EventAccessType and EventPlatform know. unknown access
+-- types become 'group' rather than 'public', so that nothing is exposed by accident
+create function pg_temp.normalize_category(value text) returns text language sql immutable as $$
+    select case
+        when n in ('arts', 'avatars', 'dance', 'education', 'exploring', 'film_media', 'gaming', 'hangout', 'music',
+                   'other', 'performance', 'roleplaying', 'wellness') then n
+        else 'other'
+    end
+    from (select regexp_replace(lower(trim(value)), '[\s-]+', '_', 'g') as n) normalized
+$$;
+
+create function pg_temp.normalize_access_type(value text) returns text language sql immutable as $$
+    select case lower(trim(value)) when 'public' then 'public' else 'group' end
+$$;
+
+create function pg_temp.normalize_platforms(value text[]) returns text[] language sql immutable as $$
+    select coalesce(array_agg(platform order by first_seen), '{}')
+    from (
+        select platform, min(ordinality) as first_seen
+        from unnest(value) with ordinality as p(raw, ordinality),
+            lateral (select case lower(trim(raw))
+                when 'standalonewindows' then 'standalonewindows'
+                when 'windows' then 'standalonewindows'
+                when 'pc' then 'standalonewindows'
+                when 'android' then 'android'
+                when 'quest' then 'android'
+                when 'ios' then 'ios'
+            end as platform) mapped
+        where platform is not null
+        group by platform
+    ) platforms
+$$;
+
+update events set
+    category = pg_temp.normalize_category(category),
+    access_type = pg_temp.normalize_access_type(access_type),
+    platforms = pg_temp.normalize_platforms(platforms),
+    version = version + 1,
+    updated_at = now()
+where (category, access_type, platforms) is distinct from
+    (pg_temp.normalize_category(category), pg_temp.normalize_access_type(access_type), pg_temp.normalize_platforms(platforms));
+
+update events_archive set
+    category = pg_temp.normalize_category(category),
+    access_type = pg_temp.normalize_access_type(access_type),
+    platforms = pg_temp.normalize_platforms(platforms)
+where (category, access_type, platforms) is distinct from
+    (pg_temp.normalize_category(category), pg_temp.normalize_access_type(access_type), pg_temp.normalize_platforms(platforms));
+
+alter table events
+    add constraint check_event_category check (category in ('arts', 'avatars', 'dance', 'education', 'exploring',
+        'film_media', 'gaming', 'hangout', 'music', 'other', 'performance', 'roleplaying', 'wellness')),
+    add constraint check_event_access_type check (access_type in ('public', 'group')),
+    add constraint check_event_platforms check (platforms <@ array['standalonewindows', 'android', 'ios']);
+
+-- makes filtering by platform, e.g. platforms=android, an index lookup
+create index events_platforms on events using gin (platforms);
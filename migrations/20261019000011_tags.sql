@@ -0,0 +1,41 @@
+-- Add migration script here
+create table tags (
+    slug text primary key,
+    description text,
+    created_at timestamptz not null default now()
+);
+
+-- other spellings of a tag, resolved to the canonical slug whenever tags are stored
+create table tag_aliases (
+    alias text primary key,
+    slug text not null references tags(slug) on delete cascade,
+    created_at timestamptz not null default now()
+);
+
+create index tag_aliases_slug on tag_aliases(slug);
+
+-- normalize existing tags the way the API does: trimmed, lowercased, no leading '#', inner whitespace as '-'
+create function pg_temp.normalize_tags(value text[]) returns text[] language sql immutable as $$
+    select array_agg(tag order by first_seen)
+    from (
+        select tag, min(ordinality) as first_seen
+        from unnest(value) with ordinality as t(raw, ordinality),
+            lateral (select lower(regexp_replace(trim(ltrim(trim(raw), '#')), '\s+', '-', 'g')) as tag) normalized
+        where tag <> ''
+        group by tag
+    ) tags
+$$;
+
+update events set tags = pg_temp.normalize_tags(tags), version = version + 1, updated_at = now()
+where tags is distinct from pg_temp.normalize_tags(tags);
+
+update events_archive set tags = pg_temp.normalize_tags(tags)
+where tags is distinct from pg_temp.normalize_tags(tags);
+
+insert into tags (slug)
+select distinct unnest(tags) from events
+union
+select distinct unnest(tags) from events_archive
+on conflict do nothing;
+
+create index events_tags on events using gin (tags);
@@ -0,0 +1,13 @@
+-- Add migration script here
+-- the time an event occupies, for overlap and "happening now" queries. events are half-open, so one ending at
+-- midnight doesn't overlap the next day, except instant events which would otherwise be empty ranges
+alter table events add column during tstzrange not null generated always as (
+    tstzrange(starts_at, ends_at, case when starts_at = ends_at then '[]' else '[)' end)
+) stored;
+
+alter table events_archive add column during tstzrange not null generated always as (
+    tstzrange(starts_at, ends_at, case when starts_at = ends_at then '[]' else '[)' end)
+) stored;
+
+create index events_during on events using gist (during);
+create index events_starts_at on events(starts_at);
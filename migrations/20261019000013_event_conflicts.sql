@@ -0,0 +1,6 @@
+-- Add migration script here
+-- lets a group's overlapping events be found through one index. it is the index an exclusion constraint
+-- `exclude using gist (vrc_group_id with =, during with &&)` would use, should double booking ever be forbidden outright
+create extension if not exists btree_gist;
+
+create index events_group_during on events using gist (vrc_group_id, during) where deleted_at is null;
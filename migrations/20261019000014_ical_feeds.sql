@@ -0,0 +1,17 @@
+-- Add migration script here
+-- external calendars imported into a group's events, e.g. a Google Calendar's public .ics address
+create table ical_feeds(
+    vrc_group_id text primary key references groups(vrc_group_id) on delete cascade,
+    url text not null,
+    created_at timestamptz not null default now(),
+    -- the last successful import, and how many upcoming events the feed had then
+    last_synced_at timestamptz,
+    event_count integer not null default 0,
+    -- the last failed import, cleared by the next successful one
+    last_error text,
+    last_error_at timestamptz
+);
+
+-- 'system' actors are background jobs such as the feed importer
+alter table audit_log drop constraint audit_log_actor_type_check;
+alter table audit_log add constraint audit_log_actor_type_check check (actor_type in ('api', 'user', 'system'));
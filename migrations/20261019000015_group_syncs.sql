@@ -0,0 +1,14 @@
+-- Add migration script here
+-- how each group's last sync with its VRChat calendar went
+create table group_syncs(
+    vrc_group_id text primary key references groups(vrc_group_id) on delete cascade,
+    -- the last successful sync, how many upcoming events the calendar had and what changed
+    last_synced_at timestamptz,
+    event_count integer not null default 0,
+    created_count integer not null default 0,
+    updated_count integer not null default 0,
+    deleted_count integer not null default 0,
+    -- the last failed sync, cleared by the next successful one
+    last_error text,
+    last_error_at timestamptz
+);
@@ -0,0 +1,13 @@
+-- Add migration script here
+-- requests to the unversioned /api routes kept as aliases of /api/v1, so they can be removed once unused
+create table legacy_route_usage(
+    method text not null,
+    -- the route as registered, e.g. /api/event/{id}
+    route text not null,
+    -- empty when the client sent none
+    user_agent text not null,
+    request_count bigint not null default 0,
+    first_used_at timestamptz not null default now(),
+    last_used_at timestamptz not null default now(),
+    primary key (method, route, user_agent)
+);
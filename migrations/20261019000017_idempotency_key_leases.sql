@@ -0,0 +1,6 @@
+-- Add migration script here
+-- set while the first request for a key is being handled. a claim whose request never finished, e.g. because the
+-- process died, can be taken over by a retry once it is older than the lease
+alter table idempotency_keys add column locked_at timestamptz;
+
+update idempotency_keys set locked_at = created_at where response_status is null;
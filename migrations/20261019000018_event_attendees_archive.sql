@@ -0,0 +1,10 @@
+-- Add migration script here
+-- the RSVPs of archived events, moved along with them by the archive job
+create table event_attendees_archive (
+    like event_attendees including defaults including constraints,
+    primary key (vrc_event_id, user_id),
+    foreign key (vrc_event_id) references events_archive(vrc_event_id) on delete cascade,
+    foreign key (user_id) references users(id) on delete cascade
+);
+
+create index event_attendees_archive_user_id on event_attendees_archive(user_id);
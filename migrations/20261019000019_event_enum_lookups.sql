@@ -0,0 +1,33 @@
+-- Add migration script here
+-- the known categories, access types and platforms live in lookup tables rather than check constraints, so a newer
+-- release can add a value with an insert while older builds keep running and load it as Unknown
+create table event_categories (name text primary key);
+create table event_access_types (name text primary key);
+create table event_platforms (name text primary key);
+
+insert into event_categories (name) values ('arts'), ('avatars'), ('dance'), ('education'), ('exploring'),
+    ('film_media'), ('gaming'), ('hangout'), ('music'), ('other'), ('performance'), ('roleplaying'), ('wellness');
+insert into event_access_types (name) values ('public'), ('group');
+insert into event_platforms (name) values ('standalonewindows'), ('android'), ('ios');
+
+alter table events
+    drop constraint check_event_category,
+    drop constraint check_event_access_type,
+    drop constraint check_event_platforms,
+    add constraint events_category_fkey foreign key (category) references event_categories(name),
+    add constraint events_access_type_fkey foreign key (access_type) references event_access_types(name);
+
+-- a foreign key cannot check the elements of an array, so platforms are checked by a trigger that fails like the
+-- check constraint it replaces
+create function check_event_platforms() returns trigger language plpgsql as $$
+begin
+    if exists (select from unnest(new.platforms) p(name) where p.name not in (select name from event_platforms)) then
+        raise exception 'unknown platform in %', new.platforms
+            using errcode = 'check_violation', constraint = 'check_event_platforms', table = 'events';
+    end if;
+    return new;
+end
+$$;
+
+create trigger check_event_platforms before insert or update of platforms on events
+    for each row execute function check_event_platforms();
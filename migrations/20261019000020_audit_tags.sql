@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table audit_log drop constraint audit_log_entity_type_check;
+alter table audit_log add constraint audit_log_entity_type_check check (entity_type in ('event', 'group', 'tag'));
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- renewed while the request that claimed the key is still being handled. locked_at keeps naming the claim, and a
+-- claim is only taken over once its heartbeat is older than the lease
+alter table idempotency_keys add column heartbeat_at timestamptz;
+
+update idempotency_keys set heartbeat_at = locked_at;
@@ -0,0 +1,10 @@
+-- Add migration script here
+-- only a hash of the feed token is kept, the token itself is shown once when it is created
+alter table users add column feed_token_hash text unique;
+alter table users add column feed_token_created_at timestamptz;
+
+update users
+set feed_token_hash = encode(sha256(convert_to(feed_token, 'UTF8')), 'hex'), feed_token_created_at = now()
+where feed_token is not null;
+
+alter table users drop column feed_token;
@@ -17,15 +17,20 @@ use tracing::Level;
 
 use crate::{
     database::PostgresDatabase,
-    middleware::create_session,
+    middleware::{LegacyUsage, create_session, deprecated, idempotency, problem_details},
     oauth::OAuth,
-    routes::{AuthRoutes, EventRoutes, GroupRoutes, WebRoutes},
+    routes::{
+        ApiError, AuditRoutes, AuthRoutes, CalendarRoutes, EventRoutes, FeedRoutes, GroupRoutes, LegacyRoutes,
+        MeRoutes, OpenApiRoutes, TagRoutes, WebRoutes,
+    },
+    scheduler::{Scheduler, SchedulerConfig},
 };
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PostgresDatabase,
     pub oauth: OAuth,
+    pub legacy_usage: LegacyUsage,
     key: Key,
 }
 
@@ -33,7 +38,12 @@ impl AppState {
     #[must_use]
     pub fn new(db: PostgresDatabase, oauth: OAuth, app_key: String) -> Self {
         let key = Key::from(&BASE64_STANDARD.decode(app_key).expect("malformed APP_KEY"));
-        Self { db, oauth, key }
+        Self {
+            legacy_usage: LegacyUsage::new(db.clone()),
+            db,
+            oauth,
+            key,
+        }
     }
 
     #[must_use]
@@ -44,19 +54,50 @@ impl AppState {
 
 pub struct App {
     router: Router,
+    scheduler: Scheduler,
+    legacy_usage: LegacyUsage,
 }
 
 impl App {
-    pub fn new(db: PostgresDatabase, oauth: OAuth, app_key: String) -> Self {
+    pub fn new(db: PostgresDatabase, oauth: OAuth, app_key: String, scheduler_config: SchedulerConfig) -> Self {
+        let scheduler = Scheduler::new(db.clone(), scheduler_config);
         let app_state = AppState::new(db, oauth, app_key);
         let files = ServeDir::new("./frontend/dist");
 
+        let v1 = Router::new()
+            .merge(EventRoutes::router())
+            .merge(GroupRoutes::router())
+            .merge(AuthRoutes::router())
+            .merge(MeRoutes::router())
+            .merge(FeedRoutes::router())
+            .merge(AuditRoutes::router())
+            .merge(TagRoutes::router())
+            .merge(CalendarRoutes::router())
+            .merge(LegacyRoutes::router())
+            .merge(OpenApiRoutes::router())
+            .fallback(|| async { ApiError::NotFound });
+        // the paths from before /api/v1, answering like their v1 counterparts until they are sunset
+        let legacy = Router::new()
+            .merge(EventRoutes::legacy_router())
+            .merge(GroupRoutes::legacy_router())
+            .merge(AuthRoutes::router())
+            .merge(MeRoutes::router())
+            .merge(FeedRoutes::legacy_router())
+            .merge(AuditRoutes::router())
+            .merge(TagRoutes::legacy_router())
+            .merge(CalendarRoutes::router())
+            .merge(OpenApiRoutes::legacy_router())
+            .fallback(|| async { ApiError::NotFound })
+            .route_layer(middleware::from_fn_with_state(app_state.clone(), deprecated));
+
         let router = Router::new()
             .merge(WebRoutes::router())
-            .nest("/api", EventRoutes::router())
-            .nest("/api", GroupRoutes::router())
-            .nest("/api", AuthRoutes::router())
+            .nest("/api/v1", v1)
+            .nest("/api", legacy)
+            .merge(OpenApiRoutes::docs_router())
+            .layer(middleware::from_fn_with_state(app_state.clone(), idempotency))
             .layer(middleware::from_fn_with_state(app_state.clone(), create_session))
+            .layer(middleware::from_fn(problem_details))
             .layer(
                 ServiceBuilder::new()
                     .layer(SetRequestIdLayer::new(
@@ -76,7 +117,7 @@ impl App {
                                     "request",
                                     id = id,
                                     method = %request.method(),
-                                    uri = %request.uri(),
+                                    path = loggable_path(request.uri().path()),
                                     user_agent = tracing::field::Empty,
                                 )
                             })
@@ -84,12 +125,36 @@ impl App {
                     ),
             )
             .fallback_service(files)
-            .with_state(app_state);
+            .with_state(app_state.clone());
+
+        Self {
+            router,
+            scheduler,
+            legacy_usage: app_state.legacy_usage,
+        }
+    }
 
-        Self { router }
+    /// The routes without the scheduler, e.g. to send requests to in tests.
+    pub fn into_router(self) -> Router {
+        self.router
     }
 
     pub async fn serve(self, listener: TcpListener) -> Result<(), std::io::Error> {
+        tokio::spawn(self.scheduler.run());
+        tokio::spawn(self.legacy_usage.flush_periodically());
         axum::serve(listener, self.router.into_make_service()).await
     }
 }
+
+/// The path as it is logged: without the query string, and with the secret token of feed URLs redacted, since every
+/// poll of a calendar app would write it to the logs otherwise.
+fn loggable_path(path: &str) -> String {
+    let mut segments: Vec<&str> = path.split('/').collect();
+    // `/api/v1/feeds/{token}/...` and the legacy `/api/feed/{token}/...`
+    for (prefix, token) in [(["api", "v1", "feeds"].as_slice(), 4), (["api", "feed"].as_slice(), 3)] {
+        if segments.len() > token && segments[1..token] == *prefix {
+            segments[token] = "[redacted]";
+        }
+    }
+    segments.join("/")
+}
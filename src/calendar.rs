@@ -0,0 +1,189 @@
+//! Day, week and month calendar grids of events in an IANA time zone.
+
+use serde::Serialize;
+use time::{Date, Duration, Month, OffsetDateTime, Time, format_description::well_known::Rfc3339};
+use time_tz::{OffsetResult, PrimitiveDateTimeExt, Tz, timezones};
+
+use crate::database::Event;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarView {
+    Day,
+    Week,
+    Month,
+}
+
+impl CalendarView {
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
+
+/// The days shown by a calendar view. Weeks start on Monday.
+#[derive(Clone, Copy, Debug)]
+pub struct CalendarRange {
+    pub view: CalendarView,
+    pub first_day: Date,
+    pub last_day: Date,
+    pub tz: &'static Tz,
+}
+
+impl CalendarRange {
+    /// The range of `view` around `date`, which is a `YYYY-MM-DD` date, or also `YYYY-MM` for the month view.
+    #[must_use]
+    pub fn new(view: CalendarView, date: &str, tz: &'static Tz) -> Option<Self> {
+        let date = match view {
+            CalendarView::Month if date.len() == "YYYY-MM".len() => parse_date(&format!("{date}-01"))?,
+            _ => parse_date(date)?,
+        };
+
+        let (first_day, last_day) = match view {
+            CalendarView::Day => (date, date),
+            CalendarView::Week => {
+                let monday = date - Duration::days(i64::from(date.weekday().number_days_from_monday()));
+                (monday, monday + Duration::days(6))
+            }
+            CalendarView::Month => {
+                let first = date.replace_day(1).ok()?;
+                let last = date.replace_day(date.month().length(date.year())).ok()?;
+                (first, last)
+            }
+        };
+
+        Some(Self {
+            view,
+            first_day,
+            last_day,
+            tz,
+        })
+    }
+
+    /// The first instant shown.
+    #[must_use]
+    pub fn start(&self) -> OffsetDateTime {
+        start_of_day(self.first_day, self.tz)
+    }
+
+    /// The first instant after the range.
+    #[must_use]
+    pub fn end(&self) -> OffsetDateTime {
+        start_of_day(self.last_day + Duration::days(1), self.tz)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = Date> {
+        let last_day = self.last_day;
+        std::iter::successors(Some(self.first_day), |day| day.next_day()).take_while(move |day| *day <= last_day)
+    }
+}
+
+/// An event on one day of the calendar.
+#[derive(Serialize)]
+pub struct CalendarEntry<'a> {
+    #[serde(flatten)]
+    pub event: &'a Event,
+    /// The event started on an earlier day.
+    pub continues_before: bool,
+    /// The event ends on a later day.
+    pub continues_after: bool,
+}
+
+#[derive(Serialize)]
+pub struct CalendarDay<'a> {
+    /// `YYYY-MM-DD` in the calendar's time zone.
+    pub date: String,
+    /// Every event on this day, including those left out of `events`.
+    pub count: usize,
+    /// How many events were left out of `events` to keep the day short.
+    pub overflow: usize,
+    pub events: Vec<CalendarEntry<'a>>,
+}
+
+/// Puts each event on every day of `range` it overlaps, soonest first, keeping at most `max_per_day` per day.
+#[must_use]
+pub fn bucket_events<'a>(range: &CalendarRange, events: &'a [Event], max_per_day: usize) -> Vec<CalendarDay<'a>> {
+    let mut events: Vec<&Event> = events.iter().collect();
+    events.sort_by(|a, b| (a.starts_at, &a.vrc_event_id).cmp(&(b.starts_at, &b.vrc_event_id)));
+
+    range
+        .days()
+        .map(|day| {
+            let day_start = start_of_day(day, range.tz);
+            let day_end = start_of_day(day + Duration::days(1), range.tz);
+
+            // an event ending at midnight doesn't spill into the next day, an instant event at midnight still counts
+            let entries: Vec<CalendarEntry> = events
+                .iter()
+                .filter(|event| {
+                    event.starts_at < day_end && (event.ends_at > day_start || event.starts_at >= day_start)
+                })
+                .map(|event| CalendarEntry {
+                    event,
+                    continues_before: event.starts_at < day_start,
+                    continues_after: event.ends_at > day_end,
+                })
+                .collect();
+
+            let count = entries.len();
+            CalendarDay {
+                date: day.to_string(),
+                count,
+                overflow: count.saturating_sub(max_per_day),
+                events: entries.into_iter().take(max_per_day).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Looks up an IANA time zone name, defaulting to UTC.
+#[must_use]
+pub fn resolve_timezone(name: Option<&str>) -> Option<&'static Tz> {
+    name.map_or(Some(timezones::db::UTC), timezones::get_by_name)
+}
+
+/// Parses a `YYYY-MM-DD` date.
+#[must_use]
+pub fn parse_date(value: &str) -> Option<Date> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Parses an RFC 3339 instant or a `YYYY-MM-DD` date. A date is the start of that day in `tz`, or its end if
+/// `end_of_day` is set.
+#[must_use]
+pub fn parse_instant(value: &str, tz: &Tz, end_of_day: bool) -> Option<OffsetDateTime> {
+    if let Ok(instant) = OffsetDateTime::parse(value, &Rfc3339) {
+        return Some(instant);
+    }
+
+    let date = parse_date(value)?;
+    Some(if end_of_day {
+        start_of_day(date + Duration::days(1), tz)
+    } else {
+        start_of_day(date, tz)
+    })
+}
+
+/// Midnight at the start of `date` in `tz`. Where a daylight saving change skips midnight, the day starts when the
+/// clocks jump.
+#[must_use]
+pub fn start_of_day(date: Date, tz: &Tz) -> OffsetDateTime {
+    let midnight = date.with_time(Time::MIDNIGHT);
+    match midnight.assume_timezone(tz) {
+        OffsetResult::Some(start) | OffsetResult::Ambiguous(start, _) => start,
+        OffsetResult::None => match (midnight - Duration::hours(1)).assume_timezone(tz) {
+            OffsetResult::Some(before) | OffsetResult::Ambiguous(before, _) => before + Duration::hours(1),
+            OffsetResult::None => midnight.assume_timezone_utc(tz),
+        },
+    }
+}
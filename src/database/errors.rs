@@ -1,11 +1,43 @@
+use sqlx::error::ErrorKind;
+
+use crate::database::ConflictingEvent;
+
+#[derive(Debug)]
 pub enum DatabaseError {
     SqlxError(sqlx::Error),
     SerdeError(serde_json::Error),
     RngError,
+    /// A unique constraint was violated, carrying the constraint name.
+    UniqueViolation(String),
+    /// A foreign key constraint was violated, carrying the constraint name.
+    ForeignKeyViolation(String),
+    /// A check constraint was violated, carrying the constraint name.
+    CheckViolation(String),
+    /// The event belongs to a group that is soft deleted, which is not allowed to gain events until it is restored.
+    GroupDeleted,
+    /// The event overlaps these events of its group, and overlaps were to be rejected.
+    ScheduleConflict(Vec<ConflictingEvent>),
+    /// A value could not be stored as given, e.g. text containing a NUL character or a number out of range, carrying
+    /// the database's message.
+    InvalidData(String),
 }
 
 impl From<sqlx::Error> for DatabaseError {
     fn from(value: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(error) = &value {
+            let constraint = error.constraint().unwrap_or_default().to_string();
+            match error.kind() {
+                ErrorKind::UniqueViolation => return Self::UniqueViolation(constraint),
+                ErrorKind::ForeignKeyViolation => return Self::ForeignKeyViolation(constraint),
+                ErrorKind::CheckViolation => return Self::CheckViolation(constraint),
+                _ => {}
+            }
+            // class 22 is "data exception", raised for values rather than for the state of the database
+            if error.code().is_some_and(|code| code.starts_with("22")) {
+                return Self::InvalidData(error.message().to_string());
+            }
+        }
+
         Self::SqlxError(value)
     }
 }
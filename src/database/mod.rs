@@ -23,6 +23,11 @@ impl PostgresDatabase {
             .await
             .expect("Failed to connect to postgres");
 
+        Self::from_pool(pool)
+    }
+
+    #[must_use]
+    pub fn from_pool(pool: PgPool) -> Self {
         Self {
             pool,
             event_cache: Arc::default(),
@@ -16,6 +16,7 @@ pub struct ApiUser {
 #[async_trait]
 pub trait ApiUserModel {
     async fn validate_api_key(&self, api_key: &str) -> Result<bool, DatabaseError>;
+    async fn get_api_user(&self, api_key: &str) -> Result<Option<ApiUser>, DatabaseError>;
 }
 
 #[async_trait]
@@ -31,4 +32,12 @@ impl ApiUserModel for PostgresDatabase {
             Ok(false)
         }
     }
+
+    async fn get_api_user(&self, api_key: &str) -> Result<Option<ApiUser>, DatabaseError> {
+        let user = sqlx::query_as!(ApiUser, "SELECT * FROM api_users WHERE api_key = $1", api_key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user)
+    }
 }
@@ -0,0 +1,187 @@
+use std::{sync::Arc, sync::atomic::Ordering};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::{QueryBuilder, prelude::FromRow};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::database::{DatabaseError, Event, EventFilters, PostgresDatabase};
+
+use super::event::push_event_filters;
+
+/// An event moved out of `events` by [`ArchiveModel::archive_events`].
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct ArchivedEvent {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub event: Event,
+    #[serde(with = "time::serde::rfc3339")]
+    pub archived_at: OffsetDateTime,
+}
+
+/// One page of archived events, `next_offset` is `None` on the last page.
+#[derive(Serialize, ToSchema)]
+pub struct ArchivedEventPage {
+    pub events: Vec<ArchivedEvent>,
+    pub limit: i64,
+    pub offset: i64,
+    pub next_offset: Option<i64>,
+}
+
+#[async_trait]
+pub trait ArchiveModel {
+    /// Moves events that ended before `ended_before` into the archive, along with their RSVPs. Soft deleted events
+    /// are left for the purge job.
+    async fn archive_events(&self, ended_before: OffsetDateTime) -> Result<u64, DatabaseError>;
+    async fn get_archived_event(&self, id: &str) -> Result<Option<ArchivedEvent>, DatabaseError>;
+    /// Live and archived events matching `filters`, for views of past dates such as the calendar.
+    async fn query_events_with_archive(&self, filters: &EventFilters) -> Result<Arc<[Event]>, DatabaseError>;
+    /// Archived events matching `filters`, most recent first.
+    async fn query_archived_events(
+        &self,
+        filters: &EventFilters,
+        limit: i64,
+        offset: i64,
+    ) -> Result<ArchivedEventPage, DatabaseError>;
+}
+
+#[async_trait]
+impl ArchiveModel for PostgresDatabase {
+    async fn archive_events(&self, ended_before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        // RSVPs lock the event first, so locking the events keeps their RSVPs from changing while they are moved
+        let ids = sqlx::query_scalar!(
+            "SELECT vrc_event_id FROM events WHERE ends_at < $1 AND deleted_at IS NULL FOR UPDATE",
+            ended_before
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        // an event that was archived, recreated and ended again replaces its archived copy and RSVPs
+        sqlx::query!("DELETE FROM events_archive WHERE vrc_event_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await?;
+
+        let archived = sqlx::query!(
+            r#"INSERT INTO events_archive
+              (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms,
+               image_url, tags, created_at, going_count, interested_count, version, updated_at)
+            SELECT
+              vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms,
+              image_url, tags, created_at, going_count, interested_count, version, updated_at
+            FROM events WHERE vrc_event_id = ANY($1)"#,
+            &ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"INSERT INTO event_attendees_archive (vrc_event_id, user_id, status, created_at)
+            SELECT vrc_event_id, user_id, status, created_at FROM event_attendees WHERE vrc_event_id = ANY($1)"#,
+            &ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM events WHERE vrc_event_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.dirty.swap(true, Ordering::Acquire);
+
+        Ok(archived.rows_affected())
+    }
+
+    async fn get_archived_event(&self, id: &str) -> Result<Option<ArchivedEvent>, DatabaseError> {
+        let row = sqlx::query!("SELECT * FROM events_archive WHERE vrc_event_id = $1", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| ArchivedEvent {
+            event: Event {
+                vrc_event_id: row.vrc_event_id,
+                vrc_group_id: row.vrc_group_id,
+                name: row.name,
+                description: row.description,
+                starts_at: row.starts_at,
+                ends_at: row.ends_at,
+                category: row.category.into(),
+                access_type: row.access_type.into(),
+                platforms: row.platforms.into(),
+                image_url: row.image_url,
+                tags: row.tags,
+                created_at: row.created_at,
+                going_count: row.going_count,
+                interested_count: row.interested_count,
+                version: row.version,
+                updated_at: row.updated_at,
+                deleted_at: row.deleted_at,
+                during: row.during,
+            },
+            archived_at: row.archived_at,
+        }))
+    }
+
+    async fn query_events_with_archive(&self, filters: &EventFilters) -> Result<Arc<[Event]>, DatabaseError> {
+        // an archived event that was recreated under the same ID is shown as its live copy
+        let mut query_builder = QueryBuilder::new(
+            r"SELECT * FROM (
+              SELECT
+                vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms,
+                image_url, tags, created_at, going_count, interested_count, version, updated_at, deleted_at, during
+              FROM events WHERE deleted_at IS NULL
+              UNION ALL
+              SELECT
+                vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms,
+                image_url, tags, created_at, going_count, interested_count, version, updated_at, deleted_at, during
+              FROM events_archive a
+              WHERE NOT EXISTS (SELECT 1 FROM events e WHERE e.vrc_event_id = a.vrc_event_id AND e.deleted_at IS NULL)
+            ) events WHERE 1=1",
+        );
+        push_event_filters(&mut query_builder, filters);
+
+        let events = query_builder.build_query_as::<Event>().fetch_all(&self.pool).await?;
+        Ok(events.into_boxed_slice().into())
+    }
+
+    async fn query_archived_events(
+        &self,
+        filters: &EventFilters,
+        limit: i64,
+        offset: i64,
+    ) -> Result<ArchivedEventPage, DatabaseError> {
+        let mut query_builder = QueryBuilder::new("SELECT * FROM events_archive WHERE 1=1");
+        push_event_filters(&mut query_builder, filters);
+
+        // fetch one extra row to tell whether there is a next page
+        query_builder.push(" ORDER BY starts_at DESC, vrc_event_id LIMIT ");
+        query_builder.push_bind(limit + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+
+        let mut events = query_builder
+            .build_query_as::<ArchivedEvent>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_offset = if events.len() > usize::try_from(limit).unwrap_or(usize::MAX) {
+            events.pop();
+            Some(offset + limit)
+        } else {
+            None
+        };
+
+        Ok(ArchivedEventPage {
+            events,
+            limit,
+            offset,
+            next_offset,
+        })
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::Ordering;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RsvpStatus {
+    Going,
+    Interested,
+}
+
+impl RsvpStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Going => "going",
+            Self::Interested => "interested",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRsvp {
+    pub status: RsvpStatus,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AttendeeCounts {
+    pub vrc_event_id: String,
+    pub going_count: i32,
+    pub interested_count: i32,
+}
+
+#[async_trait]
+pub trait AttendeeModel {
+    /// Returns `None` if the event does not exist.
+    async fn set_rsvp(
+        &self,
+        vrc_event_id: &str,
+        user_id: &str,
+        status: RsvpStatus,
+    ) -> Result<Option<AttendeeCounts>, DatabaseError>;
+    /// Returns `None` if the event does not exist.
+    async fn remove_rsvp(&self, vrc_event_id: &str, user_id: &str) -> Result<Option<AttendeeCounts>, DatabaseError>;
+}
+
+#[async_trait]
+impl AttendeeModel for PostgresDatabase {
+    async fn set_rsvp(
+        &self,
+        vrc_event_id: &str,
+        user_id: &str,
+        status: RsvpStatus,
+    ) -> Result<Option<AttendeeCounts>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+        if !lock_event(&mut tx, vrc_event_id).await? {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO event_attendees
+              (vrc_event_id, user_id, status)
+            SELECT vrc_event_id, $2, $3 FROM events WHERE vrc_event_id = $1 AND deleted_at IS NULL
+            ON CONFLICT (vrc_event_id, user_id) DO UPDATE SET
+              status = excluded.status"#,
+            vrc_event_id,
+            user_id,
+            status.as_str(),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let counts = refresh_attendee_counts(&mut tx, vrc_event_id).await?;
+        tx.commit().await?;
+
+        Ok(counts)
+    }
+
+    async fn remove_rsvp(&self, vrc_event_id: &str, user_id: &str) -> Result<Option<AttendeeCounts>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+        if !lock_event(&mut tx, vrc_event_id).await? {
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "DELETE FROM event_attendees WHERE vrc_event_id = $1 AND user_id = $2",
+            vrc_event_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let counts = refresh_attendee_counts(&mut tx, vrc_event_id).await?;
+        tx.commit().await?;
+
+        Ok(counts)
+    }
+}
+
+/// Locks the event's row until the transaction ends, so that concurrent RSVPs to it are recounted one after the
+/// other rather than each writing a count that misses the other. Returns whether the event exists.
+async fn lock_event(conn: &mut PgConnection, vrc_event_id: &str) -> Result<bool, sqlx::Error> {
+    let locked = sqlx::query_scalar!(
+        "SELECT vrc_event_id FROM events WHERE vrc_event_id = $1 AND deleted_at IS NULL FOR UPDATE",
+        vrc_event_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(locked.is_some())
+}
+
+/// Recounts the denormalized attendee counts on `events`, which keeps them readable straight from the event
+/// cache without a join. The event must be locked with [`lock_event`] first.
+///
+/// The version is left alone: RSVPs are not edits, and must not fail an organizer's `If-Match`.
+async fn refresh_attendee_counts(
+    conn: &mut PgConnection,
+    vrc_event_id: &str,
+) -> Result<Option<AttendeeCounts>, sqlx::Error> {
+    sqlx::query_as!(
+        AttendeeCounts,
+        r#"UPDATE events SET
+          going_count = (SELECT count(*) FROM event_attendees a WHERE a.vrc_event_id = $1 AND a.status = 'going'),
+          interested_count = (SELECT count(*) FROM event_attendees a WHERE a.vrc_event_id = $1 AND a.status = 'interested')
+        WHERE
+          vrc_event_id = $1 AND deleted_at IS NULL
+        RETURNING vrc_event_id, going_count, interested_count"#,
+        vrc_event_id
+    )
+    .fetch_optional(conn)
+    .await
+}
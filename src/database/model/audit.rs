@@ -0,0 +1,277 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use sqlx::{PgConnection, QueryBuilder, prelude::FromRow};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use utoipa::ToSchema;
+
+use crate::{
+    database::{DatabaseError, PostgresDatabase},
+    validation::ValidationErrors,
+};
+
+/// Fields that change on every write and would only add noise to a diff.
+const UNDIFFED_FIELDS: &[&str] = &["version", "updated_at"];
+
+/// Who made a change. Only changes to events and groups are audited, which are made through the API or by background
+/// jobs. RSVPs and follows of logged in users are not.
+#[derive(Clone, Debug)]
+pub enum Actor {
+    /// An API user, identified by the user agent its API key was issued to.
+    ApiUser(String),
+    /// A background job, identified by what it does.
+    System(String),
+}
+
+impl Actor {
+    const fn kind(&self) -> &'static str {
+        match self {
+            Self::ApiUser(_) => "api",
+            Self::System(_) => "system",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::ApiUser(name) | Self::System(name) => name,
+        }
+    }
+}
+
+/// Attributes a change to the actor and request that made it.
+#[derive(Clone, Debug)]
+pub struct AuditContext {
+    pub actor: Actor,
+    pub request_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl AuditAction {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+        }
+    }
+}
+
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub actor_type: String,
+    pub actor: String,
+    pub request_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub diff: Option<Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One page of audit entries, `next_offset` is `None` on the last page.
+#[derive(Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub limit: i64,
+    pub offset: i64,
+    pub next_offset: Option<i64>,
+}
+
+/// Filters of the audit log: exact matches on `entity_type`, `entity_id`, `action`, `actor_type`, `actor` and
+/// `request_id`, and an RFC 3339 `since`/`until` range.
+#[derive(Debug, Default)]
+pub struct AuditFilters {
+    pub equals: Vec<(&'static str, String)>,
+    pub since: Option<OffsetDateTime>,
+    pub until: Option<OffsetDateTime>,
+}
+
+impl AuditFilters {
+    /// Parses the filters from a query string, ignoring other parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns every bound that is not an RFC 3339 timestamp.
+    pub fn parse<S: BuildHasher>(query: &HashMap<String, String, S>) -> Result<Self, ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let mut bound = |key: &str| {
+            let instant = OffsetDateTime::parse(query.get(key)?, &Rfc3339).ok();
+            if instant.is_none() {
+                errors.add(key, "must be an RFC 3339 timestamp");
+            }
+            instant
+        };
+        let (since, until) = (bound("since"), bound("until"));
+        errors.into_result()?;
+
+        let equals = [
+            "entity_type",
+            "entity_id",
+            "action",
+            "actor_type",
+            "actor",
+            "request_id",
+        ]
+        .into_iter()
+        .filter_map(|column| Some((column, query.get(column)?.clone())))
+        .collect();
+
+        Ok(Self { equals, since, until })
+    }
+}
+
+#[async_trait]
+pub trait AuditModel {
+    /// Every change to one event or group, oldest first.
+    async fn get_history(&self, entity_type: &str, entity_id: &str) -> Result<Vec<AuditEntry>, DatabaseError>;
+    /// Audit entries matching `filters`, newest first.
+    async fn query_audit_log(
+        &self,
+        filters: &AuditFilters,
+        limit: i64,
+        offset: i64,
+    ) -> Result<AuditPage, DatabaseError>;
+}
+
+#[async_trait]
+impl AuditModel for PostgresDatabase {
+    async fn get_history(&self, entity_type: &str, entity_id: &str) -> Result<Vec<AuditEntry>, DatabaseError> {
+        let entries = sqlx::query_as!(
+            AuditEntry,
+            "SELECT * FROM audit_log WHERE entity_type = $1 AND entity_id = $2 ORDER BY created_at, id",
+            entity_type,
+            entity_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    async fn query_audit_log(
+        &self,
+        filters: &AuditFilters,
+        limit: i64,
+        offset: i64,
+    ) -> Result<AuditPage, DatabaseError> {
+        let mut query_builder = QueryBuilder::new("SELECT * FROM audit_log WHERE 1=1");
+
+        for (column, value) in &filters.equals {
+            query_builder.push(format!(" AND {column} = "));
+            query_builder.push_bind(value.clone());
+        }
+
+        if let Some(since) = filters.since {
+            query_builder.push(" AND created_at >= ");
+            query_builder.push_bind(since);
+        }
+
+        if let Some(until) = filters.until {
+            query_builder.push(" AND created_at < ");
+            query_builder.push_bind(until);
+        }
+
+        // fetch one extra row to tell whether there is a next page
+        query_builder.push(" ORDER BY created_at DESC, id DESC LIMIT ");
+        query_builder.push_bind(limit + 1);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+
+        let mut entries = query_builder
+            .build_query_as::<AuditEntry>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let next_offset = if entries.len() > usize::try_from(limit).unwrap_or(usize::MAX) {
+            entries.pop();
+            Some(offset.saturating_add(limit))
+        } else {
+            None
+        };
+
+        Ok(AuditPage {
+            entries,
+            limit,
+            offset,
+            next_offset,
+        })
+    }
+}
+
+/// Records a change to an event or group. Call this in the transaction that makes the change, so that the log
+/// never disagrees with the data.
+pub(super) async fn record_audit(
+    conn: &mut PgConnection,
+    audit: &AuditContext,
+    entity_type: &str,
+    entity_id: &str,
+    action: AuditAction,
+    before: Option<&(impl Serialize + Sync)>,
+    after: Option<&(impl Serialize + Sync)>,
+) -> Result<(), DatabaseError> {
+    let before = before
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(DatabaseError::SerdeError)?;
+    let after = after
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(DatabaseError::SerdeError)?;
+    let diff = match (&before, &after) {
+        (Some(before), Some(after)) => Some(diff(before, after)),
+        _ => None,
+    };
+
+    sqlx::query!(
+        r#"INSERT INTO audit_log
+          (entity_type, entity_id, action, actor_type, actor, request_id, before, after, diff)
+        VALUES
+          ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+        entity_type,
+        entity_id,
+        action.as_str(),
+        audit.actor.kind(),
+        audit.actor.name(),
+        audit.request_id,
+        before,
+        after,
+        diff,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+fn diff(before: &Value, after: &Value) -> Value {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let changes: Map<String, Value> = before
+        .keys()
+        .chain(after.keys().filter(|key| !before.contains_key(*key)))
+        .filter(|key| !UNDIFFED_FIELDS.contains(&key.as_str()))
+        .filter_map(|key| {
+            let old = before.get(key).unwrap_or(&Value::Null);
+            let new = after.get(key).unwrap_or(&Value::Null);
+            (old != new).then(|| (key.clone(), json!({ "before": old, "after": new })))
+        })
+        .collect();
+
+    Value::Object(changes)
+}
@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, prelude::FromRow};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+/// An event that overlaps the one being scheduled.
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ConflictingEvent {
+    pub vrc_event_id: String,
+    pub name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ends_at: OffsetDateTime,
+}
+
+/// Two events of the same group that overlap, and the time they share.
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct EventConflict {
+    pub vrc_event_id: String,
+    pub name: String,
+    pub conflicting_event_id: String,
+    pub conflicting_name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub overlap_starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub overlap_ends_at: OffsetDateTime,
+}
+
+#[async_trait]
+pub trait ConflictModel {
+    /// The group's events overlapping `starts_at..ends_at`, soonest first, leaving out `exclude_event_id` so that
+    /// an event being updated does not conflict with itself.
+    async fn get_overlapping_events(
+        &self,
+        group_id: &str,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        exclude_event_id: Option<&str>,
+    ) -> Result<Vec<ConflictingEvent>, DatabaseError>;
+    /// Every pair of the group's events that overlap each other and the range, soonest first. An unset bound
+    /// leaves the range open on that side.
+    async fn get_group_conflicts(
+        &self,
+        group_id: &str,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> Result<Vec<EventConflict>, DatabaseError>;
+}
+
+#[async_trait]
+impl ConflictModel for PostgresDatabase {
+    async fn get_overlapping_events(
+        &self,
+        group_id: &str,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        exclude_event_id: Option<&str>,
+    ) -> Result<Vec<ConflictingEvent>, DatabaseError> {
+        let events = overlapping_events(
+            &mut *self.pool.acquire().await?,
+            group_id,
+            starts_at,
+            ends_at,
+            exclude_event_id,
+        )
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_group_conflicts(
+        &self,
+        group_id: &str,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> Result<Vec<EventConflict>, DatabaseError> {
+        let conflicts = sqlx::query_as!(
+            EventConflict,
+            r#"SELECT
+              a.vrc_event_id, a.name,
+              b.vrc_event_id AS conflicting_event_id, b.name AS conflicting_name,
+              lower(a.during * b.during) AS "overlap_starts_at!",
+              upper(a.during * b.during) AS "overlap_ends_at!"
+            FROM events a
+            JOIN events b ON
+              b.vrc_group_id = a.vrc_group_id
+              AND b.vrc_event_id > a.vrc_event_id
+              AND b.during && a.during
+              AND b.deleted_at IS NULL
+            WHERE
+              a.vrc_group_id = $1
+              AND a.deleted_at IS NULL
+              AND (a.during * b.during) && tstzrange($2::timestamptz, $3::timestamptz, '[)')
+            ORDER BY "overlap_starts_at!", a.vrc_event_id, b.vrc_event_id"#,
+            group_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(conflicts)
+    }
+}
+
+/// See [`ConflictModel::get_overlapping_events`]. Call [`lock_group_schedule`] first when the result decides
+/// whether an event is stored.
+pub(super) async fn overlapping_events(
+    conn: &mut PgConnection,
+    group_id: &str,
+    starts_at: OffsetDateTime,
+    ends_at: OffsetDateTime,
+    exclude_event_id: Option<&str>,
+) -> Result<Vec<ConflictingEvent>, sqlx::Error> {
+    // the range is built the same way as the events' own `during`
+    sqlx::query_as!(
+        ConflictingEvent,
+        r#"SELECT vrc_event_id, name, starts_at, ends_at FROM events
+        WHERE
+          vrc_group_id = $1
+          AND during && tstzrange($2::timestamptz, $3::timestamptz, CASE WHEN $2::timestamptz = $3::timestamptz THEN '[]' ELSE '[)' END)
+          AND vrc_event_id IS DISTINCT FROM $4
+          AND deleted_at IS NULL
+        ORDER BY starts_at, vrc_event_id"#,
+        group_id,
+        starts_at,
+        ends_at,
+        exclude_event_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Keeps other transactions from adding, moving or restoring events of the group until this one ends, so that the
+/// overlaps it found stay the only ones until its own event is stored. Every write that schedules events takes it
+/// before locking any event rows.
+pub(super) async fn lock_group_schedule(conn: &mut PgConnection, group_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "SELECT pg_advisory_xact_lock(hashtextextended('group_schedule:' || $1, 0))",
+        group_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
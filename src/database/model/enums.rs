@@ -0,0 +1,171 @@
+use std::{fmt, ops::Deref};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{
+    Decode, Postgres, Type,
+    error::BoxDynError,
+    postgres::{PgTypeInfo, PgValueRef},
+};
+use utoipa::{
+    PartialSchema, ToSchema,
+    openapi::{ObjectBuilder, RefOr, Schema, schema::Type as SchemaType},
+};
+
+/// Declares an enum stored as `text`. Values this build does not know, e.g. ones written by a newer version, are
+/// kept as `Unknown` instead of failing to load, and round-trip unchanged. Aliases listed after a value's `|` are
+/// parsed as that value.
+macro_rules! text_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal $(| $alias:literal)*,)+ }) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)+
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Every known value, in the form stored and serialized.
+            pub const NAMES: &[&str] = &[$($value),+];
+
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Unknown(value) => value,
+                }
+            }
+
+            #[must_use]
+            pub const fn is_known(&self) -> bool {
+                !matches!(self, Self::Unknown(_))
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.trim().to_ascii_lowercase().as_str() {
+                    $($value $(| $alias)* => Self::$variant,)+
+                    _ => Self::Unknown(value),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+
+        impl Type<Postgres> for $name {
+            fn type_info() -> PgTypeInfo {
+                <String as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <String as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $name {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                Ok(String::decode(value)?.into())
+            }
+        }
+
+        // documents the known values, clients should still expect values added later
+        impl PartialSchema for $name {
+            fn schema() -> RefOr<Schema> {
+                ObjectBuilder::new()
+                    .schema_type(SchemaType::String)
+                    .enum_values(Some(Self::NAMES.iter().copied()))
+                    .into()
+            }
+        }
+
+        impl ToSchema for $name {}
+    };
+}
+
+text_enum! {
+    /// What kind of event it is, matching the categories offered for group events in game.
+    EventCategory {
+        Arts => "arts",
+        Avatars => "avatars",
+        Dance => "dance",
+        Education => "education",
+        Exploring => "exploring",
+        FilmMedia => "film_media",
+        Gaming => "gaming",
+        Hangout => "hangout",
+        Music => "music",
+        Other => "other",
+        Performance => "performance",
+        Roleplaying => "roleplaying",
+        Wellness => "wellness",
+    }
+}
+
+text_enum! {
+    /// Who can join the event's instance.
+    EventAccessType {
+        Public => "public",
+        Group => "group",
+    }
+}
+
+text_enum! {
+    /// A platform the event's world supports, named after the game's build targets. The names players use for them
+    /// are accepted too.
+    EventPlatform {
+        StandaloneWindows => "standalonewindows" | "windows" | "pc",
+        Android => "android" | "quest",
+        Ios => "ios",
+    }
+}
+
+/// The platforms of a stored event. Wraps the list so it can be loaded straight from a `text[]` column.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventPlatforms(pub Vec<EventPlatform>);
+
+impl Deref for EventPlatforms {
+    type Target = [EventPlatform];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<String>> for EventPlatforms {
+    fn from(value: Vec<String>) -> Self {
+        Self(value.into_iter().map(EventPlatform::from).collect())
+    }
+}
+
+impl Type<Postgres> for EventPlatforms {
+    fn type_info() -> PgTypeInfo {
+        <Vec<String> as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Vec<String> as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for EventPlatforms {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Vec::<String>::decode(value)?.into())
+    }
+}
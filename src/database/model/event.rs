@@ -1,16 +1,33 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
+    hash::BuildHasher,
     sync::{Arc, atomic::Ordering},
 };
 
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
-use sqlx::{QueryBuilder, prelude::FromRow};
-use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Connection, PgConnection, Postgres, QueryBuilder, postgres::types::PgRange, prelude::FromRow};
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
 
-use crate::database::{DatabaseError, PostgresDatabase};
+use crate::{
+    calendar,
+    database::{
+        AuditAction, AuditContext, ConflictingEvent, DatabaseError, EventAccessType, EventCategory, EventPlatform,
+        EventPlatforms, PostgresDatabase,
+    },
+    validation::{Validate, ValidationErrors},
+};
 
-#[derive(Serialize, FromRow)]
+use super::{
+    audit::record_audit,
+    conflict::{lock_group_schedule, overlapping_events},
+    group::check_group_not_deleted,
+    job::{cancel_event_reminder, schedule_event_reminder},
+    tag::{canonicalize_tags, normalize_tag},
+};
+
+#[derive(Serialize, FromRow, ToSchema)]
 pub struct Event {
     pub vrc_event_id: String,
     pub vrc_group_id: String,
@@ -20,45 +37,349 @@ pub struct Event {
     pub starts_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub ends_at: OffsetDateTime,
-    pub category: String,
-    pub access_type: String,
-    pub platforms: Vec<String>,
+    pub category: EventCategory,
+    pub access_type: EventAccessType,
+    #[schema(value_type = Vec<EventPlatform>)]
+    pub platforms: EventPlatforms,
     pub image_url: Option<String>,
     pub tags: Option<Vec<String>>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    pub going_count: i32,
+    pub interested_count: i32,
+    /// Incremented on every edit, but not on RSVPs. The start of the `ETag`, and all that `If-Match` compares.
+    pub version: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// Set while the event is soft deleted. Deleted events are left out of every read.
+    #[serde(with = "time::serde::rfc3339::option", skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<OffsetDateTime>,
+    /// The time the event occupies, maintained by the database for overlap queries.
+    #[serde(skip)]
+    pub during: PgRange<OffsetDateTime>,
+}
+
+/// An event together with the requesting user's RSVP.
+#[derive(Serialize, FromRow)]
+pub struct AttendingEvent {
+    pub rsvp_status: String,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub event: Event,
+}
+
+/// One page of events, `next_offset` is `None` on the last page.
+#[derive(Serialize, ToSchema)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    pub limit: i64,
+    pub offset: i64,
+    pub next_offset: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateEvent {
     pub vrc_event_id: String,
     pub vrc_group_id: String,
+    /// Stored with surrounding whitespace trimmed.
     pub name: String,
     pub description: String,
     #[serde(with = "time::serde::rfc3339")]
     pub starts_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub ends_at: OffsetDateTime,
-    pub category: String,
-    pub access_type: String,
-    pub platforms: Vec<String>,
+    pub category: EventCategory,
+    pub access_type: EventAccessType,
+    pub platforms: Vec<EventPlatform>,
     pub image_url: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_tags")]
     pub tags: Option<Vec<String>>,
 }
 
-#[derive(Serialize)]
+const MAX_EVENT_NAME_LENGTH: usize = 200;
+const MAX_EVENT_DESCRIPTION_LENGTH: usize = 10_000;
+const MAX_EVENT_TAGS: usize = 10;
+pub(super) const MAX_EVENT_TAG_LENGTH: usize = 32;
+/// Events may last at most this long, which also bounds the events imported from feeds.
+pub const MAX_EVENT_DURATION: Duration = Duration::days(7);
+
+impl Validate for CreateEvent {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.check_length("vrc_event_id", &self.vrc_event_id, 1, 64);
+        errors.check_length("vrc_group_id", &self.vrc_group_id, 1, 64);
+        errors.check_length("name", self.name.trim(), 1, MAX_EVENT_NAME_LENGTH);
+        errors.check_length("description", &self.description, 0, MAX_EVENT_DESCRIPTION_LENGTH);
+        if self.starts_at > self.ends_at {
+            errors.add("ends_at", "must not be before starts_at");
+        } else if self.ends_at - self.starts_at > MAX_EVENT_DURATION {
+            errors.add(
+                "ends_at",
+                format!(
+                    "must be at most {} days after starts_at",
+                    MAX_EVENT_DURATION.whole_days()
+                ),
+            );
+        }
+        errors.check_one_of("category", self.category.as_str(), EventCategory::NAMES);
+        errors.check_one_of("access_type", self.access_type.as_str(), EventAccessType::NAMES);
+        for platform in &self.platforms {
+            errors.check_one_of("platforms", platform.as_str(), EventPlatform::NAMES);
+        }
+        if let Some(image_url) = &self.image_url {
+            errors.check_url("image_url", image_url, &[]);
+        }
+        if let Some(tags) = &self.tags {
+            if tags.len() > MAX_EVENT_TAGS {
+                errors.add("tags", format!("must contain at most {MAX_EVENT_TAGS} tags"));
+            }
+            for tag in tags {
+                errors.check_length("tags", tag, 1, MAX_EVENT_TAG_LENGTH);
+            }
+        }
+        errors.into_result()
+    }
+}
+
+/// The filters shared by the live and archived event listings:
+///
+/// - `starts_at`/`ends_at`: events entirely within the range
+/// - `from`/`to`: events overlapping the range, including multi-day events that started before it
+/// - `group_id`
+/// - comma-separated `category`, `access_type`, `platforms` and `tags` lists, matching events with any of the values
+#[derive(Debug, Default)]
+pub struct EventFilters {
+    pub starts_at: Option<OffsetDateTime>,
+    pub ends_at: Option<OffsetDateTime>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+    pub group_id: Option<String>,
+    pub categories: Option<Vec<String>>,
+    pub access_types: Option<Vec<String>>,
+    pub platforms: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl EventFilters {
+    /// Parses the filters from a query string, ignoring other parameters. Bounds are RFC 3339 instants or
+    /// `YYYY-MM-DD` dates in the time zone `tz` (UTC by default). A date as an upper bound includes the whole day.
+    ///
+    /// # Errors
+    ///
+    /// Returns every parameter that is not a valid bound or time zone.
+    pub fn parse<S: BuildHasher>(query: &HashMap<String, String, S>) -> Result<Self, ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        let tz = calendar::resolve_timezone(query.get("tz").map(String::as_str));
+        if tz.is_none() {
+            errors.add("tz", "must be an IANA time zone name such as 'Europe/Berlin'");
+        }
+        let mut bound = |key: &str, end_of_day: bool| {
+            let value = query.get(key)?;
+            let instant = calendar::parse_instant(value, tz?, end_of_day);
+            if instant.is_none() {
+                errors.add(key, "must be a date such as 2026-10-19 or an RFC 3339 timestamp");
+            }
+            instant
+        };
+        let (starts_at, ends_at) = (bound("starts_at", false), bound("ends_at", true));
+        let (from, to) = (bound("from", false), bound("to", true));
+        errors.into_result()?;
+
+        // values go through the enums so that e.g. `platforms=Android` matches the stored `android`
+        Ok(Self {
+            starts_at,
+            ends_at,
+            from,
+            to,
+            group_id: query.get("group_id").cloned(),
+            categories: query.get("category").map(|value| list_param::<EventCategory>(value)),
+            access_types: query
+                .get("access_type")
+                .map(|value| list_param::<EventAccessType>(value)),
+            platforms: query.get("platforms").map(|value| list_param::<EventPlatform>(value)),
+            tags: query.get("tags").map(|value| {
+                value
+                    .split(',')
+                    .map(normalize_tag)
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            }),
+        })
+    }
+}
+
+/// Adds `filters` to a query over `events` or `events_archive`.
+pub(super) fn push_event_filters(query_builder: &mut QueryBuilder<'_, Postgres>, filters: &EventFilters) {
+    if let Some(starts_at) = filters.starts_at {
+        query_builder.push(" AND starts_at >= ");
+        query_builder.push_bind(starts_at);
+    }
+
+    if let Some(ends_at) = filters.ends_at {
+        query_builder.push(" AND ends_at <= ");
+        query_builder.push_bind(ends_at);
+    }
+
+    // an unset bound leaves the range open on that side
+    if filters.from.is_some() || filters.to.is_some() {
+        query_builder.push(" AND during && tstzrange(");
+        query_builder.push_bind(filters.from);
+        query_builder.push("::timestamptz, ");
+        query_builder.push_bind(filters.to);
+        query_builder.push("::timestamptz, '[)')");
+    }
+
+    if let Some(group_id) = &filters.group_id {
+        query_builder.push(" AND vrc_group_id = ");
+        query_builder.push_bind(group_id.clone());
+    }
+
+    if let Some(categories) = &filters.categories {
+        query_builder.push(" AND category = ANY(");
+        query_builder.push_bind(categories.clone());
+        query_builder.push(")");
+    }
+
+    if let Some(access_types) = &filters.access_types {
+        query_builder.push(" AND access_type = ANY(");
+        query_builder.push_bind(access_types.clone());
+        query_builder.push(")");
+    }
+
+    if let Some(platforms) = &filters.platforms {
+        query_builder.push(" AND platforms && ");
+        query_builder.push_bind(platforms.clone());
+    }
+
+    // an alias finds the events stored under its canonical tag
+    if let Some(tags) = &filters.tags {
+        query_builder.push(" AND tags && ARRAY(SELECT COALESCE(a.slug, t.tag) FROM unnest(");
+        query_builder.push_bind(tags.clone());
+        query_builder.push("::text[]) AS t(tag) LEFT JOIN tag_aliases a ON a.alias = t.tag)");
+    }
+}
+
+fn list_param<T: From<String> + ToString>(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(|item| T::from(item.to_string()).to_string())
+        .collect()
+}
+
+fn platform_names(platforms: &[EventPlatform]) -> Vec<String> {
+    platforms.iter().map(ToString::to_string).collect()
+}
+
+/// Normalizes tags with [`normalize_tag`] and drops empty and duplicate ones.
+fn deserialize_tags<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    let Some(tags) = Option::<Vec<String>>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_tag(&tag);
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    Ok(Some(normalized))
+}
+
+/// Normalizes tags coming from another system, dropping the ones that would fail validation and any past the limit.
+#[must_use]
+pub fn import_tags<T: AsRef<str>>(tags: impl IntoIterator<Item = T>) -> Vec<String> {
+    let mut imported: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = normalize_tag(tag.as_ref());
+        if !tag.is_empty() && tag.len() <= MAX_EVENT_TAG_LENGTH && !imported.contains(&tag) {
+            imported.push(tag);
+        }
+    }
+    imported.truncate(MAX_EVENT_TAGS);
+
+    imported
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct CreatedEvent {
     pub vrc_event_id: String,
 }
 
+/// What [`EventModel::upsert_events`] did with one event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    Created,
+    Updated,
+    /// The stored event already matched.
+    Unchanged,
+    /// The event exists under a different group. Events cannot be moved between groups.
+    GroupMismatch,
+    /// The event is soft deleted and has to be restored before it can be updated.
+    Deleted,
+}
+
 #[async_trait]
 pub trait EventModel {
     async fn get_all_events(&self) -> Result<Arc<[Event]>, DatabaseError>;
-    async fn query_events(&self, query: HashMap<String, String>) -> Result<Arc<[Event]>, DatabaseError>;
+    async fn query_events(&self, filters: &EventFilters) -> Result<Arc<[Event]>, DatabaseError>;
     async fn get_event(&self, id: &str) -> Result<Option<Event>, DatabaseError>;
-    async fn insert_event(&self, create_event: CreateEvent) -> Result<CreatedEvent, DatabaseError>;
-    async fn update_event(&self, id: &str, create_event: CreateEvent) -> Result<(), DatabaseError>;
-    async fn delete_event(&self, id: &str) -> Result<(), DatabaseError>;
+    async fn get_attending_events(&self, user_id: &str) -> Result<Vec<AttendingEvent>, DatabaseError>;
+    /// Upcoming and ongoing events from the groups a user follows, soonest first.
+    async fn get_followed_events(&self, user_id: &str, limit: i64, offset: i64) -> Result<EventPage, DatabaseError>;
+    /// Events for a user's calendar feed: everything they RSVP'd to, plus recent and upcoming events from the groups
+    /// they follow.
+    async fn get_feed_events(&self, user_id: &str) -> Result<Vec<Event>, DatabaseError>;
+    /// Events in progress right now, ending soonest first.
+    async fn get_current_events(&self) -> Result<Vec<Event>, DatabaseError>;
+    /// Events starting from now until `within` from now, soonest first.
+    async fn get_upcoming_events(&self, within: Duration, limit: i64) -> Result<Vec<Event>, DatabaseError>;
+    /// IDs of the group's events starting with `id_prefix` that have not ended yet, used to find the events an
+    /// import no longer lists.
+    async fn get_unended_event_ids(&self, group_id: &str, id_prefix: &str) -> Result<Vec<String>, DatabaseError>;
+    /// Creates the event and returns the events of its group it overlaps. With `reject_conflicts`, overlapping
+    /// any fails with [`DatabaseError::ScheduleConflict`] instead.
+    async fn insert_event(
+        &self,
+        create_event: CreateEvent,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<(CreatedEvent, Vec<ConflictingEvent>), DatabaseError>;
+    /// Creates or updates each event by `vrc_event_id` in a single transaction. A constraint violation only fails
+    /// its own event, which is reported in the returned list in the same order as `events`. Events that are written
+    /// are checked for overlaps like in [`EventModel::insert_event`], where a rejected conflict also fails only its
+    /// own event.
+    async fn upsert_events(
+        &self,
+        events: Vec<CreateEvent>,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<Vec<Result<(UpsertOutcome, Vec<ConflictingEvent>), DatabaseError>>, DatabaseError>;
+    /// Returns `None` if the event does not exist, or if `expected_version` is given and does not match. Overlaps
+    /// with other events of the group are handled like in [`EventModel::insert_event`].
+    async fn update_event(
+        &self,
+        id: &str,
+        create_event: CreateEvent,
+        expected_version: Option<i32>,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<Option<(Event, Vec<ConflictingEvent>)>, DatabaseError>;
+    /// Soft deletes the event. Returns `false` if the event does not exist, or if `expected_version` is given and
+    /// does not match.
+    async fn delete_event(
+        &self,
+        id: &str,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<bool, DatabaseError>;
+    /// Undoes [`EventModel::delete_event`]. Returns `None` if the event does not exist or is not deleted.
+    async fn restore_event(&self, id: &str, audit: &AuditContext) -> Result<Option<Event>, DatabaseError>;
+    /// Permanently deletes events that were soft deleted before `deleted_before`.
+    async fn purge_deleted_events(&self, deleted_before: OffsetDateTime) -> Result<u64, DatabaseError>;
 }
 
 #[async_trait]
@@ -66,7 +387,7 @@ impl EventModel for PostgresDatabase {
     async fn get_all_events(&self) -> Result<Arc<[Event]>, DatabaseError> {
         if self.dirty.swap(false, Ordering::Acquire) {
             let mut cache = self.event_cache.write().await;
-            *cache = sqlx::query_as!(Event, "SELECT * FROM events")
+            *cache = sqlx::query_as!(Event, "SELECT * FROM events WHERE deleted_at IS NULL")
                 .fetch_all(&self.pool)
                 .await?
                 .into_boxed_slice()
@@ -82,101 +403,535 @@ impl EventModel for PostgresDatabase {
         }
     }
 
-    async fn query_events(&self, query: HashMap<String, String>) -> Result<Arc<[Event]>, DatabaseError> {
-        let mut query_builder = QueryBuilder::new("SELECT * FROM events WHERE 1=1");
-
-        if let Some(starts_at_str) = query.get("starts_at") {
-            let starts_at = OffsetDateTime::parse(starts_at_str, &Rfc3339)
-                .map_err(|e| sqlx::Error::InvalidArgument(e.to_string()))?;
-            // .with_timezone(&Utc);
-            query_builder.push(" AND starts_at >= ");
-            query_builder.push_bind(starts_at);
-        }
-
-        if let Some(ends_at_str) = query.get("ends_at") {
-            let ends_at = OffsetDateTime::parse(ends_at_str, &Rfc3339)
-                .map_err(|e| sqlx::Error::InvalidArgument(e.to_string()))?;
-            query_builder.push(" AND ends_at <= ");
-            query_builder.push_bind(ends_at);
-        }
-
-        if let Some(group_id) = query.get("group_id") {
-            query_builder.push(" AND vrc_group_id = ");
-            query_builder.push_bind(group_id);
-        }
+    async fn query_events(&self, filters: &EventFilters) -> Result<Arc<[Event]>, DatabaseError> {
+        let mut query_builder = QueryBuilder::new("SELECT * FROM events WHERE deleted_at IS NULL");
+        push_event_filters(&mut query_builder, filters);
 
         let query = query_builder.build_query_as::<Event>().fetch_all(&self.pool).await?;
         Ok(query.into_boxed_slice().into())
     }
 
     async fn get_event(&self, id: &str) -> Result<Option<Event>, DatabaseError> {
-        let event = sqlx::query_as!(Event, "SELECT * FROM events WHERE vrc_event_id = $1", id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let event = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE vrc_event_id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
         Ok(event)
     }
 
-    async fn insert_event(&self, create_event: CreateEvent) -> Result<CreatedEvent, DatabaseError> {
+    async fn get_attending_events(&self, user_id: &str) -> Result<Vec<AttendingEvent>, DatabaseError> {
+        let events = sqlx::query_as::<_, AttendingEvent>(
+            r"SELECT e.*, a.status AS rsvp_status
+            FROM events e JOIN event_attendees a ON a.vrc_event_id = e.vrc_event_id
+            WHERE a.user_id = $1 AND e.deleted_at IS NULL
+            ORDER BY e.starts_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_followed_events(&self, user_id: &str, limit: i64, offset: i64) -> Result<EventPage, DatabaseError> {
+        // fetch one extra row to learn whether there is another page
+        let mut events = sqlx::query_as!(
+            Event,
+            r#"SELECT e.* FROM events e
+            JOIN group_follows f ON f.vrc_group_id = e.vrc_group_id
+            WHERE f.user_id = $1 AND e.ends_at > now() AND e.deleted_at IS NULL
+            ORDER BY e.starts_at, e.vrc_event_id
+            LIMIT $2 OFFSET $3"#,
+            user_id,
+            limit + 1,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = events.len() > usize::try_from(limit).unwrap_or_default();
+        events.truncate(usize::try_from(limit).unwrap_or_default());
+
+        Ok(EventPage {
+            events,
+            limit,
+            offset,
+            next_offset: has_more.then(|| offset.saturating_add(limit)),
+        })
+    }
+
+    async fn get_feed_events(&self, user_id: &str) -> Result<Vec<Event>, DatabaseError> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"SELECT * FROM events e
+            WHERE
+              e.deleted_at IS NULL
+              AND (
+                e.vrc_event_id IN (SELECT vrc_event_id FROM event_attendees WHERE user_id = $1)
+                OR (
+                  e.vrc_group_id IN (SELECT vrc_group_id FROM group_follows WHERE user_id = $1)
+                  AND e.ends_at > now() - interval '30 days'
+                )
+              )
+            ORDER BY e.starts_at"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_current_events(&self) -> Result<Vec<Event>, DatabaseError> {
+        let events = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE during @> now() AND deleted_at IS NULL ORDER BY ends_at, vrc_event_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_upcoming_events(&self, within: Duration, limit: i64) -> Result<Vec<Event>, DatabaseError> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"SELECT * FROM events
+            WHERE
+              starts_at >= now() AND starts_at <= now() + make_interval(secs => $1)
+              AND deleted_at IS NULL
+            ORDER BY starts_at, vrc_event_id
+            LIMIT $2"#,
+            within.as_seconds_f64(),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_unended_event_ids(&self, group_id: &str, id_prefix: &str) -> Result<Vec<String>, DatabaseError> {
+        let ids = sqlx::query_scalar!(
+            r#"SELECT vrc_event_id FROM events
+            WHERE
+              vrc_group_id = $1 AND starts_with(vrc_event_id, $2) AND ends_at > now() AND deleted_at IS NULL"#,
+            group_id,
+            id_prefix
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn insert_event(
+        &self,
+        create_event: CreateEvent,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<(CreatedEvent, Vec<ConflictingEvent>), DatabaseError> {
         self.dirty.swap(true, Ordering::Acquire);
 
-        sqlx::query!(
+        let mut tx = self.pool.begin().await?;
+
+        lock_group_schedule(&mut tx, &create_event.vrc_group_id).await?;
+        check_group_not_deleted(&mut tx, &create_event.vrc_group_id).await?;
+        let conflicts = check_conflicts(&mut tx, &create_event, None, reject_conflicts).await?;
+        let tags = canonicalize_tags(&mut tx, create_event.tags.as_deref()).await?;
+        let event = sqlx::query_as!(
+            Event,
             r#"INSERT INTO events
               (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms, image_url, tags)
             VALUES
-              ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+              ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *"#,
             create_event.vrc_event_id,
             create_event.vrc_group_id,
-            create_event.name,
+            create_event.name.trim(),
             create_event.description,
             create_event.starts_at,
             create_event.ends_at,
-            create_event.category,
-            create_event.access_type,
-            &create_event.platforms,
+            create_event.category.as_str(),
+            create_event.access_type.as_str(),
+            &platform_names(&create_event.platforms),
             create_event.image_url,
-            create_event.tags.as_deref(),
+            tags.as_deref(),
         )
-        .execute(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(CreatedEvent {
-            vrc_event_id: create_event.vrc_event_id,
-        })
+        schedule_event_reminder(&mut tx, &event.vrc_event_id, event.starts_at).await?;
+        record_audit(
+            &mut tx,
+            audit,
+            "event",
+            &event.vrc_event_id,
+            AuditAction::Create,
+            None::<&Event>,
+            Some(&event),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok((
+            CreatedEvent {
+                vrc_event_id: event.vrc_event_id,
+            },
+            conflicts,
+        ))
     }
 
-    async fn update_event(&self, id: &str, create_event: CreateEvent) -> Result<(), DatabaseError> {
+    async fn upsert_events(
+        &self,
+        events: Vec<CreateEvent>,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<Vec<Result<(UpsertOutcome, Vec<ConflictingEvent>), DatabaseError>>, DatabaseError> {
         self.dirty.swap(true, Ordering::Acquire);
 
-        sqlx::query!(
+        let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(events.len());
+
+        // in a fixed order, so that two batches cannot each wait for a group the other has locked
+        let group_ids: BTreeSet<&str> = events.iter().map(|event| event.vrc_group_id.as_str()).collect();
+        for group_id in group_ids {
+            lock_group_schedule(&mut tx, group_id).await?;
+        }
+
+        for create_event in events {
+            // each event gets a savepoint, so an event the database rejects is rolled back without aborting the
+            // rest. anything else, e.g. a lost connection, rolls back the whole batch
+            let mut savepoint = tx.begin().await?;
+            match upsert_event(&mut savepoint, &create_event, reject_conflicts, audit).await {
+                Ok(outcome) => {
+                    savepoint.commit().await?;
+                    outcomes.push(Ok(outcome));
+                }
+                Err(
+                    e @ (DatabaseError::UniqueViolation(_)
+                    | DatabaseError::ForeignKeyViolation(_)
+                    | DatabaseError::CheckViolation(_)
+                    | DatabaseError::InvalidData(_)
+                    | DatabaseError::GroupDeleted
+                    | DatabaseError::ScheduleConflict(_)
+                    | DatabaseError::SqlxError(sqlx::Error::Database(_))),
+                ) => {
+                    savepoint.rollback().await?;
+                    outcomes.push(Err(e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(outcomes)
+    }
+
+    async fn update_event(
+        &self,
+        id: &str,
+        create_event: CreateEvent,
+        expected_version: Option<i32>,
+        reject_conflicts: bool,
+        audit: &AuditContext,
+    ) -> Result<Option<(Event, Vec<ConflictingEvent>)>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+
+        // an event never changes its group, so the group can be locked before the event
+        let Some(group_id) = sqlx::query_scalar!("SELECT vrc_group_id FROM events WHERE vrc_event_id = $1", id)
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+        lock_group_schedule(&mut tx, &group_id).await?;
+
+        let Some(before) = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE vrc_event_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let conflicts = check_conflicts(&mut tx, &create_event, Some(id), reject_conflicts).await?;
+        let tags = canonicalize_tags(&mut tx, create_event.tags.as_deref()).await?;
+        let event = sqlx::query_as!(
+            Event,
             r#"UPDATE events SET
-              name = $2, description = $3, starts_at = $4, ends_at = $5, category = $6, access_type = $7, platforms = $8, image_url = $9, tags = $10
+              name = $2, description = $3, starts_at = $4, ends_at = $5, category = $6, access_type = $7, platforms = $8, image_url = $9, tags = $10,
+              version = version + 1, updated_at = now()
             WHERE
-              vrc_event_id = $1"#,
+              vrc_event_id = $1 AND ($11::int IS NULL OR version = $11)
+            RETURNING *"#,
             id,
-            create_event.name,
+            create_event.name.trim(),
             create_event.description,
             create_event.starts_at,
             create_event.ends_at,
-            create_event.category,
-            create_event.access_type,
-            &create_event.platforms,
+            create_event.category.as_str(),
+            create_event.access_type.as_str(),
+            &platform_names(&create_event.platforms),
             create_event.image_url,
-            create_event.tags.as_deref(),
+            tags.as_deref(),
+            expected_version,
         )
-        .execute(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        Ok(())
+        if let Some(event) = &event {
+            schedule_event_reminder(&mut tx, id, event.starts_at).await?;
+            record_audit(
+                &mut tx,
+                audit,
+                "event",
+                id,
+                AuditAction::Update,
+                Some(&before),
+                Some(event),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(event.map(|event| (event, conflicts)))
     }
 
-    async fn delete_event(&self, id: &str) -> Result<(), DatabaseError> {
+    async fn delete_event(
+        &self,
+        id: &str,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<bool, DatabaseError> {
         self.dirty.swap(true, Ordering::Acquire);
 
-        sqlx::query!("DELETE FROM events WHERE vrc_event_id = $1", id)
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE vrc_event_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        let deleted = sqlx::query!(
+            r#"UPDATE events SET
+              deleted_at = now(), version = version + 1, updated_at = now()
+            WHERE
+              vrc_event_id = $1 AND ($2::int IS NULL OR version = $2)"#,
+            id,
+            expected_version
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if deleted.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        cancel_event_reminder(&mut tx, id).await?;
+        record_audit(
+            &mut tx,
+            audit,
+            "event",
+            id,
+            AuditAction::Delete,
+            Some(&before),
+            None::<&Event>,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn restore_event(&self, id: &str, audit: &AuditContext) -> Result<Option<Event>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(group_id) = sqlx::query_scalar!("SELECT vrc_group_id FROM events WHERE vrc_event_id = $1", id)
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+        lock_group_schedule(&mut tx, &group_id).await?;
+
+        let event = sqlx::query_as!(
+            Event,
+            r#"UPDATE events SET
+              deleted_at = NULL, version = version + 1, updated_at = now()
+            WHERE
+              vrc_event_id = $1 AND deleted_at IS NOT NULL
+            RETURNING *"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(event) = event else {
+            return Ok(None);
+        };
+
+        check_group_not_deleted(&mut tx, &event.vrc_group_id).await?;
+        schedule_event_reminder(&mut tx, id, event.starts_at).await?;
+        record_audit(
+            &mut tx,
+            audit,
+            "event",
+            id,
+            AuditAction::Restore,
+            None::<&Event>,
+            Some(&event),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(event))
+    }
+
+    async fn purge_deleted_events(&self, deleted_before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        let purged = sqlx::query!("DELETE FROM events WHERE deleted_at < $1", deleted_before)
             .execute(&self.pool)
             .await?;
 
-        Ok(())
+        Ok(purged.rows_affected())
+    }
+}
+
+/// Finds the group's events that `create_event` overlaps, other than `exclude_event_id`. Call it after
+/// [`lock_group_schedule`], so that the answer holds until the transaction commits.
+///
+/// # Errors
+///
+/// Returns [`DatabaseError::ScheduleConflict`] if there are overlaps and `reject` is set.
+async fn check_conflicts(
+    conn: &mut PgConnection,
+    create_event: &CreateEvent,
+    exclude_event_id: Option<&str>,
+    reject: bool,
+) -> Result<Vec<ConflictingEvent>, DatabaseError> {
+    let conflicts = overlapping_events(
+        conn,
+        &create_event.vrc_group_id,
+        create_event.starts_at,
+        create_event.ends_at,
+        exclude_event_id,
+    )
+    .await?;
+
+    if reject && !conflicts.is_empty() {
+        return Err(DatabaseError::ScheduleConflict(conflicts));
+    }
+
+    Ok(conflicts)
+}
+
+async fn upsert_event(
+    conn: &mut PgConnection,
+    create_event: &CreateEvent,
+    reject_conflicts: bool,
+    audit: &AuditContext,
+) -> Result<(UpsertOutcome, Vec<ConflictingEvent>), DatabaseError> {
+    let before = sqlx::query_as!(
+        Event,
+        "SELECT * FROM events WHERE vrc_event_id = $1 FOR UPDATE",
+        create_event.vrc_event_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match &before {
+        Some(before) if before.deleted_at.is_some() => return Ok((UpsertOutcome::Deleted, Vec::new())),
+        Some(before) if before.vrc_group_id != create_event.vrc_group_id => {
+            return Ok((UpsertOutcome::GroupMismatch, Vec::new()));
+        }
+        Some(_) => {}
+        None => check_group_not_deleted(conn, &create_event.vrc_group_id).await?,
     }
+
+    let tags = canonicalize_tags(conn, create_event.tags.as_deref()).await?;
+
+    let event = sqlx::query_as!(
+        Event,
+        r#"INSERT INTO events
+          (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms, image_url, tags)
+        VALUES
+          ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (vrc_event_id) DO UPDATE SET
+          name = excluded.name, description = excluded.description, starts_at = excluded.starts_at,
+          ends_at = excluded.ends_at, category = excluded.category, access_type = excluded.access_type,
+          platforms = excluded.platforms, image_url = excluded.image_url, tags = excluded.tags,
+          version = events.version + 1, updated_at = now()
+        WHERE
+          (events.name, events.description, events.starts_at, events.ends_at, events.category, events.access_type,
+           events.platforms, events.image_url, events.tags)
+          IS DISTINCT FROM
+          (excluded.name, excluded.description, excluded.starts_at, excluded.ends_at, excluded.category,
+           excluded.access_type, excluded.platforms, excluded.image_url, excluded.tags)
+        RETURNING *"#,
+        create_event.vrc_event_id,
+        create_event.vrc_group_id,
+        create_event.name.trim(),
+        create_event.description,
+        create_event.starts_at,
+        create_event.ends_at,
+        create_event.category.as_str(),
+        create_event.access_type.as_str(),
+        &platform_names(&create_event.platforms),
+        create_event.image_url,
+        tags.as_deref(),
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    // the conflicting row is left alone when nothing changed
+    let Some(event) = event else {
+        return Ok((UpsertOutcome::Unchanged, Vec::new()));
+    };
+
+    // only once the event was written, so that an unchanged event is not failed for overlaps it already had. the
+    // event itself is left out, and so is its old time if it moved
+    let conflicts = check_conflicts(conn, create_event, Some(&event.vrc_event_id), reject_conflicts).await?;
+    schedule_event_reminder(conn, &event.vrc_event_id, event.starts_at).await?;
+
+    let action = if before.is_some() {
+        AuditAction::Update
+    } else {
+        AuditAction::Create
+    };
+    record_audit(
+        conn,
+        audit,
+        "event",
+        &event.vrc_event_id,
+        action,
+        before.as_ref(),
+        Some(&event),
+    )
+    .await?;
+
+    let outcome = if before.is_some() {
+        UpsertOutcome::Updated
+    } else {
+        UpsertOutcome::Created
+    };
+
+    Ok((outcome, conflicts))
 }
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::{
+    database::{DatabaseError, PostgresDatabase},
+    validation::{Validate, ValidationErrors},
+};
+
+/// An external iCalendar feed whose events are imported into a group.
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct IcalFeed {
+    pub vrc_group_id: String,
+    pub url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_synced_at: Option<OffsetDateTime>,
+    /// Upcoming events the feed had on the last successful import.
+    pub event_count: i32,
+    /// Why the last import failed, cleared once one succeeds again.
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_error_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PutIcalFeed {
+    /// An `http`, `https` or `webcal` address of an `.ics` file on a public host.
+    pub url: String,
+}
+
+impl Validate for PutIcalFeed {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        // calendar apps hand out webcal:// links, which are fetched over https
+        match self.url.strip_prefix("webcal://") {
+            Some(rest) => errors.check_url("url", &format!("https://{rest}"), &[]),
+            None => errors.check_url("url", &self.url, &[]),
+        }
+        errors.into_result()
+    }
+}
+
+#[async_trait]
+pub trait FeedModel {
+    /// Feeds of groups that are not deleted.
+    async fn get_feeds(&self) -> Result<Vec<IcalFeed>, DatabaseError>;
+    async fn get_feed(&self, group_id: &str) -> Result<Option<IcalFeed>, DatabaseError>;
+    /// Registers the group's feed, or changes its URL.
+    async fn put_feed(&self, group_id: &str, put_feed: PutIcalFeed) -> Result<IcalFeed, DatabaseError>;
+    /// Stops importing the group's feed. Events already imported are kept.
+    async fn delete_feed(&self, group_id: &str) -> Result<bool, DatabaseError>;
+    /// Records a successful import of `event_count` upcoming events.
+    async fn record_feed_sync(&self, group_id: &str, event_count: i32) -> Result<(), DatabaseError>;
+    async fn record_feed_error(&self, group_id: &str, error: &str) -> Result<(), DatabaseError>;
+}
+
+#[async_trait]
+impl FeedModel for PostgresDatabase {
+    async fn get_feeds(&self) -> Result<Vec<IcalFeed>, DatabaseError> {
+        let feeds = sqlx::query_as!(
+            IcalFeed,
+            r#"SELECT f.* FROM ical_feeds f
+            JOIN groups g ON g.vrc_group_id = f.vrc_group_id
+            WHERE g.deleted_at IS NULL
+            ORDER BY f.vrc_group_id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(feeds)
+    }
+
+    async fn get_feed(&self, group_id: &str) -> Result<Option<IcalFeed>, DatabaseError> {
+        let feed = sqlx::query_as!(IcalFeed, "SELECT * FROM ical_feeds WHERE vrc_group_id = $1", group_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(feed)
+    }
+
+    async fn put_feed(&self, group_id: &str, put_feed: PutIcalFeed) -> Result<IcalFeed, DatabaseError> {
+        // a new URL starts over, so the previous feed's status doesn't linger
+        let feed = sqlx::query_as!(
+            IcalFeed,
+            r#"INSERT INTO ical_feeds (vrc_group_id, url) VALUES ($1, $2)
+            ON CONFLICT (vrc_group_id) DO UPDATE SET
+              url = excluded.url, last_synced_at = NULL, event_count = 0, last_error = NULL, last_error_at = NULL
+            WHERE
+              ical_feeds.url <> excluded.url
+            RETURNING *"#,
+            group_id,
+            put_feed.url
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match feed {
+            Some(feed) => Ok(feed),
+            None => self
+                .get_feed(group_id)
+                .await?
+                .ok_or(DatabaseError::SqlxError(sqlx::Error::RowNotFound)),
+        }
+    }
+
+    async fn delete_feed(&self, group_id: &str) -> Result<bool, DatabaseError> {
+        let deleted = sqlx::query!("DELETE FROM ical_feeds WHERE vrc_group_id = $1", group_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn record_feed_sync(&self, group_id: &str, event_count: i32) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE ical_feeds SET
+              last_synced_at = now(), event_count = $2, last_error = NULL, last_error_at = NULL
+            WHERE
+              vrc_group_id = $1"#,
+            group_id,
+            event_count
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_feed_error(&self, group_id: &str, error: &str) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "UPDATE ical_feeds SET last_error = $2, last_error_at = now() WHERE vrc_group_id = $1",
+            group_id,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+
+use crate::database::{DatabaseError, Group, PostgresDatabase};
+
+#[async_trait]
+pub trait FollowModel {
+    /// Returns `false` if the group does not exist.
+    async fn follow_group(&self, user_id: &str, vrc_group_id: &str) -> Result<bool, DatabaseError>;
+    async fn unfollow_group(&self, user_id: &str, vrc_group_id: &str) -> Result<(), DatabaseError>;
+    async fn get_followed_groups(&self, user_id: &str) -> Result<Vec<Group>, DatabaseError>;
+}
+
+#[async_trait]
+impl FollowModel for PostgresDatabase {
+    async fn follow_group(&self, user_id: &str, vrc_group_id: &str) -> Result<bool, DatabaseError> {
+        let group_exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NULL) AS "exists!""#,
+            vrc_group_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !group_exists {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "INSERT INTO group_follows (user_id, vrc_group_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            user_id,
+            vrc_group_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(true)
+    }
+
+    async fn unfollow_group(&self, user_id: &str, vrc_group_id: &str) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "DELETE FROM group_follows WHERE user_id = $1 AND vrc_group_id = $2",
+            user_id,
+            vrc_group_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_followed_groups(&self, user_id: &str) -> Result<Vec<Group>, DatabaseError> {
+        let groups = sqlx::query_as!(
+            Group,
+            r#"SELECT g.* FROM groups g
+            JOIN group_follows f ON f.vrc_group_id = g.vrc_group_id
+            WHERE f.user_id = $1 AND g.deleted_at IS NULL
+            ORDER BY g.name"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(groups)
+    }
+}
@@ -1,102 +1,628 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::atomic::Ordering};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use sqlx::{QueryBuilder, prelude::FromRow};
+use sqlx::{PgConnection, QueryBuilder, prelude::FromRow};
 use time::OffsetDateTime;
+use utoipa::ToSchema;
 
-use crate::database::{DatabaseError, PostgresDatabase};
+use crate::{
+    database::{AuditAction, AuditContext, DatabaseError, Event, PostgresDatabase},
+    validation::{Validate, ValidationErrors},
+};
 
-#[derive(Serialize, FromRow)]
+use super::{
+    audit::record_audit,
+    conflict::lock_group_schedule,
+    job::{cancel_group_event_reminders, schedule_event_reminder},
+};
+
+#[derive(Serialize, FromRow, ToSchema)]
 pub struct Group {
     pub vrc_group_id: String,
     pub name: String,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    pub description: Option<String>,
+    pub icon_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub short_code: Option<String>,
+    pub discord_invite_url: Option<String>,
+    pub website_url: Option<String>,
+    pub timezone: Option<String>,
+    pub language: Option<String>,
+    pub visibility: String,
+    /// Incremented on every change and exposed as the `ETag`.
+    pub version: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// Set while the group is soft deleted. Deleted groups are left out of every read.
+    #[serde(with = "time::serde::rfc3339::option", skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+/// Unlisted and private groups are left out of group listings, but can still be fetched by ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupVisibility {
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl GroupVisibility {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// The optional profile fields shared by [`CreateGroup`] and [`UpdateGroup`].
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct GroupProfile {
+    pub description: Option<String>,
+    pub icon_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub short_code: Option<String>,
+    pub discord_invite_url: Option<String>,
+    pub website_url: Option<String>,
+    pub timezone: Option<String>,
+    pub language: Option<String>,
+    pub visibility: Option<GroupVisibility>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateGroup {
     pub vrc_group_id: String,
     pub name: String,
+    #[serde(flatten)]
+    pub profile: GroupProfile,
+}
+
+/// A partial update: fields that are absent or `null` are left unchanged, so it cannot clear a profile field. A
+/// merge patch setting the field to `null` does that instead.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateGroup {
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub profile: GroupProfile,
+}
+
+const MAX_GROUP_NAME_LENGTH: usize = 100;
+const MAX_GROUP_DESCRIPTION_LENGTH: usize = 4000;
+
+impl GroupProfile {
+    fn check(&self, errors: &mut ValidationErrors) {
+        if let Some(description) = &self.description {
+            errors.check_length("description", description, 0, MAX_GROUP_DESCRIPTION_LENGTH);
+        }
+        if let Some(icon_url) = &self.icon_url {
+            errors.check_url("icon_url", icon_url, &[]);
+        }
+        if let Some(banner_url) = &self.banner_url {
+            errors.check_url("banner_url", banner_url, &[]);
+        }
+        if let Some(short_code) = &self.short_code
+            && !((2..=16).contains(&short_code.len())
+                && short_code.chars().all(|c| c.is_ascii_alphanumeric() || c == '.'))
+        {
+            errors.add("short_code", "must be 2 to 16 letters, digits or '.'");
+        }
+        if let Some(discord_invite_url) = &self.discord_invite_url {
+            errors.check_url(
+                "discord_invite_url",
+                discord_invite_url,
+                &["discord.gg", "discord.com", "www.discord.com"],
+            );
+        }
+        if let Some(website_url) = &self.website_url {
+            errors.check_url("website_url", website_url, &[]);
+        }
+        if let Some(timezone) = &self.timezone {
+            errors.check_timezone("timezone", timezone);
+        }
+        if let Some(language) = &self.language {
+            errors.check_language("language", language);
+        }
+    }
+}
+
+impl Validate for CreateGroup {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.check_length("vrc_group_id", &self.vrc_group_id, 1, 64);
+        errors.check_length("name", &self.name, 1, MAX_GROUP_NAME_LENGTH);
+        self.profile.check(&mut errors);
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateGroup {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some(name) = &self.name {
+            errors.check_length("name", name, 1, MAX_GROUP_NAME_LENGTH);
+        }
+        self.profile.check(&mut errors);
+        errors.into_result()
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CreatedGroup {
     pub group_id: String,
 }
 
+/// What [`GroupModel::delete_group`] did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupDeletion {
+    Deleted,
+    /// The group does not exist, or `expected_version` did not match.
+    NotFound,
+    /// The group still has events and `cascade` was not set. Holds the IDs of those events.
+    HasEvents(Vec<String>),
+}
+
 #[async_trait]
 pub trait GroupModel {
     async fn get_all_groups(&self) -> Result<Vec<Group>, DatabaseError>;
     async fn query_groups(&self, query: HashMap<String, String>) -> Result<Vec<Group>, DatabaseError>;
     async fn get_group(&self, id: &str) -> Result<Option<Group>, DatabaseError>;
-    async fn insert_group(&self, create_group: CreateGroup) -> Result<CreatedGroup, DatabaseError>;
-    async fn update_group(&self, id: &str, create_group: CreateGroup) -> Result<(), DatabaseError>;
-    async fn delete_group(&self, id: &str) -> Result<(), DatabaseError>;
+    async fn insert_group(
+        &self,
+        create_group: CreateGroup,
+        audit: &AuditContext,
+    ) -> Result<CreatedGroup, DatabaseError>;
+    /// Only changes the fields that are set, see [`UpdateGroup`]. Returns `None` if the group does not exist, or if
+    /// `expected_version` is given and does not match.
+    async fn update_group(
+        &self,
+        id: &str,
+        update_group: UpdateGroup,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<Option<Group>, DatabaseError>;
+    /// Overwrites every mutable field, clearing profile fields that are `None`. Returns `None` if the group does
+    /// not exist, or if `expected_version` is given and does not match.
+    async fn replace_group(
+        &self,
+        id: &str,
+        create_group: CreateGroup,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<Option<Group>, DatabaseError>;
+    /// Soft deletes the group, along with its events if `cascade` is set. A group that still has events is left
+    /// alone unless `cascade` is set, which is only checked once `expected_version` matched.
+    async fn delete_group(
+        &self,
+        id: &str,
+        expected_version: Option<i32>,
+        cascade: bool,
+        audit: &AuditContext,
+    ) -> Result<GroupDeletion, DatabaseError>;
+    /// Undoes [`GroupModel::delete_group`], restoring the events that were deleted with the group. Returns `None` if
+    /// the group does not exist or is not deleted.
+    async fn restore_group(&self, id: &str, audit: &AuditContext) -> Result<Option<Group>, DatabaseError>;
+    /// Permanently deletes groups that were soft deleted before `deleted_before`, along with their events.
+    async fn purge_deleted_groups(&self, deleted_before: OffsetDateTime) -> Result<u64, DatabaseError>;
 }
 
 #[async_trait]
 impl GroupModel for PostgresDatabase {
     async fn get_all_groups(&self) -> Result<Vec<Group>, DatabaseError> {
-        let groups = sqlx::query_as!(Group, "SELECT * FROM groups")
-            .fetch_all(&self.pool)
-            .await?;
+        let groups = sqlx::query_as!(
+            Group,
+            "SELECT * FROM groups WHERE visibility = 'public' AND deleted_at IS NULL ORDER BY name"
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(groups)
     }
 
     async fn query_groups(&self, query: HashMap<String, String>) -> Result<Vec<Group>, DatabaseError> {
-        let mut query_builder = QueryBuilder::new("SELECT * FROM groups WHERE 1=1");
+        let mut query_builder =
+            QueryBuilder::new("SELECT * FROM groups WHERE visibility = 'public' AND deleted_at IS NULL");
 
         if let Some(name) = query.get("name") {
             query_builder.push(" AND name = ");
             query_builder.push_bind(name);
         }
 
+        if let Some(short_code) = query.get("short_code") {
+            query_builder.push(" AND short_code = ");
+            query_builder.push_bind(short_code);
+        }
+
+        query_builder.push(" ORDER BY name");
+
         let query = query_builder.build_query_as::<Group>();
         Ok(query.fetch_all(&self.pool).await?)
     }
 
     async fn get_group(&self, id: &str) -> Result<Option<Group>, DatabaseError> {
-        let group = sqlx::query_as!(Group, "SELECT * FROM groups WHERE vrc_group_id = $1", id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let group = sqlx::query_as!(
+            Group,
+            "SELECT * FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
         Ok(group)
     }
 
-    async fn insert_group(&self, create_group: CreateGroup) -> Result<CreatedGroup, DatabaseError> {
-        sqlx::query!(
-            "INSERT INTO groups (vrc_group_id, name) VALUES ($1, $2)",
+    async fn insert_group(
+        &self,
+        create_group: CreateGroup,
+        audit: &AuditContext,
+    ) -> Result<CreatedGroup, DatabaseError> {
+        let profile = create_group.profile;
+
+        let mut tx = self.pool.begin().await?;
+
+        let group = sqlx::query_as!(
+            Group,
+            r#"INSERT INTO groups
+              (vrc_group_id, name, description, icon_url, banner_url, short_code, discord_invite_url, website_url, timezone, language, visibility)
+            VALUES
+              ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *"#,
             create_group.vrc_group_id,
-            create_group.name
+            create_group.name,
+            profile.description,
+            profile.icon_url,
+            profile.banner_url,
+            profile.short_code,
+            profile.discord_invite_url,
+            profile.website_url,
+            profile.timezone,
+            profile.language,
+            profile.visibility.unwrap_or(GroupVisibility::Public).as_str(),
         )
-        .execute(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        record_audit(
+            &mut tx,
+            audit,
+            "group",
+            &group.vrc_group_id,
+            AuditAction::Create,
+            None::<&Group>,
+            Some(&group),
+        )
+        .await?;
+        tx.commit().await?;
+
         Ok(CreatedGroup {
-            group_id: create_group.vrc_group_id,
+            group_id: group.vrc_group_id,
         })
     }
 
-    async fn update_group(&self, id: &str, create_group: CreateGroup) -> Result<(), DatabaseError> {
+    async fn update_group(
+        &self,
+        id: &str,
+        update_group: UpdateGroup,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<Option<Group>, DatabaseError> {
+        let profile = update_group.profile;
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = sqlx::query_as!(
+            Group,
+            "SELECT * FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let group = sqlx::query_as!(
+            Group,
+            r#"UPDATE groups SET
+              name = COALESCE($2, name),
+              description = COALESCE($3, description),
+              icon_url = COALESCE($4, icon_url),
+              banner_url = COALESCE($5, banner_url),
+              short_code = COALESCE($6, short_code),
+              discord_invite_url = COALESCE($7, discord_invite_url),
+              website_url = COALESCE($8, website_url),
+              timezone = COALESCE($9, timezone),
+              language = COALESCE($10, language),
+              visibility = COALESCE($11, visibility),
+              version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1 AND ($12::int IS NULL OR version = $12)
+            RETURNING *"#,
+            id,
+            update_group.name,
+            profile.description,
+            profile.icon_url,
+            profile.banner_url,
+            profile.short_code,
+            profile.discord_invite_url,
+            profile.website_url,
+            profile.timezone,
+            profile.language,
+            profile.visibility.map(GroupVisibility::as_str),
+            expected_version,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(group) = &group {
+            record_audit(
+                &mut tx,
+                audit,
+                "group",
+                id,
+                AuditAction::Update,
+                Some(&before),
+                Some(group),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(group)
+    }
+
+    async fn replace_group(
+        &self,
+        id: &str,
+        create_group: CreateGroup,
+        expected_version: Option<i32>,
+        audit: &AuditContext,
+    ) -> Result<Option<Group>, DatabaseError> {
+        let profile = create_group.profile;
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = sqlx::query_as!(
+            Group,
+            "SELECT * FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let group = sqlx::query_as!(
+            Group,
+            r#"UPDATE groups SET
+              name = $2, description = $3, icon_url = $4, banner_url = $5, short_code = $6, discord_invite_url = $7,
+              website_url = $8, timezone = $9, language = $10, visibility = $11, version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1 AND ($12::int IS NULL OR version = $12)
+            RETURNING *"#,
+            id,
+            create_group.name,
+            profile.description,
+            profile.icon_url,
+            profile.banner_url,
+            profile.short_code,
+            profile.discord_invite_url,
+            profile.website_url,
+            profile.timezone,
+            profile.language,
+            profile.visibility.unwrap_or(GroupVisibility::Public).as_str(),
+            expected_version,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(group) = &group {
+            record_audit(
+                &mut tx,
+                audit,
+                "group",
+                id,
+                AuditAction::Update,
+                Some(&before),
+                Some(group),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(group)
+    }
+
+    async fn delete_group(
+        &self,
+        id: &str,
+        expected_version: Option<i32>,
+        cascade: bool,
+        audit: &AuditContext,
+    ) -> Result<GroupDeletion, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+
+        let Some(group) = sqlx::query_as!(
+            Group,
+            "SELECT * FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(GroupDeletion::NotFound);
+        };
+        // a stale version fails before the events are looked at, so that it is not mistaken for a missing cascade
+        if expected_version.is_some_and(|version| version != group.version) {
+            return Ok(GroupDeletion::NotFound);
+        }
+
+        // locking the events also keeps new ones from being added until the group is deleted
+        let events = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE vrc_group_id = $1 AND deleted_at IS NULL ORDER BY starts_at, vrc_event_id FOR UPDATE",
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !cascade && !events.is_empty() {
+            return Ok(GroupDeletion::HasEvents(
+                events.into_iter().map(|event| event.vrc_event_id).collect(),
+            ));
+        }
+
+        // the row is locked and its version checked above
+        sqlx::query!(
+            r#"UPDATE groups SET
+              deleted_at = now(), version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1"#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // the events share the group's deleted_at, which is how restore_group finds them again
         sqlx::query!(
-            "UPDATE groups SET name = $2 WHERE vrc_group_id = $1",
+            r#"UPDATE events SET
+              deleted_at = now(), version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        cancel_group_event_reminders(&mut tx, id).await?;
+        for event in &events {
+            record_audit(
+                &mut tx,
+                audit,
+                "event",
+                &event.vrc_event_id,
+                AuditAction::Delete,
+                Some(event),
+                None::<&Event>,
+            )
+            .await?;
+        }
+        record_audit(
+            &mut tx,
+            audit,
+            "group",
             id,
-            create_group.name
+            AuditAction::Delete,
+            Some(&group),
+            None::<&Group>,
         )
-        .execute(&self.pool)
         .await?;
+        tx.commit().await?;
 
-        Ok(())
+        Ok(GroupDeletion::Deleted)
     }
 
-    async fn delete_group(&self, id: &str) -> Result<(), DatabaseError> {
-        sqlx::query!("DELETE FROM groups WHERE vrc_group_id = $1", id)
+    async fn restore_group(&self, id: &str, audit: &AuditContext) -> Result<Option<Group>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+
+        // restoring the group's events schedules them again
+        lock_group_schedule(&mut tx, id).await?;
+        let Some(deleted_at) = sqlx::query_scalar!(
+            r#"SELECT deleted_at AS "deleted_at!" FROM groups WHERE vrc_group_id = $1 AND deleted_at IS NOT NULL FOR UPDATE"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let group = sqlx::query_as!(
+            Group,
+            r#"UPDATE groups SET
+              deleted_at = NULL, version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1
+            RETURNING *"#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // events deleted on their own before the group stay deleted
+        let events = sqlx::query_as!(
+            Event,
+            r#"UPDATE events SET
+              deleted_at = NULL, version = version + 1, updated_at = now()
+            WHERE
+              vrc_group_id = $1 AND deleted_at = $2
+            RETURNING *"#,
+            id,
+            deleted_at
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        record_audit(
+            &mut tx,
+            audit,
+            "group",
+            id,
+            AuditAction::Restore,
+            None::<&Group>,
+            Some(&group),
+        )
+        .await?;
+        for event in &events {
+            schedule_event_reminder(&mut tx, &event.vrc_event_id, event.starts_at).await?;
+            record_audit(
+                &mut tx,
+                audit,
+                "event",
+                &event.vrc_event_id,
+                AuditAction::Restore,
+                None::<&Event>,
+                Some(event),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(Some(group))
+    }
+
+    async fn purge_deleted_groups(&self, deleted_before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        // events of a deleted group were deleted with it, so the cascade only removes deleted events
+        let purged = sqlx::query!("DELETE FROM groups WHERE deleted_at < $1", deleted_before)
             .execute(&self.pool)
             .await?;
 
+        Ok(purged.rows_affected())
+    }
+}
+
+/// Rejects events for a soft deleted group, which the foreign key still lets through.
+pub(super) async fn check_group_not_deleted(conn: &mut PgConnection, vrc_group_id: &str) -> Result<(), DatabaseError> {
+    let deleted = sqlx::query_scalar!(
+        r#"SELECT deleted_at IS NOT NULL AS "deleted!" FROM groups WHERE vrc_group_id = $1 FOR SHARE"#,
+        vrc_group_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    if deleted == Some(true) {
+        Err(DatabaseError::GroupDeleted)
+    } else {
         Ok(())
     }
 }
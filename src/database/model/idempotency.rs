@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, prelude::FromRow};
+use time::{Duration, OffsetDateTime};
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+/// How long a response is kept for replay. A key is free to be used for a new request afterwards.
+pub const IDEMPOTENCY_KEY_TTL: Duration = Duration::hours(24);
+/// How long a key stays claimed by a request that neither stored a response nor renewed its claim.
+///
+/// A retry after that takes the key over, since the request it was claimed for died without releasing it.
+pub const IDEMPOTENCY_KEY_LEASE: Duration = Duration::minutes(1);
+
+#[derive(FromRow)]
+pub struct IdempotencyRecord {
+    pub api_key: String,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub response_status: Option<i32>,
+    pub response_content_type: Option<String>,
+    pub response_location: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: OffsetDateTime,
+    /// Set while the request the key was claimed for is being handled.
+    pub locked_at: Option<OffsetDateTime>,
+    /// When the claim was last renewed, see [`IdempotencyModel::renew_idempotency_key`].
+    pub heartbeat_at: Option<OffsetDateTime>,
+}
+
+pub enum IdempotencyClaim {
+    /// The key is now claimed for this request. Storing or releasing it names the claim by its `locked_at`, so
+    /// that a request whose claim was taken over cannot touch the new one.
+    Claimed { locked_at: OffsetDateTime },
+    /// The key was already used, and its request is either still being handled or its response can be replayed.
+    Used(IdempotencyRecord),
+}
+
+/// The parts of a response that are replayed for a retried request.
+pub struct StoredResponse {
+    pub status: i32,
+    pub content_type: Option<String>,
+    pub location: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[async_trait]
+pub trait IdempotencyModel {
+    /// Claims `idempotency_key` for a new request. Returns the existing record instead if the key was already used
+    /// within [`IDEMPOTENCY_KEY_TTL`], unless its request has not renewed its claim within [`IDEMPOTENCY_KEY_LEASE`].
+    async fn claim_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyClaim, DatabaseError>;
+    async fn store_idempotent_response(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+        response: StoredResponse,
+    ) -> Result<(), DatabaseError>;
+    /// Keeps the claim of a request that is still being handled from being taken over.
+    async fn renew_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError>;
+    /// Frees a claimed key without storing a response, so the request can be retried.
+    async fn release_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError>;
+    async fn purge_idempotency_keys(&self) -> Result<u64, DatabaseError>;
+}
+
+#[async_trait]
+impl IdempotencyModel for PostgresDatabase {
+    async fn claim_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyClaim, DatabaseError> {
+        // a record released between the claim and the select is claimed on the next round
+        loop {
+            if let Some(locked_at) = try_claim(&self.pool, api_key, idempotency_key, request_hash).await? {
+                return Ok(IdempotencyClaim::Claimed { locked_at });
+            }
+
+            let record = sqlx::query_as!(
+                IdempotencyRecord,
+                "SELECT * FROM idempotency_keys WHERE api_key = $1 AND idempotency_key = $2",
+                api_key,
+                idempotency_key
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some(record) = record {
+                return Ok(IdempotencyClaim::Used(record));
+            }
+        }
+    }
+
+    async fn store_idempotent_response(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+        response: StoredResponse,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE idempotency_keys SET
+              response_status = $4, response_content_type = $5, response_location = $6, response_body = $7,
+              locked_at = NULL
+            WHERE
+              api_key = $1 AND idempotency_key = $2 AND locked_at = $3"#,
+            api_key,
+            idempotency_key,
+            locked_at,
+            response.status,
+            response.content_type,
+            response.location,
+            response.body,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn renew_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE idempotency_keys SET heartbeat_at = now()
+            WHERE api_key = $1 AND idempotency_key = $2 AND locked_at = $3"#,
+            api_key,
+            idempotency_key,
+            locked_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release_idempotency_key(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        locked_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE api_key = $1 AND idempotency_key = $2 AND locked_at = $3",
+            api_key,
+            idempotency_key,
+            locked_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_idempotency_keys(&self) -> Result<u64, DatabaseError> {
+        let deleted = sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE created_at < now() - make_interval(secs => $1)",
+            IDEMPOTENCY_KEY_TTL.as_seconds_f64()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(deleted.rows_affected())
+    }
+}
+
+/// Claims the key unless it is in use, returning the claim's `locked_at`. Expired records and abandoned claims are
+/// taken over as if they did not exist. Both are judged by the database's clock, which wrote the timestamps.
+async fn try_claim(
+    pool: &PgPool,
+    api_key: &str,
+    idempotency_key: &str,
+    request_hash: &str,
+) -> Result<Option<OffsetDateTime>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"INSERT INTO idempotency_keys
+          (api_key, idempotency_key, request_hash, locked_at, heartbeat_at)
+        VALUES
+          ($1, $2, $3, now(), now())
+        ON CONFLICT (api_key, idempotency_key) DO UPDATE SET
+          request_hash = excluded.request_hash, response_status = NULL, response_content_type = NULL,
+          response_location = NULL, response_body = NULL, created_at = now(), locked_at = now(),
+          heartbeat_at = now()
+        WHERE
+          idempotency_keys.created_at < now() - make_interval(secs => $4)
+          OR (
+            idempotency_keys.response_status IS NULL
+            AND COALESCE(idempotency_keys.heartbeat_at, idempotency_keys.locked_at) < now() - make_interval(secs => $5)
+          )
+        RETURNING locked_at AS "locked_at!""#,
+        api_key,
+        idempotency_key,
+        request_hash,
+        IDEMPOTENCY_KEY_TTL.as_seconds_f64(),
+        IDEMPOTENCY_KEY_LEASE.as_seconds_f64(),
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::{PgConnection, prelude::FromRow};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+pub const EVENT_REMINDER_JOB: &str = "event_reminder";
+pub const REAP_SESSIONS_JOB: &str = "reap_sessions";
+pub const PURGE_IDEMPOTENCY_KEYS_JOB: &str = "purge_idempotency_keys";
+pub const PURGE_DELETED_JOB: &str = "purge_deleted";
+pub const PURGE_FINISHED_JOBS_JOB: &str = "purge_finished_jobs";
+pub const ARCHIVE_EVENTS_JOB: &str = "archive_events";
+pub const SYNC_ICAL_FEEDS_JOB: &str = "sync_ical_feeds";
+pub const SYNC_ICAL_FEED_JOB: &str = "sync_ical_feed";
+pub const SYNC_VRCHAT_GROUPS_JOB: &str = "sync_vrchat_groups";
+pub const SYNC_VRCHAT_GROUP_JOB: &str = "sync_vrchat_group";
+
+/// How long before an event starts its reminder fires.
+pub const EVENT_REMINDER_LEAD: Duration = Duration::minutes(15);
+
+#[derive(Serialize, FromRow)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub dedupe_key: Option<String>,
+    pub payload: String,
+    pub status: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub run_at: OffsetDateTime,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub locked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug)]
+pub struct NewJob {
+    pub kind: &'static str,
+    /// Jobs sharing a dedupe key are collapsed into one row, which is rescheduled instead of duplicated.
+    pub dedupe_key: Option<String>,
+    pub payload: String,
+    pub run_at: OffsetDateTime,
+    pub max_attempts: i32,
+}
+
+#[async_trait]
+pub trait JobModel {
+    async fn schedule_job(&self, new_job: NewJob) -> Result<(), DatabaseError>;
+    /// Claims up to `limit` due jobs, including jobs whose lease ran out. Those are moved to the dead-letter
+    /// instead once they used up their attempts, so that a job crashing its worker is not retried forever.
+    async fn claim_due_jobs(&self, limit: i64, lease: Duration) -> Result<Vec<Job>, DatabaseError>;
+    /// Extends the lease of a job that is still running.
+    async fn renew_job_lease(&self, id: Uuid) -> Result<(), DatabaseError>;
+    /// Finishes a job claimed with `claimed_run_at`. A job that was scheduled again while it ran is due again at
+    /// its new `run_at` instead.
+    async fn complete_job(&self, id: Uuid, claimed_run_at: OffsetDateTime) -> Result<(), DatabaseError>;
+    async fn reschedule_job(&self, id: Uuid, run_at: OffsetDateTime) -> Result<(), DatabaseError>;
+    /// Records a failed attempt of a job claimed with `claimed_run_at`, which is retried at `retry_at` or moved to
+    /// the dead-letter once it used up its attempts. A job that was scheduled again while it ran starts over with
+    /// fresh attempts, at the later of its new `run_at` and `retry_at`.
+    async fn fail_job(
+        &self,
+        id: Uuid,
+        claimed_run_at: OffsetDateTime,
+        error: &str,
+        retry_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError>;
+    /// Deletes jobs that finished before `finished_before`. Dead jobs are kept for inspection.
+    async fn purge_finished_jobs(&self, finished_before: OffsetDateTime) -> Result<u64, DatabaseError>;
+}
+
+#[async_trait]
+impl JobModel for PostgresDatabase {
+    async fn schedule_job(&self, new_job: NewJob) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.acquire().await?;
+        upsert_job(&mut conn, &new_job).await?;
+        Ok(())
+    }
+
+    async fn claim_due_jobs(&self, limit: i64, lease: Duration) -> Result<Vec<Job>, DatabaseError> {
+        // jobs left 'running' past their lease belonged to a worker that died mid-job
+        let stale_before = OffsetDateTime::now_utc() - lease;
+
+        sqlx::query!(
+            r#"UPDATE jobs SET
+              status = 'dead', locked_at = NULL, last_error = 'abandoned by its worker on the last attempt'
+            WHERE
+              status = 'running' AND locked_at < $1 AND attempts >= max_attempts"#,
+            stale_before,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let jobs = sqlx::query_as!(
+            Job,
+            r#"UPDATE jobs SET
+              status = 'running', locked_at = now(), attempts = attempts + 1
+            WHERE id IN (
+              SELECT id FROM jobs
+              WHERE
+                (status = 'pending' AND run_at <= now())
+                OR (status = 'running' AND locked_at < $2 AND attempts < max_attempts)
+              ORDER BY run_at
+              LIMIT $1
+              FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *"#,
+            limit,
+            stale_before,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    async fn renew_job_lease(&self, id: Uuid) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            "UPDATE jobs SET locked_at = now() WHERE id = $1 AND status = 'running'",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn complete_job(&self, id: Uuid, claimed_run_at: OffsetDateTime) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE jobs SET
+              status = CASE WHEN run_at > $2 THEN 'pending' ELSE 'done' END,
+              attempts = CASE WHEN run_at > $2 THEN 0 ELSE attempts END,
+              locked_at = NULL, last_error = NULL
+            WHERE
+              id = $1 AND status = 'running'"#,
+            id,
+            claimed_run_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn reschedule_job(&self, id: Uuid, run_at: OffsetDateTime) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE jobs SET
+              status = 'pending', run_at = $2, attempts = 0, locked_at = NULL, last_error = NULL
+            WHERE
+              id = $1 AND status = 'running'"#,
+            id,
+            run_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fail_job(
+        &self,
+        id: Uuid,
+        claimed_run_at: OffsetDateTime,
+        error: &str,
+        retry_at: OffsetDateTime,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"UPDATE jobs SET
+              status = CASE WHEN run_at <= $2 AND attempts >= max_attempts THEN 'dead' ELSE 'pending' END,
+              attempts = CASE WHEN run_at > $2 THEN 0 ELSE attempts END,
+              run_at = GREATEST(run_at, $4), last_error = $3, locked_at = NULL
+            WHERE
+              id = $1 AND status = 'running'"#,
+            id,
+            claimed_run_at,
+            error,
+            retry_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_finished_jobs(&self, finished_before: OffsetDateTime) -> Result<u64, DatabaseError> {
+        // done reminders are what keeps an event update from sending them twice, a retention longer than the
+        // reminder lead only drops those of events that already started
+        let purged = sqlx::query!(
+            "DELETE FROM jobs WHERE status = 'done' AND run_at < $1",
+            finished_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(purged.rows_affected())
+    }
+}
+
+/// Inserts a job, or moves an existing job with the same dedupe key to the new `run_at`. A job whose `run_at`
+/// is unchanged is left alone so that finished reminders are not sent twice. A running job keeps running, so that
+/// no other worker claims it meanwhile, and runs again once it completes.
+async fn upsert_job(conn: &mut PgConnection, new_job: &NewJob) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO jobs
+          (kind, dedupe_key, payload, run_at, max_attempts)
+        VALUES
+          ($1, $2, $3, $4, $5)
+        ON CONFLICT (dedupe_key) DO UPDATE SET
+          payload = excluded.payload, run_at = excluded.run_at, max_attempts = excluded.max_attempts,
+          status = CASE WHEN jobs.status = 'running' THEN 'running' ELSE 'pending' END,
+          attempts = CASE WHEN jobs.status = 'running' THEN jobs.attempts ELSE 0 END,
+          last_error = CASE WHEN jobs.status = 'running' THEN jobs.last_error END,
+          locked_at = CASE WHEN jobs.status = 'running' THEN jobs.locked_at END
+        WHERE
+          jobs.run_at IS DISTINCT FROM excluded.run_at"#,
+        new_job.kind,
+        new_job.dedupe_key,
+        new_job.payload,
+        new_job.run_at,
+        new_job.max_attempts,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+fn event_reminder_key(vrc_event_id: &str) -> String {
+    format!("{EVENT_REMINDER_JOB}:{vrc_event_id}")
+}
+
+/// Schedules (or moves) the start reminder for an event. Events starting within the reminder lead are due
+/// immediately, and events that already started have any pending reminder cancelled.
+pub(super) async fn schedule_event_reminder(
+    conn: &mut PgConnection,
+    vrc_event_id: &str,
+    starts_at: OffsetDateTime,
+) -> Result<(), sqlx::Error> {
+    if starts_at <= OffsetDateTime::now_utc() {
+        return cancel_event_reminder(conn, vrc_event_id).await;
+    }
+
+    let new_job = NewJob {
+        kind: EVENT_REMINDER_JOB,
+        dedupe_key: Some(event_reminder_key(vrc_event_id)),
+        payload: serde_json::json!({ "vrc_event_id": vrc_event_id }).to_string(),
+        run_at: starts_at - EVENT_REMINDER_LEAD,
+        max_attempts: 5,
+    };
+
+    upsert_job(conn, &new_job).await
+}
+
+pub(super) async fn cancel_event_reminder(conn: &mut PgConnection, vrc_event_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM jobs WHERE dedupe_key = $1",
+        event_reminder_key(vrc_event_id)
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub(super) async fn cancel_group_event_reminders(
+    conn: &mut PgConnection,
+    vrc_group_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"DELETE FROM jobs WHERE dedupe_key IN (
+          SELECT $2::text || vrc_event_id FROM events WHERE vrc_group_id = $1
+        )"#,
+        vrc_group_id,
+        event_reminder_key("")
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
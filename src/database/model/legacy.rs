@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+/// How often one client called a deprecated route.
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct LegacyRouteUsage {
+    pub method: String,
+    /// The route as registered, e.g. `/api/event/{id}`.
+    pub route: String,
+    /// Empty if the client sent no `User-Agent`.
+    pub user_agent: String,
+    pub request_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub first_used_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_used_at: OffsetDateTime,
+}
+
+/// Calls of one deprecated route by one client that are not stored yet.
+#[derive(Clone, Debug)]
+pub struct LegacyRequestCount {
+    pub method: String,
+    pub route: String,
+    pub user_agent: String,
+    pub request_count: i64,
+    pub first_used_at: OffsetDateTime,
+    pub last_used_at: OffsetDateTime,
+}
+
+#[async_trait]
+pub trait LegacyRouteModel {
+    /// Adds the counts to the stored ones.
+    async fn record_legacy_requests(&self, counts: &[LegacyRequestCount]) -> Result<(), DatabaseError>;
+    /// Every deprecated route that was called, most recently used first.
+    async fn get_legacy_usage(&self) -> Result<Vec<LegacyRouteUsage>, DatabaseError>;
+}
+
+#[async_trait]
+impl LegacyRouteModel for PostgresDatabase {
+    async fn record_legacy_requests(&self, counts: &[LegacyRequestCount]) -> Result<(), DatabaseError> {
+        let methods: Vec<String> = counts.iter().map(|count| count.method.clone()).collect();
+        let routes: Vec<String> = counts.iter().map(|count| count.route.clone()).collect();
+        let user_agents: Vec<String> = counts.iter().map(|count| count.user_agent.clone()).collect();
+        let request_counts: Vec<i64> = counts.iter().map(|count| count.request_count).collect();
+        let first_used: Vec<OffsetDateTime> = counts.iter().map(|count| count.first_used_at).collect();
+        let last_used: Vec<OffsetDateTime> = counts.iter().map(|count| count.last_used_at).collect();
+
+        sqlx::query!(
+            r#"INSERT INTO legacy_route_usage (method, route, user_agent, request_count, first_used_at, last_used_at)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::bigint[], $5::timestamptz[], $6::timestamptz[])
+            ON CONFLICT (method, route, user_agent) DO UPDATE SET
+              request_count = legacy_route_usage.request_count + excluded.request_count,
+              last_used_at = greatest(legacy_route_usage.last_used_at, excluded.last_used_at)"#,
+            &methods,
+            &routes,
+            &user_agents,
+            &request_counts,
+            &first_used,
+            &last_used
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_legacy_usage(&self) -> Result<Vec<LegacyRouteUsage>, DatabaseError> {
+        let usage = sqlx::query_as!(
+            LegacyRouteUsage,
+            "SELECT * FROM legacy_route_usage ORDER BY last_used_at DESC, route, method, user_agent"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+}
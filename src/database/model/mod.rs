@@ -1,9 +1,35 @@
 mod api_user;
+mod archive;
+mod attendee;
+mod audit;
+mod conflict;
+mod enums;
 mod event;
+mod feed;
+mod follow;
 mod group;
+mod idempotency;
+mod job;
+mod legacy;
 mod session;
+mod sync;
+mod tag;
+mod user;
 
 pub use api_user::*;
+pub use archive::*;
+pub use attendee::*;
+pub use audit::*;
+pub use conflict::*;
+pub use enums::*;
 pub use event::*;
+pub use feed::*;
+pub use follow::*;
 pub use group::*;
+pub use idempotency::*;
+pub use job::*;
+pub use legacy::*;
 pub use session::*;
+pub use sync::*;
+pub use tag::*;
+pub use user::*;
@@ -69,7 +69,7 @@ impl SessionModel for PostgresDatabase {
     }
 
     async fn delete_expired_sessions(&self) -> Result<(), DatabaseError> {
-        sqlx::query!("DELETE FROM sessions WHERE expires > $1", OffsetDateTime::now_utc())
+        sqlx::query!("DELETE FROM sessions WHERE expires < $1", OffsetDateTime::now_utc())
             .execute(&self.pool)
             .await?;
 
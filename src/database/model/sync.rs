@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::database::{DatabaseError, PostgresDatabase};
+
+/// How the last sync of a group's in-game calendar went.
+#[derive(Serialize, FromRow, ToSchema)]
+pub struct GroupSync {
+    pub vrc_group_id: String,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_synced_at: Option<OffsetDateTime>,
+    /// Upcoming events the calendar had on the last successful sync.
+    pub event_count: i32,
+    pub created_count: i32,
+    pub updated_count: i32,
+    pub deleted_count: i32,
+    /// Why the last sync failed, cleared once one succeeds again.
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_error_at: Option<OffsetDateTime>,
+}
+
+/// What a successful sync changed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyncSummary {
+    pub event_count: i32,
+    pub created: i32,
+    pub updated: i32,
+    pub deleted: i32,
+}
+
+#[async_trait]
+pub trait SyncModel {
+    /// Every group that is not deleted, whatever its visibility, the least recently synced first.
+    async fn get_syncable_group_ids(&self) -> Result<Vec<String>, DatabaseError>;
+    /// Returns `None` if the group was never synced.
+    async fn get_group_sync(&self, group_id: &str) -> Result<Option<GroupSync>, DatabaseError>;
+    async fn record_group_sync(&self, group_id: &str, summary: SyncSummary) -> Result<(), DatabaseError>;
+    async fn record_group_sync_error(&self, group_id: &str, error: &str) -> Result<(), DatabaseError>;
+}
+
+#[async_trait]
+impl SyncModel for PostgresDatabase {
+    async fn get_syncable_group_ids(&self) -> Result<Vec<String>, DatabaseError> {
+        // so that groups a run didn't get to are not left behind again
+        let ids = sqlx::query_scalar!(
+            r#"SELECT g.vrc_group_id FROM groups g
+            LEFT JOIN group_syncs s ON s.vrc_group_id = g.vrc_group_id
+            WHERE g.deleted_at IS NULL
+            ORDER BY greatest(s.last_synced_at, s.last_error_at) NULLS FIRST, g.vrc_group_id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn get_group_sync(&self, group_id: &str) -> Result<Option<GroupSync>, DatabaseError> {
+        let sync = sqlx::query_as!(GroupSync, "SELECT * FROM group_syncs WHERE vrc_group_id = $1", group_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(sync)
+    }
+
+    async fn record_group_sync(&self, group_id: &str, summary: SyncSummary) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"INSERT INTO group_syncs
+              (vrc_group_id, last_synced_at, event_count, created_count, updated_count, deleted_count)
+            VALUES ($1, now(), $2, $3, $4, $5)
+            ON CONFLICT (vrc_group_id) DO UPDATE SET
+              last_synced_at = excluded.last_synced_at, event_count = excluded.event_count,
+              created_count = excluded.created_count, updated_count = excluded.updated_count,
+              deleted_count = excluded.deleted_count, last_error = NULL, last_error_at = NULL"#,
+            group_id,
+            summary.event_count,
+            summary.created,
+            summary.updated,
+            summary.deleted
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_group_sync_error(&self, group_id: &str, error: &str) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"INSERT INTO group_syncs (vrc_group_id, last_error, last_error_at) VALUES ($1, $2, now())
+            ON CONFLICT (vrc_group_id) DO UPDATE SET
+              last_error = excluded.last_error, last_error_at = excluded.last_error_at"#,
+            group_id,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, prelude::FromRow};
+use time::OffsetDateTime;
+
+use crate::{
+    database::{AuditAction, AuditContext, DatabaseError, Event, PostgresDatabase},
+    validation::{Validate, ValidationErrors},
+};
+
+use super::{audit::record_audit, event::MAX_EVENT_TAG_LENGTH};
+
+const MAX_TAG_DESCRIPTION_LENGTH: usize = 500;
+
+/// A canonical tag with the number of live events using it.
+#[derive(Serialize, FromRow)]
+pub struct Tag {
+    pub slug: String,
+    pub description: Option<String>,
+    /// Other spellings that are stored as `slug`, e.g. `dj-set` for `dj`.
+    pub aliases: Vec<String>,
+    pub event_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTag {
+    pub description: Option<String>,
+}
+
+impl Validate for UpdateTag {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if let Some(description) = &self.description {
+            errors.check_length("description", description, 0, MAX_TAG_DESCRIPTION_LENGTH);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeTag {
+    /// The tag the merged tag becomes an alias of.
+    pub into: String,
+}
+
+impl Validate for MergeTag {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.check_length("into", &normalize_tag(&self.into), 1, MAX_EVENT_TAG_LENGTH);
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct TagMerge {
+    pub tag: Tag,
+    /// How many events had the merged tag replaced.
+    pub events_updated: usize,
+}
+
+#[async_trait]
+pub trait TagModel {
+    /// Tags by popularity, most used first, optionally only those starting with `prefix`.
+    async fn get_tags(&self, prefix: Option<&str>, limit: i64) -> Result<Vec<Tag>, DatabaseError>;
+    async fn get_tag(&self, slug: &str) -> Result<Option<Tag>, DatabaseError>;
+    /// Returns `None` if the tag does not exist.
+    async fn update_tag(
+        &self,
+        slug: &str,
+        update_tag: UpdateTag,
+        audit: &AuditContext,
+    ) -> Result<Option<Tag>, DatabaseError>;
+    /// Replaces `source` with `target` on every event and keeps `source` and its aliases as aliases of `target`.
+    /// Returns `None` if either tag does not exist.
+    async fn merge_tags(
+        &self,
+        source: &str,
+        target: &str,
+        audit: &AuditContext,
+    ) -> Result<Option<TagMerge>, DatabaseError>;
+}
+
+#[async_trait]
+impl TagModel for PostgresDatabase {
+    async fn get_tags(&self, prefix: Option<&str>, limit: i64) -> Result<Vec<Tag>, DatabaseError> {
+        let tags = sqlx::query_as!(
+            Tag,
+            r#"SELECT
+              t.slug, t.description, t.created_at,
+              ARRAY(SELECT alias FROM tag_aliases WHERE slug = t.slug ORDER BY alias) AS "aliases!",
+              (SELECT count(*) FROM events WHERE tags @> ARRAY[t.slug] AND deleted_at IS NULL) AS "event_count!"
+            FROM tags t
+            WHERE $1::text IS NULL OR starts_with(t.slug, $1)
+            ORDER BY "event_count!" DESC, t.slug
+            LIMIT $2"#,
+            prefix.map(normalize_tag),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tags)
+    }
+
+    async fn get_tag(&self, slug: &str) -> Result<Option<Tag>, DatabaseError> {
+        let tag = fetch_tag(&mut *self.pool.acquire().await?, slug).await?;
+
+        Ok(tag)
+    }
+
+    async fn update_tag(
+        &self,
+        slug: &str,
+        update_tag: UpdateTag,
+        audit: &AuditContext,
+    ) -> Result<Option<Tag>, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        let locked = sqlx::query_scalar!("SELECT slug FROM tags WHERE slug = $1 FOR UPDATE", slug)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if locked.is_none() {
+            return Ok(None);
+        }
+        let before = fetch_tag(&mut tx, slug).await?;
+
+        sqlx::query!(
+            "UPDATE tags SET description = $2 WHERE slug = $1",
+            slug,
+            update_tag.description
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let after = fetch_tag(&mut tx, slug).await?;
+        record_audit(
+            &mut tx,
+            audit,
+            "tag",
+            slug,
+            AuditAction::Update,
+            before.as_ref(),
+            after.as_ref(),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+
+    async fn merge_tags(
+        &self,
+        source: &str,
+        target: &str,
+        audit: &AuditContext,
+    ) -> Result<Option<TagMerge>, DatabaseError> {
+        self.dirty.swap(true, Ordering::Acquire);
+
+        let mut tx = self.pool.begin().await?;
+
+        let found = sqlx::query_scalar!(
+            r#"SELECT count(*) AS "count!" FROM (SELECT 1 FROM tags WHERE slug = $1 OR slug = $2 FOR UPDATE) t"#,
+            source,
+            target
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if found != 2 {
+            return Ok(None);
+        }
+
+        let before: HashMap<String, Event> = sqlx::query_as!(
+            Event,
+            "SELECT * FROM events WHERE tags @> ARRAY[$1] AND deleted_at IS NULL FOR UPDATE",
+            source
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|event| (event.vrc_event_id.clone(), event))
+        .collect();
+
+        // replace the tag in place, dropping it instead where the event already has the target
+        let events = sqlx::query_as!(
+            Event,
+            r#"UPDATE events SET
+              tags = CASE WHEN $2 = ANY(tags) THEN array_remove(tags, $1) ELSE array_replace(tags, $1, $2) END,
+              version = version + 1, updated_at = now()
+            WHERE
+              tags @> ARRAY[$1] AND deleted_at IS NULL
+            RETURNING *"#,
+            source,
+            target
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for event in &events {
+            record_audit(
+                &mut tx,
+                audit,
+                "event",
+                &event.vrc_event_id,
+                AuditAction::Update,
+                before.get(&event.vrc_event_id),
+                Some(event),
+            )
+            .await?;
+        }
+
+        // deleted and archived events are not audited, but should not keep the old tag either
+        sqlx::query!(
+            r#"UPDATE events SET
+              tags = CASE WHEN $2 = ANY(tags) THEN array_remove(tags, $1) ELSE array_replace(tags, $1, $2) END
+            WHERE
+              tags @> ARRAY[$1] AND deleted_at IS NOT NULL"#,
+            source,
+            target
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"UPDATE events_archive SET
+              tags = CASE WHEN $2 = ANY(tags) THEN array_remove(tags, $1) ELSE array_replace(tags, $1, $2) END
+            WHERE
+              tags @> ARRAY[$1]"#,
+            source,
+            target
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("UPDATE tag_aliases SET slug = $2 WHERE slug = $1", source, target)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("INSERT INTO tag_aliases (alias, slug) VALUES ($1, $2)", source, target)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM tags WHERE slug = $1", source)
+            .execute(&mut *tx)
+            .await?;
+
+        let tag = fetch_tag(&mut tx, target).await?;
+        tx.commit().await?;
+
+        Ok(tag.map(|tag| TagMerge {
+            tag,
+            events_updated: events.len(),
+        }))
+    }
+}
+
+async fn fetch_tag(conn: &mut PgConnection, slug: &str) -> Result<Option<Tag>, DatabaseError> {
+    let tag = sqlx::query_as!(
+        Tag,
+        r#"SELECT
+          t.slug, t.description, t.created_at,
+          ARRAY(SELECT alias FROM tag_aliases WHERE slug = t.slug ORDER BY alias) AS "aliases!",
+          (SELECT count(*) FROM events WHERE tags @> ARRAY[t.slug] AND deleted_at IS NULL) AS "event_count!"
+        FROM tags t
+        WHERE t.slug = $1"#,
+        slug
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(tag)
+}
+
+/// The form every tag is stored in, so that `#Music`, `music` and ` MUSIC ` are the same tag: trimmed,
+/// lowercased, without a leading `#` and with inner whitespace replaced by `-`.
+#[must_use]
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Resolves aliases to their canonical tag and registers tags that are new. Call this in the transaction that
+/// stores the tags.
+pub(super) async fn canonicalize_tags(
+    conn: &mut PgConnection,
+    tags: Option<&[String]>,
+) -> Result<Option<Vec<String>>, DatabaseError> {
+    let Some(tags) = tags else {
+        return Ok(None);
+    };
+
+    let resolved = sqlx::query_scalar!(
+        r#"SELECT COALESCE(a.slug, t.tag) AS "slug!"
+        FROM unnest($1::text[]) WITH ORDINALITY AS t(tag, position)
+        LEFT JOIN tag_aliases a ON a.alias = t.tag
+        ORDER BY t.position"#,
+        tags
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // two aliases of the same tag collapse into one
+    let mut canonical: Vec<String> = Vec::with_capacity(resolved.len());
+    for tag in resolved {
+        if !canonical.contains(&tag) {
+            canonical.push(tag);
+        }
+    }
+
+    sqlx::query!(
+        "INSERT INTO tags (slug) SELECT unnest($1::text[]) ON CONFLICT DO NOTHING",
+        &canonical
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(canonical))
+}
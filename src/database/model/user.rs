@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use base64::{Engine, prelude::BASE64_URL_SAFE};
+use rand::{TryRngCore, rngs::OsRng};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+
+use crate::{
+    database::{DatabaseError, PostgresDatabase},
+    oauth::DiscordInfo,
+};
+
+/// A Discord account that has logged in through OAuth. `id` is the Discord user ID.
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct User {
+    pub id: String,
+    pub username: String,
+    pub global_name: Option<String>,
+    pub avatar: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// SHA-256 of the secret in the user's calendar feed URLs. The token itself is only shown when it is created
+    /// with a `POST` to `/me/feed-token`, until then the user has no feed.
+    #[serde(skip_serializing)]
+    pub feed_token_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub feed_token_created_at: Option<OffsetDateTime>,
+}
+
+#[async_trait]
+pub trait UserModel {
+    async fn get_user(&self, id: &str) -> Result<Option<User>, DatabaseError>;
+    async fn upsert_user(&self, info: &DiscordInfo) -> Result<User, DatabaseError>;
+    async fn get_user_by_feed_token(&self, feed_token: &str) -> Result<Option<User>, DatabaseError>;
+    /// Replaces the user's feed token, revoking any subscription URLs built from the old one.
+    async fn regenerate_feed_token(&self, id: &str) -> Result<String, DatabaseError>;
+}
+
+#[async_trait]
+impl UserModel for PostgresDatabase {
+    async fn get_user(&self, id: &str) -> Result<Option<User>, DatabaseError> {
+        let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user)
+    }
+
+    async fn upsert_user(&self, info: &DiscordInfo) -> Result<User, DatabaseError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"INSERT INTO users
+              (id, username, global_name, avatar)
+            VALUES
+              ($1, $2, $3, $4)
+            ON CONFLICT (id) DO UPDATE SET
+              username = excluded.username, global_name = excluded.global_name, avatar = excluded.avatar
+            RETURNING *"#,
+            info.id,
+            info.username,
+            info.global_name,
+            info.avatar,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn get_user_by_feed_token(&self, feed_token: &str) -> Result<Option<User>, DatabaseError> {
+        let user = sqlx::query_as!(
+            User,
+            "SELECT * FROM users WHERE feed_token_hash = $1",
+            hash_feed_token(feed_token)
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn regenerate_feed_token(&self, id: &str) -> Result<String, DatabaseError> {
+        let mut bytes = [0u8; 33];
+        OsRng.try_fill_bytes(&mut bytes).map_err(|_| DatabaseError::RngError)?;
+        let feed_token = BASE64_URL_SAFE.encode(bytes);
+
+        sqlx::query!(
+            "UPDATE users SET feed_token_hash = $2, feed_token_created_at = now() WHERE id = $1",
+            id,
+            hash_feed_token(&feed_token)
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(feed_token)
+    }
+}
+
+/// Tokens are random enough that an unsalted hash can't be reversed, and a lookup by it stays a plain index scan.
+fn hash_feed_token(feed_token: &str) -> String {
+    format!("{:x}", Sha256::digest(feed_token))
+}
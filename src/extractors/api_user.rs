@@ -6,6 +6,8 @@ use crate::app::AppState;
 use crate::database::ApiUserModel;
 use crate::extractors::errors::AuthError;
 
+/// The user agent the API key was issued to. Unlike the `User-Agent` header, it cannot be picked by the client, so
+/// it is what changes are attributed to.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthenticatedApiUser(pub String);
 
@@ -26,11 +28,7 @@ impl FromRequestParts<AppState> for AuthenticatedApiUser {
             .ok_or(AuthError::MissingUserAgent)?
             .to_string();
 
-        let has_api_key = state
-            .db
-            .validate_api_key(api_key)
-            .await
-            .map_err(AuthError::DatabaseError)?;
+        let api_user = state.db.get_api_user(api_key).await.map_err(AuthError::DatabaseError)?;
         // .ok_or(ApiError::from(AuthError::InvalidCredentials))
         // .inspect_err(|_| {
         //     tracing::info!("Login attempt by IP: '{}' via User-Agent '{}'", ip, user_agent);
@@ -38,10 +36,8 @@ impl FromRequestParts<AppState> for AuthenticatedApiUser {
 
         tracing::Span::current().record("user_agent", tracing::field::display(&user_agent));
 
-        if has_api_key {
-            Ok(AuthenticatedApiUser(user_agent.to_string()))
-        } else {
-            Err(AuthError::InvalidCredentials)
-        }
+        api_user
+            .map(|api_user| AuthenticatedApiUser(api_user.user_agent))
+            .ok_or(AuthError::InvalidCredentials)
     }
 }
@@ -0,0 +1,92 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header::CONTENT_TYPE,
+};
+use serde_json::{Map, Value};
+
+use crate::{routes::ApiError, validation::ValidationErrors};
+
+/// A JSON Merge Patch (RFC 7396) request body.
+#[derive(Debug)]
+pub struct MergePatch(pub Value);
+
+impl MergePatch {
+    /// Checks the patch against the current JSON representation of a resource: fields in `immutable` may not be
+    /// changed, fields in `required` may not be removed, and fields the resource does not have are refused rather
+    /// than dropped, so that a misspelled field is not mistaken for a successful change.
+    pub fn check_fields(&self, current: &Value, immutable: &[&str], required: &[&str], errors: &mut ValidationErrors) {
+        let Value::Object(patch) = &self.0 else {
+            return;
+        };
+
+        for (field, value) in patch {
+            if immutable.contains(&field.as_str()) {
+                if current.get(field) != Some(value) {
+                    errors.add(field, "cannot be changed");
+                }
+            } else if current.get(field).is_none() {
+                errors.add(field, "is not a known field");
+            } else if value.is_null() && required.contains(&field.as_str()) {
+                errors.add(field, "cannot be removed");
+            }
+        }
+    }
+
+    /// Applies the patch to `target`.
+    pub fn apply(&self, target: &mut Value) {
+        merge(target, &self.0);
+    }
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+impl<S> FromRequest<S> for MergePatch
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(str::trim);
+
+        if !matches!(content_type, Some("application/merge-patch+json" | "application/json")) {
+            return Err(ApiError::UnsupportedMediaType);
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| ApiError::BadRequest)?;
+        let patch: Value = serde_json::from_slice(&body).map_err(|_| ApiError::BadRequest)?;
+
+        if patch.is_object() {
+            Ok(Self(patch))
+        } else {
+            Err(ApiError::BadRequest)
+        }
+    }
+}
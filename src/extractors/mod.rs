@@ -1,7 +1,15 @@
 mod api_user;
 mod errors;
+mod merge_patch;
+mod preconditions;
+mod request_id;
 mod session;
+mod user;
 
 pub use api_user::*;
 pub use errors::*;
+pub use merge_patch::*;
+pub use preconditions::*;
+pub use request_id::*;
 pub use session::*;
+pub use user::*;
@@ -0,0 +1,105 @@
+use std::convert::Infallible;
+
+use axum::extract::FromRequestParts;
+use axum::http::{
+    HeaderMap,
+    header::{IF_MATCH, IF_NONE_MATCH},
+    request::Parts,
+};
+
+use crate::routes::ApiError;
+
+/// The `If-Match` and `If-None-Match` headers of a conditional request (RFC 9110, section 13).
+#[derive(Debug, Default)]
+pub struct Preconditions {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+}
+
+impl Preconditions {
+    #[must_use]
+    pub const fn has_if_match(&self) -> bool {
+        self.if_match.is_some()
+    }
+
+    /// # Errors
+    ///
+    /// Returns [`ApiError::PreconditionFailed`] if `If-Match` was sent and none of its tags match `etag`.
+    pub fn check_if_match(&self, etag: &str) -> Result<(), ApiError> {
+        let Some(if_match) = &self.if_match else {
+            return Ok(());
+        };
+
+        // If-Match uses the strong comparison, so weak tags never match
+        if if_match.trim() == "*" || entity_tags(if_match).any(|tag| !tag.starts_with("W/") && tag == etag) {
+            Ok(())
+        } else {
+            Err(ApiError::PreconditionFailed)
+        }
+    }
+
+    /// Like [`Self::check_if_match`], but only compares the version an event's `ETag` starts with, so that RSVPs
+    /// since the client fetched the event do not fail the precondition.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::PreconditionFailed`] if `If-Match` was sent and none of its tags are at `version`.
+    pub fn check_if_match_version(&self, version: i32) -> Result<(), ApiError> {
+        let Some(if_match) = &self.if_match else {
+            return Ok(());
+        };
+
+        let version = version.to_string();
+        let at_version =
+            |tag: &str| !tag.starts_with("W/") && tag.trim_matches('"').split('.').next() == Some(version.as_str());
+        if if_match.trim() == "*" || entity_tags(if_match).any(at_version) {
+            Ok(())
+        } else {
+            Err(ApiError::PreconditionFailed)
+        }
+    }
+
+    /// Whether a GET for a representation tagged `etag` can be answered with `304 Not Modified`.
+    #[must_use]
+    pub fn is_not_modified(&self, etag: &str) -> bool {
+        let Some(if_none_match) = &self.if_none_match else {
+            return false;
+        };
+
+        // If-None-Match uses the weak comparison
+        let etag = etag.trim_start_matches("W/");
+        if_none_match.trim() == "*" || entity_tags(if_none_match).any(|tag| tag.trim_start_matches("W/") == etag)
+    }
+}
+
+fn entity_tags(header: &str) -> impl Iterator<Item = &str> {
+    header.split(',').map(str::trim).filter(|tag| !tag.is_empty())
+}
+
+fn header_string(headers: &HeaderMap, name: impl axum::http::header::AsHeaderName) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(","))
+    }
+}
+
+impl<S> FromRequestParts<S> for Preconditions
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            if_match: header_string(&parts.headers, IF_MATCH),
+            if_none_match: header_string(&parts.headers, IF_NONE_MATCH),
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use crate::app::AppState;
+use crate::database::{User, UserModel};
+use crate::extractors::ApiSession;
+use crate::oauth::DiscordToken;
+use crate::routes::ApiError;
+
+/// The Discord user logged in to the current session.
+#[derive(Clone)]
+pub struct SessionUser(pub User);
+
+impl FromRequestParts<AppState> for SessionUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let ApiSession(mut session) = ApiSession::from_request_parts(parts, state).await?;
+
+        let user_id: Option<String> = session.get("user_id").map_err(|_| ApiError::BadRequest)?;
+        if let Some(user_id) = user_id
+            && let Some(user) = state.db.get_user(&user_id).await?
+        {
+            return Ok(Self(user));
+        }
+
+        // sessions that logged in before users were recorded only carry the Discord token
+        let token: DiscordToken = session
+            .get("token")
+            .map_err(|_| ApiError::BadRequest)?
+            .ok_or(ApiError::Unauthorized(None))?;
+        let info = state.oauth.get_discord_info(&token).await?;
+        let user = state.db.upsert_user(&info).await?;
+        session.set("user_id", &user.id).await?;
+
+        Ok(Self(user))
+    }
+}
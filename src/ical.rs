@@ -0,0 +1,1022 @@
+//! Minimal iCalendar (RFC 5545) support for calendar feeds, both the ones served and the external ones imported.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration as StdDuration,
+};
+
+use reqwest::{Url, header::LOCATION, redirect::Policy};
+use sha2::{Digest, Sha256};
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+use time_tz::{OffsetResult, PrimitiveDateTimeExt, Tz, timezones};
+
+use crate::{
+    calendar,
+    database::{CreateEvent, Event, EventAccessType, EventCategory, MAX_EVENT_DURATION, import_tags, normalize_tag},
+};
+
+const PRODID: &str = "-//vrc-calendar//events//EN";
+/// Content lines longer than this many octets must be folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders `events` as a `VCALENDAR` named `name`.
+#[must_use]
+pub fn write_calendar<'a>(name: &str, events: impl IntoIterator<Item = &'a Event>) -> String {
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{PRODID}"));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    let now = format_utc(OffsetDateTime::now_utc());
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", escape_text(&event.vrc_event_id)));
+        push_line(&mut out, &format!("DTSTAMP:{now}"));
+        push_line(&mut out, &format!("CREATED:{}", format_utc(event.created_at)));
+        push_line(&mut out, &format!("DTSTART:{}", format_utc(event.starts_at)));
+        push_line(&mut out, &format!("DTEND:{}", format_utc(event.ends_at)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&event.name)));
+        if !event.description.is_empty() {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&event.description)));
+        }
+
+        let categories: Vec<String> = std::iter::once(event.category.as_str())
+            .chain(event.tags.iter().flatten().map(String::as_str))
+            .map(escape_text)
+            .collect();
+        push_line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+
+        if let Some(image_url) = &event.image_url {
+            push_line(&mut out, &format!("ATTACH:{image_url}"));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn format_utc(datetime: OffsetDateTime) -> String {
+    let utc = datetime.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        utc.year(),
+        u8::from(utc.month()),
+        utc.day(),
+        utc.hour(),
+        utc.minute(),
+        utc.second()
+    )
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folding it so that no physical line exceeds 75 octets.
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Events imported from a feed have IDs starting with this, see [`feed_event_id`].
+pub const FEED_EVENT_ID_PREFIX: &str = "ical_";
+/// Feeds larger than this are not imported.
+const MAX_FEED_BYTES: usize = 5 * 1024 * 1024;
+const FEED_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+/// Redirects followed when fetching a feed, each to a host checked like the feed's own.
+const MAX_FEED_REDIRECTS: usize = 5;
+const UNTITLED_EVENT_NAME: &str = "(No title)";
+const MAX_IMPORTED_NAME_CHARS: usize = 200;
+const MAX_IMPORTED_DESCRIPTION_CHARS: usize = 10_000;
+/// Recurrence rules are followed for at most this many days, weeks, months or years.
+const MAX_RECURRENCE_PERIODS: i64 = 10_000;
+
+/// A `VEVENT` read from an external feed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcalEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+    pub categories: Vec<String>,
+    /// `STATUS:CANCELLED`, which calendars use to take back an event without removing it from the feed.
+    pub cancelled: bool,
+    /// The start the recurrence rule gave this occurrence of a recurring event, its `RECURRENCE-ID`.
+    pub recurrence_id: Option<OffsetDateTime>,
+    /// The `RRULE` of a recurring event, which [`ParsedCalendar::occurrences`] expands.
+    pub recurrence: Option<Recurrence>,
+}
+
+/// The events of a feed, and why the ones that could not be read were left out.
+#[derive(Debug, Default)]
+pub struct ParsedCalendar {
+    pub events: Vec<IcalEvent>,
+    /// Instances that replace a single occurrence of a recurring event, e.g. one that was moved.
+    pub overrides: Vec<IcalEvent>,
+    pub skipped: Vec<String>,
+}
+
+impl ParsedCalendar {
+    /// The events to import. Single events are returned as they are, recurring events as their occurrences that
+    /// end after `from` and start before `until`, with the overriding instances in place of the ones they replace.
+    #[must_use]
+    pub fn occurrences(&self, from: OffsetDateTime, until: OffsetDateTime) -> Vec<IcalEvent> {
+        let mut occurrences = Vec::new();
+        for event in &self.events {
+            let Some(recurrence) = &event.recurrence else {
+                occurrences.push(event.clone());
+                continue;
+            };
+
+            let duration = event.ends_at - event.starts_at;
+            occurrences.extend(
+                recurrence
+                    .starts(duration, from, until)
+                    .into_iter()
+                    .filter_map(|starts_at| {
+                        Some(IcalEvent {
+                            starts_at,
+                            ends_at: starts_at.checked_add(duration)?,
+                            recurrence_id: Some(starts_at),
+                            recurrence: None,
+                            ..event.clone()
+                        })
+                    }),
+            );
+        }
+
+        for instance in &self.overrides {
+            occurrences.retain(|occurrence| {
+                occurrence.uid != instance.uid || occurrence.recurrence_id != instance.recurrence_id
+            });
+            occurrences.push(instance.clone());
+        }
+
+        occurrences
+    }
+}
+
+impl IcalEvent {
+    /// The event as stored for `vrc_group_id`. Feeds have no notion of categories, platforms or access types, so
+    /// a `CATEGORIES` entry naming a known category is used as the category and the rest become tags.
+    #[must_use]
+    pub fn to_create_event(&self, vrc_group_id: &str) -> CreateEvent {
+        let category = self
+            .categories
+            .iter()
+            .map(|category| EventCategory::from(normalize_tag(category).replace('-', "_")))
+            .find(EventCategory::is_known)
+            .unwrap_or(EventCategory::Other);
+
+        let tags = import_tags(
+            self.categories
+                .iter()
+                .filter(|tag| normalize_tag(tag).replace('-', "_") != category.as_str()),
+        );
+
+        // occurrences of a recurring event share its UID, the RECURRENCE-ID tells them apart
+        let key = self.recurrence_id.map_or_else(
+            || self.uid.clone(),
+            |recurrence_id| format!("{}/{}", self.uid, format_utc(recurrence_id)),
+        );
+
+        let name = truncate_chars(self.summary.trim(), MAX_IMPORTED_NAME_CHARS);
+        CreateEvent {
+            vrc_event_id: feed_event_id(vrc_group_id, &key),
+            vrc_group_id: vrc_group_id.to_string(),
+            name: if name.is_empty() {
+                UNTITLED_EVENT_NAME.to_string()
+            } else {
+                name
+            },
+            description: truncate_chars(&self.description, MAX_IMPORTED_DESCRIPTION_CHARS),
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            category,
+            access_type: EventAccessType::Group,
+            platforms: Vec::new(),
+            image_url: None,
+            tags: (!tags.is_empty()).then_some(tags),
+        }
+    }
+}
+
+/// The ID an imported event is stored under. It only depends on the group and the event's `UID`, so every import
+/// of the same event updates the same row.
+#[must_use]
+pub fn feed_event_id(vrc_group_id: &str, uid: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(vrc_group_id);
+    hasher.update(b"\0");
+    hasher.update(uid);
+    let hash = format!("{:x}", hasher.finalize());
+
+    format!("{FEED_EVENT_ID_PREFIX}{}", &hash[..32])
+}
+
+/// Downloads a feed. `webcal://` addresses are fetched over https. The host, and the host of every redirect, must
+/// not resolve to an internal address unless it is one of `allowed_hosts`.
+///
+/// # Errors
+///
+/// Returns a description of the failure if a host is refused, the request fails, the server does not respond with a
+/// success status, or the feed is larger than 5 MiB. The description is shown to whoever registered the feed, so it
+/// only tells what went wrong, the details are logged.
+pub async fn fetch_feed(url: &str, allowed_hosts: &[String]) -> Result<String, String> {
+    let mut url = feed_url(url).ok_or_else(|| "the feed address is not a valid URL".to_string())?;
+
+    // redirects are followed by hand, so that a public host can't send the request on to an internal one
+    for _ in 0..=MAX_FEED_REDIRECTS {
+        let mut client = reqwest::Client::builder().redirect(Policy::none());
+        if let Some(host) = url
+            .host_str()
+            .filter(|host| !allowed_hosts.iter().any(|allowed| allowed == host))
+        {
+            let addresses = resolve_public_host(&url)
+                .await
+                .map_err(|error| error.as_str().to_string())?;
+            // connects to the addresses that were checked, the host can't resolve to another one in between
+            client = client.resolve_to_addrs(host, &addresses);
+        }
+
+        let response = client
+            .build()
+            .map_err(fetch_error)?
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, "text/calendar")
+            .timeout(FEED_TIMEOUT)
+            .send()
+            .await
+            .map_err(fetch_error)?;
+
+        if response.status().is_redirection() {
+            url = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| url.join(location).ok())
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+                .ok_or_else(|| "the server redirected to an invalid address".to_string())?;
+            continue;
+        }
+
+        let mut response = response.error_for_status().map_err(fetch_error)?;
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(fetch_error)? {
+            if body.len() + chunk.len() > MAX_FEED_BYTES {
+                return Err(format!("the feed is larger than {} MiB", MAX_FEED_BYTES / 1024 / 1024));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        return String::from_utf8(body).map_err(|_| "the feed is not valid UTF-8".to_string());
+    }
+
+    Err("the server redirected too many times".to_string())
+}
+
+/// The address a feed is fetched from, reading calendar apps' `webcal://` links as `https://`. `None` unless it is
+/// an `http` or `https` URL with a host.
+#[must_use]
+pub fn feed_url(url: &str) -> Option<Url> {
+    let url = url
+        .strip_prefix("webcal://")
+        .map_or_else(|| url.to_string(), |rest| format!("https://{rest}"));
+
+    Url::parse(&url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+}
+
+/// Why a feed's host was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedHostError {
+    Unresolved,
+    /// The host has a private, loopback or link-local address.
+    Internal,
+}
+
+impl FeedHostError {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Unresolved => "the feed's host could not be resolved",
+            Self::Internal => "the feed's host is a private, loopback or link-local address",
+        }
+    }
+}
+
+/// Resolves the host of a feed, refusing it if any of its addresses is private, loopback or link-local, so that
+/// the importer can't be pointed at services only reachable from the server.
+///
+/// # Errors
+///
+/// Returns why the host was refused.
+pub async fn resolve_public_host(url: &Url) -> Result<Vec<SocketAddr>, FeedHostError> {
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or_default();
+
+    let addresses: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(address) => vec![SocketAddr::new(address, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map(Iterator::collect)
+            .unwrap_or_default(),
+    };
+
+    if addresses.is_empty() {
+        Err(FeedHostError::Unresolved)
+    } else if addresses.iter().all(|address| is_public_address(address.ip())) {
+        Ok(addresses)
+    } else {
+        Err(FeedHostError::Internal)
+    }
+}
+
+fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            // 0.0.0.0/8 is "this network", 100.64.0.0/10 is carrier-grade NAT
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || first == 0
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or_else(
+            || {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            },
+            |ip| is_public_address(IpAddr::V4(ip)),
+        ),
+    }
+}
+
+fn fetch_error(error: reqwest::Error) -> String {
+    let reason = match error.status() {
+        Some(status) => format!("the server responded with {status}"),
+        None if error.is_timeout() => "the server did not respond in time".to_string(),
+        None if error.is_connect() => "the server could not be reached".to_string(),
+        None => "the feed could not be downloaded".to_string(),
+    };
+    tracing::warn!(error = %error.without_url(), "failed to fetch feed");
+
+    reason
+}
+
+/// Reads the `VEVENT`s of a `VCALENDAR`. Events that can't be read are skipped rather than failing the whole feed.
+///
+/// Recurring events are returned once, with their `RRULE` and `EXDATE`s, and instances overriding a single
+/// occurrence (those with a `RECURRENCE-ID`) are returned as [`ParsedCalendar::overrides`]; see
+/// [`ParsedCalendar::occurrences`]. `RDATE`s are ignored. Times in a `TZID` must use IANA names, and floating times
+/// are read in the calendar's `X-WR-TIMEZONE`, or UTC.
+///
+/// # Errors
+///
+/// Returns an error if `input` is not a `VCALENDAR`.
+pub fn parse_calendar(input: &str) -> Result<ParsedCalendar, String> {
+    let lines = unfold_lines(input);
+    if !lines
+        .first()
+        .is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("the feed is not an iCalendar file".to_string());
+    }
+
+    let default_tz = lines
+        .iter()
+        .filter_map(|line| ContentLine::parse(line))
+        .find(|line| line.name == "X-WR-TIMEZONE")
+        .and_then(|line| timezones::get_by_name(line.value.trim()))
+        .unwrap_or(timezones::db::UTC);
+
+    let mut calendar = ParsedCalendar::default();
+    let mut event: Option<Vec<ContentLine>> = None;
+    // VALARMs and other components nested in an event have their own DTSTART and the like
+    let mut nested = 0usize;
+
+    for line in &lines {
+        let Some(line) = ContentLine::parse(line) else {
+            continue;
+        };
+
+        match (line.name.as_str(), line.value.to_ascii_uppercase().as_str(), &mut event) {
+            ("BEGIN", "VEVENT", None) => event = Some(Vec::new()),
+            ("BEGIN", _, Some(_)) => nested += 1,
+            ("END", "VEVENT", Some(_)) if nested == 0 => {
+                let properties = event.take().unwrap_or_default();
+                match read_event(&properties, default_tz) {
+                    Ok(event) if event.recurrence_id.is_some() => calendar.overrides.push(event),
+                    Ok(event) => calendar.events.push(event),
+                    Err(reason) => calendar.skipped.push(reason),
+                }
+            }
+            ("END", _, Some(_)) => nested = nested.saturating_sub(1),
+            (_, _, Some(properties)) if nested == 0 => properties.push(line),
+            _ => {}
+        }
+    }
+
+    Ok(calendar)
+}
+
+/// Reads one event's properties.
+fn read_event(properties: &[ContentLine], default_tz: &'static Tz) -> Result<IcalEvent, String> {
+    let property = |name: &str| properties.iter().find(|line| line.name == name);
+
+    let uid = property("UID")
+        .map(|line| unescape_text(&line.value))
+        .filter(|uid| !uid.is_empty())
+        .ok_or_else(|| "an event has no UID".to_string())?;
+
+    let invalid = |reason: &str| format!("event {uid}: {reason}");
+    let (start, tz, all_day) = property("DTSTART")
+        .ok_or_else(|| invalid("has no DTSTART"))
+        .and_then(|line| parse_local_date_time(line, default_tz).ok_or_else(|| invalid("has an invalid DTSTART")))?;
+    let starts_at = local_instant(start, tz, all_day);
+
+    let ends_at = if let Some(line) = property("DTEND") {
+        parse_date_time(line, default_tz)
+            .ok_or_else(|| invalid("has an invalid DTEND"))?
+            .0
+    } else if let Some(line) = property("DURATION") {
+        parse_duration(&line.value)
+            .and_then(|duration| starts_at.checked_add(duration))
+            .ok_or_else(|| invalid("has an invalid DURATION"))?
+    } else if all_day {
+        // an all-day event without an end lasts the day
+        starts_at
+            .checked_add(Duration::days(1))
+            .ok_or_else(|| invalid("has an invalid DTSTART"))?
+    } else {
+        starts_at
+    };
+    if ends_at < starts_at {
+        return Err(invalid("ends before it starts"));
+    }
+    // could not be stored anyway, and would push the occurrences of a recurring event past the end of the calendar
+    if ends_at - starts_at > MAX_EVENT_DURATION {
+        return Err(invalid(&format!(
+            "lasts longer than {} days",
+            MAX_EVENT_DURATION.whole_days()
+        )));
+    }
+
+    let recurrence_id = property("RECURRENCE-ID")
+        .map(|line| {
+            parse_date_time(line, default_tz)
+                .map(|(instant, _)| instant)
+                .ok_or_else(|| invalid("has an invalid RECURRENCE-ID"))
+        })
+        .transpose()?;
+
+    let recurrence = match property("RRULE") {
+        Some(line) if recurrence_id.is_none() => {
+            let exdates = properties
+                .iter()
+                .filter(|line| line.name == "EXDATE")
+                .flat_map(|line| {
+                    line.value.split(',').map(|value| ContentLine {
+                        name: line.name.clone(),
+                        params: line.params.clone(),
+                        value: value.to_string(),
+                    })
+                })
+                .map(|line| parse_date_time(&line, default_tz).map(|(instant, _)| instant))
+                .collect::<Option<_>>()
+                .ok_or_else(|| invalid("has an invalid EXDATE"))?;
+
+            let recurrence = Recurrence::parse(&line.value, start, tz, all_day, exdates)
+                .ok_or_else(|| invalid("has an unsupported RRULE"))?;
+            Some(recurrence)
+        }
+        _ => None,
+    };
+
+    let categories = properties
+        .iter()
+        .filter(|line| line.name == "CATEGORIES")
+        .flat_map(|line| split_list(&line.value))
+        .map(|category| unescape_text(&category))
+        .filter(|category| !category.trim().is_empty())
+        .collect();
+
+    Ok(IcalEvent {
+        summary: property("SUMMARY")
+            .map(|line| unescape_text(&line.value))
+            .unwrap_or_default(),
+        description: property("DESCRIPTION")
+            .map(|line| unescape_text(&line.value))
+            .unwrap_or_default(),
+        starts_at,
+        ends_at,
+        categories,
+        cancelled: property("STATUS").is_some_and(|line| line.value.trim().eq_ignore_ascii_case("CANCELLED")),
+        recurrence_id,
+        recurrence,
+        uid,
+    })
+}
+
+/// A property of a component, e.g. `DTSTART;TZID=Europe/Berlin:20261019T200000`.
+#[derive(Debug)]
+struct ContentLine {
+    /// Uppercased, since names are case-insensitive.
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn parse(line: &str) -> Option<Self> {
+        // the value starts at the first colon outside a quoted parameter value
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        })?;
+
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                Some((key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Joins folded lines back together, accepting bare `\n` line endings as well as the standard `\r\n`.
+fn unfold_lines(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.trim_start_matches('\u{feff}').lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Parses a `DATE` or `DATE-TIME` value, returning whether it was a date. Dates and floating times are read in
+/// the property's `TZID`, or `default_tz`.
+fn parse_date_time(line: &ContentLine, default_tz: &'static Tz) -> Option<(OffsetDateTime, bool)> {
+    let (local, tz, all_day) = parse_local_date_time(line, default_tz)?;
+
+    Some((local_instant(local, tz, all_day), all_day))
+}
+
+/// Parses a `DATE` or `DATE-TIME` value as the local time in the zone it is in, and whether it was a date. Dates
+/// are read as midnight.
+fn parse_local_date_time(
+    line: &ContentLine,
+    default_tz: &'static Tz,
+) -> Option<(PrimitiveDateTime, &'static Tz, bool)> {
+    let tz = match line.param("TZID") {
+        Some(name) => timezones::get_by_name(name.trim_start_matches('/'))?,
+        None => default_tz,
+    };
+    let value = line.value.trim();
+
+    let date = parse_basic_date(value.get(..8)?)?;
+    if value.len() == 8 {
+        return Some((date.midnight(), tz, true));
+    }
+
+    let time = value.get(8..)?.strip_prefix('T')?;
+    let (time, utc) = time.strip_suffix('Z').map_or((time, false), |time| (time, true));
+    if time.len() != 6 || !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let time = Time::from_hms(
+        time[..2].parse().ok()?,
+        time[2..4].parse().ok()?,
+        time[4..].parse().ok()?,
+    )
+    .ok()?;
+    let tz = if utc { timezones::db::UTC } else { tz };
+
+    Some((PrimitiveDateTime::new(date, time), tz, false))
+}
+
+/// The instant a local time in `tz` is at, or the start of its day for dates.
+fn local_instant(local: PrimitiveDateTime, tz: &Tz, all_day: bool) -> OffsetDateTime {
+    if all_day {
+        return calendar::start_of_day(local.date(), tz);
+    }
+
+    match local.assume_timezone(tz) {
+        OffsetResult::Some(instant) | OffsetResult::Ambiguous(instant, _) => instant,
+        // a time skipped by a daylight saving change is read as if the clocks hadn't changed yet
+        OffsetResult::None => local.assume_timezone_utc(tz),
+    }
+}
+
+/// How a recurring event repeats.
+///
+/// Supports the rules calendar apps commonly create: every so many days, weeks on some weekdays, months on some
+/// days or weekdays such as the second Tuesday, or years, up to a `COUNT` or `UNTIL`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<u32>,
+    until: Option<OffsetDateTime>,
+    /// `BYDAY`, weekdays with an ordinal within the month for monthly rules, such as `2TU` or `-1FR`.
+    by_day: Vec<(Option<i8>, Weekday)>,
+    /// `BYMONTHDAY`, days counted from the end of the month when negative.
+    by_month_day: Vec<i8>,
+    exdates: Vec<OffsetDateTime>,
+    /// The local `DTSTART`, which occurrences keep the time of.
+    start: PrimitiveDateTime,
+    tz: &'static Tz,
+    all_day: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    /// Reads an `RRULE` value, returning `None` for rules that are invalid or use parts that aren't supported.
+    fn parse(
+        value: &str,
+        start: PrimitiveDateTime,
+        tz: &'static Tz,
+        all_day: bool,
+        exdates: Vec<OffsetDateTime>,
+    ) -> Option<Self> {
+        let mut frequency = None;
+        let mut recurrence = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            exdates,
+            start,
+            tz,
+            all_day,
+        };
+
+        for part in value.trim().split(';') {
+            let (key, value) = part.split_once('=')?;
+            let value = value.trim().to_ascii_uppercase();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    });
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+                "COUNT" => recurrence.count = Some(value.parse().ok()?),
+                "UNTIL" => recurrence.until = Some(parse_until(&value, tz)?),
+                "BYDAY" => recurrence.by_day = value.split(',').map(parse_weekday).collect::<Option<_>>()?,
+                "BYMONTHDAY" => {
+                    recurrence.by_month_day = value
+                        .split(',')
+                        .map(|day| day.parse().ok().filter(|day: &i8| (1..=31).contains(&day.abs())))
+                        .collect::<Option<_>>()?;
+                }
+                // weeks are taken to start on Monday, which only matters to weekly rules with an interval
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+        recurrence.frequency = frequency?;
+
+        let by_day_ordinals = recurrence.by_day.iter().any(|(ordinal, _)| ordinal.is_some());
+        let supported = match recurrence.frequency {
+            Frequency::Daily | Frequency::Yearly => recurrence.by_day.is_empty() && recurrence.by_month_day.is_empty(),
+            Frequency::Weekly => !by_day_ordinals && recurrence.by_month_day.is_empty(),
+            Frequency::Monthly => recurrence.by_day.is_empty() || recurrence.by_month_day.is_empty(),
+        };
+
+        supported.then_some(recurrence)
+    }
+
+    /// The starts of the occurrences lasting `duration` that end after `from` and start before `until`, leaving out
+    /// the `EXDATE`s.
+    fn starts(&self, duration: Duration, from: OffsetDateTime, until: OffsetDateTime) -> Vec<OffsetDateTime> {
+        let first_day = self.start.date();
+        let mut starts = Vec::new();
+        let mut counted = 0;
+
+        let first_period = from.checked_sub(duration).map_or(0, |from| self.first_period(from));
+        for period in first_period..MAX_RECURRENCE_PERIODS {
+            let Some(days) = self.period_days(period) else {
+                break;
+            };
+
+            for day in days.into_iter().filter(|day| *day >= first_day) {
+                let starts_at = local_instant(day.with_time(self.start.time()), self.tz, self.all_day);
+                if starts_at >= until
+                    || self.until.is_some_and(|last| starts_at > last)
+                    || self.count.is_some_and(|count| counted >= count)
+                {
+                    return starts;
+                }
+
+                counted += 1;
+                let ends_after_from = starts_at.checked_add(duration).is_some_and(|ends_at| ends_at > from);
+                if ends_after_from && !self.exdates.contains(&starts_at) {
+                    starts.push(starts_at);
+                }
+            }
+        }
+
+        starts
+    }
+
+    /// The period to start expanding at. Rules repeating by the day or week skip ahead to shortly before `from`,
+    /// unless they have a `COUNT`, which counts from the first occurrence.
+    fn first_period(&self, from: OffsetDateTime) -> i64 {
+        let period_days = match self.frequency {
+            Frequency::Daily => self.interval,
+            Frequency::Weekly => self.interval * 7,
+            Frequency::Monthly | Frequency::Yearly => return 0,
+        };
+        if self.count.is_some() {
+            return 0;
+        }
+
+        let elapsed = (from.to_offset(UtcOffset::UTC).date() - self.start.date()).whole_days();
+        (elapsed / period_days - 1).max(0)
+    }
+
+    /// The days of the `period`th day, week, month or year that match the rule, in order. Returns `None` once the
+    /// period is past the end of the calendar.
+    fn period_days(&self, period: i64) -> Option<Vec<Date>> {
+        let first_day = self.start.date();
+        let step = period.checked_mul(self.interval)?;
+
+        let days = match self.frequency {
+            Frequency::Daily => vec![first_day.checked_add(Duration::days(step))?],
+            Frequency::Weekly => {
+                let monday =
+                    first_day.checked_sub(Duration::days(i64::from(first_day.weekday().number_days_from_monday())))?;
+                let week = monday.checked_add(Duration::weeks(step))?;
+
+                let mut weekdays: Vec<Weekday> = self.by_day.iter().map(|(_, weekday)| *weekday).collect();
+                if weekdays.is_empty() {
+                    weekdays.push(first_day.weekday());
+                }
+                weekdays.sort_by_key(|weekday| weekday.number_days_from_monday());
+                weekdays.dedup();
+
+                weekdays
+                    .into_iter()
+                    .map(|weekday| week.checked_add(Duration::days(i64::from(weekday.number_days_from_monday()))))
+                    .collect::<Option<_>>()?
+            }
+            Frequency::Monthly => {
+                let months = i64::from(first_day.year()) * 12 + i64::from(u8::from(first_day.month())) - 1 + step;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = Month::try_from(u8::try_from(months.rem_euclid(12) + 1).ok()?).ok()?;
+                if year > Date::MAX.year() {
+                    return None;
+                }
+
+                let month_days: Vec<Date> = (1..=31)
+                    .filter_map(|day| Date::from_calendar_date(year, month, day).ok())
+                    .collect();
+                let nth = |days: &[Date], n: i8| {
+                    let index = if n > 0 {
+                        usize::try_from(n - 1).ok()?
+                    } else {
+                        days.len().checked_sub(usize::from(n.unsigned_abs()))?
+                    };
+                    days.get(index).copied()
+                };
+
+                let mut days: Vec<Date> = if !self.by_month_day.is_empty() {
+                    self.by_month_day
+                        .iter()
+                        .filter_map(|day| nth(&month_days, *day))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    self.by_day
+                        .iter()
+                        .flat_map(|(ordinal, weekday)| {
+                            let weekdays: Vec<Date> = month_days
+                                .iter()
+                                .copied()
+                                .filter(|day| day.weekday() == *weekday)
+                                .collect();
+                            match ordinal {
+                                Some(n) => nth(&weekdays, *n).into_iter().collect(),
+                                None => weekdays,
+                            }
+                        })
+                        .collect()
+                } else {
+                    Date::from_calendar_date(year, month, first_day.day())
+                        .ok()
+                        .into_iter()
+                        .collect()
+                };
+                days.sort();
+                days.dedup();
+                days
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(i64::from(first_day.year()).checked_add(step)?).ok()?;
+                if year > Date::MAX.year() {
+                    return None;
+                }
+                // February 29 only recurs in leap years
+                Date::from_calendar_date(year, first_day.month(), first_day.day())
+                    .ok()
+                    .into_iter()
+                    .collect()
+            }
+        };
+
+        Some(days)
+    }
+}
+
+/// Parses an `UNTIL`, which includes the whole day when it is a date.
+fn parse_until(value: &str, tz: &'static Tz) -> Option<OffsetDateTime> {
+    let line = ContentLine {
+        name: "UNTIL".to_string(),
+        params: Vec::new(),
+        value: value.to_string(),
+    };
+    let (local, tz, all_day) = parse_local_date_time(&line, tz)?;
+
+    if all_day {
+        Some(calendar::start_of_day(local.date().next_day()?, tz) - Duration::SECOND)
+    } else {
+        Some(local_instant(local, tz, false))
+    }
+}
+
+/// Parses a `BYDAY` entry such as `MO`, `2TU` or `-1FR`.
+fn parse_weekday(value: &str) -> Option<(Option<i8>, Weekday)> {
+    let split = value.len().checked_sub(2)?;
+    let weekday = match value.get(split..)? {
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        "SU" => Weekday::Sunday,
+        _ => return None,
+    };
+
+    let ordinal = match value.get(..split)? {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i8>()
+                .ok()
+                .filter(|ordinal| *ordinal != 0 && ordinal.abs() <= 5)?,
+        ),
+    };
+
+    Some((ordinal, weekday))
+}
+
+/// Parses a `YYYYMMDD` date.
+fn parse_basic_date(value: &str) -> Option<Date> {
+    if value.len() != 8 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let month = Month::try_from(value[4..6].parse::<u8>().ok()?).ok()?;
+
+    Date::from_calendar_date(value[..4].parse().ok()?, month, value[6..].parse().ok()?).ok()
+}
+
+/// Parses a `DURATION` such as `PT1H30M`, `P1D` or `P2W`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let (negative, value) = value.strip_prefix('-').map_or((false, value), |value| (true, value));
+    let value = value.strip_prefix('P')?;
+
+    let mut duration = Duration::ZERO;
+    let mut amount = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                amount.push(c);
+                continue;
+            }
+            'T' if !in_time && amount.is_empty() => {
+                in_time = true;
+                continue;
+            }
+            'W' if !in_time => Duration::weeks(1),
+            'D' if !in_time => Duration::days(1),
+            'H' if in_time => Duration::hours(1),
+            'M' if in_time => Duration::minutes(1),
+            'S' if in_time => Duration::seconds(1),
+            _ => return None,
+        };
+        duration += unit.checked_mul(std::mem::take(&mut amount).parse().ok()?)?;
+    }
+
+    if !amount.is_empty() {
+        return None;
+    }
+    Some(if negative { -duration } else { duration })
+}
+
+/// Splits a comma-separated list value, leaving escaped commas alone.
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        if c == ',' && !escaped {
+            items.push(String::new());
+        } else if let Some(item) = items.last_mut() {
+            item.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    items
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
@@ -1,6 +1,11 @@
 pub mod app;
+pub mod calendar;
 pub mod database;
 pub mod extractors;
+pub mod ical;
 pub mod middleware;
 pub mod oauth;
 pub mod routes;
+pub mod scheduler;
+pub mod validation;
+pub mod vrchat;
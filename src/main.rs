@@ -1,6 +1,6 @@
 use std::env;
 
-use rust_vue_skeleton::{app::App, database::PostgresDatabase, oauth::OAuth};
+use rust_vue_skeleton::{app::App, database::PostgresDatabase, oauth::OAuth, scheduler::SchedulerConfig};
 
 use tokio::net::TcpListener;
 
@@ -35,10 +35,32 @@ async fn main() -> Result<(), std::io::Error> {
 
     let app_key = env::var("APP_KEY").expect("APP_KEY not set");
 
+    let defaults = SchedulerConfig::default();
+    let scheduler_config = SchedulerConfig {
+        reminder_webhook_url: env::var("REMINDER_WEBHOOK_URL").ok().filter(|url| !url.is_empty()),
+        vrchat_api_url: env::var("VRCHAT_API_URL").ok().filter(|url| !url.is_empty()),
+        vrchat_auth_cookie: env::var("VRCHAT_AUTH_COOKIE").ok().filter(|cookie| !cookie.is_empty()),
+        deleted_retention: env::var("DELETED_RETENTION_DAYS")
+            .ok()
+            .filter(|days| !days.is_empty())
+            .map_or(defaults.deleted_retention, |days| {
+                time::Duration::days(days.parse().expect("DELETED_RETENTION_DAYS malformed"))
+            }),
+        archive_after: env::var("EVENT_ARCHIVE_AFTER_DAYS")
+            .ok()
+            .filter(|days| !days.is_empty())
+            .map_or(defaults.archive_after, |days| {
+                // 0 turns archiving off
+                let days: i64 = days.parse().expect("EVENT_ARCHIVE_AFTER_DAYS malformed");
+                (days > 0).then(|| time::Duration::days(days))
+            }),
+        ..defaults
+    };
+
     let listener = TcpListener::bind(("0.0.0.0", port))
         .await
         .expect("Failed to bind to address");
 
-    let app = App::new(db, oauth, app_key);
+    let app = App::new(db, oauth, app_key, scheduler_config);
     app.serve(listener).await
 }
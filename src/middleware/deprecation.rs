@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration as StdDuration,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{
+        HeaderName, HeaderValue,
+        header::{LINK, USER_AGENT},
+    },
+    middleware::Next,
+    response::Response,
+};
+
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppState,
+    database::{DatabaseError, LegacyRequestCount, LegacyRouteModel, PostgresDatabase},
+};
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+/// 2026-10-19T00:00:00Z, when `/api/v1` replaced the unversioned routes.
+const DEPRECATED_AT: HeaderValue = HeaderValue::from_static("@1792368000");
+/// When the unversioned routes are removed.
+const SUNSET_AT: HeaderValue = HeaderValue::from_static("Mon, 19 Apr 2027 00:00:00 GMT");
+const DEPRECATION_LINK: HeaderValue =
+    HeaderValue::from_static("</api/v1/docs/>; rel=\"deprecation\"; type=\"text/html\"");
+/// How often the counted calls are written to the database.
+const FLUSH_INTERVAL: StdDuration = StdDuration::from_mins(1);
+/// How many distinct routes and clients are counted between flushes. Calls by further clients are not counted, so
+/// that made up user agents can't take up memory.
+const MAX_PENDING_COUNTS: usize = 10_000;
+const MAX_USER_AGENT_CHARS: usize = 256;
+
+type CountKey = (String, String, String);
+
+/// Counts calls of the deprecated routes in memory, so that they don't cost a database write each. The counts are
+/// added to the stored ones every minute, and before they are read.
+#[derive(Clone)]
+pub struct LegacyUsage {
+    db: PostgresDatabase,
+    pending: Arc<Mutex<HashMap<CountKey, LegacyRequestCount>>>,
+}
+
+impl LegacyUsage {
+    #[must_use]
+    pub fn new(db: PostgresDatabase) -> Self {
+        Self {
+            db,
+            pending: Arc::default(),
+        }
+    }
+
+    fn record(&self, method: &str, route: &str, user_agent: &str) {
+        let now = OffsetDateTime::now_utc();
+        let user_agent: String = user_agent.chars().take(MAX_USER_AGENT_CHARS).collect();
+        let key = (method.to_string(), route.to_string(), user_agent);
+
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = pending.get_mut(&key) {
+            count.request_count += 1;
+            count.last_used_at = now;
+        } else if pending.len() < MAX_PENDING_COUNTS {
+            let count = LegacyRequestCount {
+                method: key.0.clone(),
+                route: key.1.clone(),
+                user_agent: key.2.clone(),
+                request_count: 1,
+                first_used_at: now,
+                last_used_at: now,
+            };
+            pending.insert(key, count);
+        }
+    }
+
+    /// Writes the counts since the last flush to the database. Counts that could not be written are kept for the
+    /// next flush.
+    ///
+    /// # Errors
+    ///
+    /// Returns the database error the counts could not be written on.
+    pub async fn flush(&self) -> Result<(), DatabaseError> {
+        let counts: Vec<LegacyRequestCount> =
+            std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner))
+                .into_values()
+                .collect();
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.db.record_legacy_requests(&counts).await;
+        if result.is_err() {
+            let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            for count in counts {
+                let key = (count.method.clone(), count.route.clone(), count.user_agent.clone());
+                pending
+                    .entry(key)
+                    .and_modify(|newer| {
+                        newer.request_count += count.request_count;
+                        newer.first_used_at = count.first_used_at;
+                    })
+                    .or_insert(count);
+            }
+        }
+
+        result
+    }
+
+    /// Flushes the counts every minute, forever.
+    pub async fn flush_periodically(self) {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.flush().await {
+                tracing::warn!(?error, "failed to store legacy route usage");
+            }
+        }
+    }
+}
+
+/// Marks responses of the unversioned routes as deprecated and announces when they go away.
+///
+/// The headers follow RFC 9745 and RFC 8594. Every request is counted per route and client in [`LegacyUsage`], so we
+/// can tell who still has to move to `/api/v1`.
+pub async fn deprecated(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if let Some(route) = req.extensions().get::<MatchedPath>() {
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        state
+            .legacy_usage
+            .record(req.method().as_str(), route.as_str(), user_agent);
+    }
+
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, DEPRECATED_AT);
+    headers.insert(SUNSET, SUNSET_AT);
+    headers.append(LINK, DEPRECATION_LINK);
+
+    response
+}
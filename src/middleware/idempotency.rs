@@ -0,0 +1,224 @@
+use std::{convert::Infallible, time::Duration as StdDuration};
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{
+        HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+        header::{CONTENT_TYPE, LOCATION},
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppState,
+    database::{ApiUserModel, IdempotencyClaim, IdempotencyModel, IdempotencyRecord, PostgresDatabase, StoredResponse},
+    routes::ApiError,
+};
+
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+const MAX_KEY_LENGTH: usize = 255;
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// How often a request that is still being handled renews its claim, well within
+/// [`IDEMPOTENCY_KEY_LEASE`](crate::database::IDEMPOTENCY_KEY_LEASE).
+const CLAIM_RENEWAL_INTERVAL: StdDuration = StdDuration::from_secs(15);
+
+/// Makes `POST` requests from API users safe to retry: when an `Idempotency-Key` header is sent, the first response
+/// is stored and replayed for every retry with the same key and body.
+pub async fn idempotency(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+
+    let (Some(idempotency_key), Some(api_key)) = (
+        header(req.headers(), &IDEMPOTENCY_KEY),
+        header(req.headers(), "x-api-key"),
+    ) else {
+        return next.run(req).await;
+    };
+
+    if idempotency_key.is_empty() || idempotency_key.len() > MAX_KEY_LENGTH {
+        return ApiError::BadRequest.into_response();
+    }
+
+    // unauthenticated requests are rejected by the handler and never stored
+    match state.db.validate_api_key(&api_key).await {
+        Ok(true) => {}
+        Ok(false) => return next.run(req).await,
+        Err(e) => return ApiError::from(e).into_response(),
+    }
+
+    let (parts, body) = req.into_parts();
+    let Ok(body) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return ApiError::BadRequest.into_response();
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(parts.method.as_str());
+    hasher.update(b" ");
+    hasher.update(parts.uri.to_string());
+    hasher.update(b"\n");
+    hasher.update(&body);
+    let request_hash = format!("{:x}", hasher.finalize());
+
+    let locked_at = match state
+        .db
+        .claim_idempotency_key(&api_key, &idempotency_key, &request_hash)
+        .await
+    {
+        Ok(IdempotencyClaim::Claimed { locked_at }) => locked_at,
+        Ok(IdempotencyClaim::Used(record)) if record.request_hash != request_hash => {
+            return ApiError::IdempotencyKeyReused.into_response();
+        }
+        Ok(IdempotencyClaim::Used(record)) => return replay(record),
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let claim = Claim {
+        db: state.db.clone(),
+        api_key,
+        idempotency_key,
+        locked_at,
+        settled: false,
+    };
+
+    let response = tokio::select! {
+        response = next.run(Request::from_parts(parts, Body::from(body))) => response,
+        never = claim.renew() => match never {},
+    };
+
+    // server errors are not stored, so that the retry gets another chance
+    if response.status().is_server_error() {
+        claim.release().await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        claim.release().await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    claim
+        .store(StoredResponse {
+            status: i32::from(parts.status.as_u16()),
+            content_type: header(&parts.headers, CONTENT_TYPE),
+            location: header(&parts.headers, LOCATION),
+            body: body.to_vec(),
+        })
+        .await;
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// A claimed key, which is released if the request never gets to store its response: when the handler panics, or
+/// the client disconnects and the request is dropped mid-way. Otherwise every retry would be answered with
+/// `409 Conflict` until the claim's lease runs out.
+struct Claim {
+    db: PostgresDatabase,
+    api_key: String,
+    idempotency_key: String,
+    locked_at: OffsetDateTime,
+    settled: bool,
+}
+
+impl Claim {
+    /// Keeps renewing the claim while the request is handled, until this future is dropped.
+    async fn renew(&self) -> Infallible {
+        let mut interval = tokio::time::interval(CLAIM_RENEWAL_INTERVAL);
+        // the first tick completes immediately, right after the key was claimed
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = self
+                .db
+                .renew_idempotency_key(&self.api_key, &self.idempotency_key, self.locked_at)
+                .await
+            {
+                tracing::warn!(error = ?e, "failed to renew idempotency key");
+            }
+        }
+    }
+
+    async fn store(mut self, response: StoredResponse) {
+        self.settled = true;
+        if let Err(e) = self
+            .db
+            .store_idempotent_response(&self.api_key, &self.idempotency_key, self.locked_at, response)
+            .await
+        {
+            // without a stored response the retry would be told to wait forever, so it is handled again instead
+            tracing::error!(error = ?e, "failed to store idempotent response");
+            self.release_now().await;
+        }
+    }
+
+    async fn release(mut self) {
+        self.settled = true;
+        self.release_now().await;
+    }
+
+    async fn release_now(&self) {
+        if let Err(e) = self
+            .db
+            .release_idempotency_key(&self.api_key, &self.idempotency_key, self.locked_at)
+            .await
+        {
+            tracing::error!(error = ?e, "failed to release idempotency key");
+        }
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+
+        let claim = Self {
+            db: self.db.clone(),
+            api_key: std::mem::take(&mut self.api_key),
+            idempotency_key: std::mem::take(&mut self.idempotency_key),
+            locked_at: self.locked_at,
+            settled: true,
+        };
+        tokio::spawn(async move { claim.release_now().await });
+    }
+}
+
+fn header(headers: &HeaderMap, name: impl axum::http::header::AsHeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+fn replay(record: IdempotencyRecord) -> Response {
+    let (Some(status), Some(body)) = (record.response_status, record.response_body) else {
+        return ApiError::IdempotencyKeyInUse.into_response();
+    };
+
+    let status = u16::try_from(status)
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let mut response = (status, body).into_response();
+    let headers = response.headers_mut();
+    headers.remove(CONTENT_TYPE);
+    for (name, value) in [
+        (CONTENT_TYPE, record.response_content_type),
+        (LOCATION, record.response_location),
+    ] {
+        if let Some(value) = value.and_then(|value| HeaderValue::from_str(&value).ok()) {
+            headers.insert(name, value);
+        }
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    response
+}
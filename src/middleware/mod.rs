@@ -1,3 +1,9 @@
+mod deprecation;
+mod idempotency;
+mod problem;
 mod session;
 
+pub use deprecation::*;
+pub use idempotency::*;
+pub use problem::*;
 pub use session::*;
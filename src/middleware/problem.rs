@@ -0,0 +1,80 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{
+        HeaderName, StatusCode,
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+    },
+    middleware::Next,
+    response::Response,
+};
+
+use crate::routes::{PROBLEM_JSON, Problem, ProblemCode};
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Adds the request id and path to problem details, and turns the plain-text errors of axum's router and
+/// extractors on `/api` into problem details, so that every API error carries a `code`.
+pub async fn problem_details(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string);
+
+    let response = next.run(req).await;
+    let status = response.status();
+    let is_problem = response.headers().get(CONTENT_TYPE) == Some(&PROBLEM_JSON);
+    let is_api_error =
+        (path == "/api" || path.starts_with("/api/")) && (status.is_client_error() || status.is_server_error());
+    if !is_problem && !is_api_error {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let mut problem = match to_bytes(body, MAX_BODY_BYTES).await {
+        // also the stored bodies of replayed idempotent requests, which were never sent with this request's id
+        Ok(body) if is_problem => match serde_json::from_slice::<Problem>(&body) {
+            Ok(problem) => problem,
+            Err(_) => return Response::from_parts(parts, Body::from(body)),
+        },
+        Ok(body) => {
+            let text = String::from_utf8_lossy(&body).trim().to_string();
+            let detail = if status.is_server_error() {
+                if !text.is_empty() {
+                    tracing::error!(%status, body = text, "server error");
+                }
+                None
+            } else {
+                Some(text).filter(|text| !text.is_empty())
+            };
+            status_problem(status, detail)
+        }
+        // the body is gone, so answer with what the status alone tells
+        Err(error) => {
+            tracing::warn!(%status, ?error, "failed to read the body of an error response");
+            status_problem(status, None)
+        }
+    };
+    problem.instance = Some(path);
+    problem.request_id = request_id;
+
+    // the original body is replaced either way, its length no longer applies
+    parts.headers.remove(CONTENT_LENGTH);
+    let Ok(body) = serde_json::to_vec(&problem) else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    parts.headers.insert(CONTENT_TYPE, PROBLEM_JSON);
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// A problem with the code of `status`, keeping the status itself even where the code's usual one differs.
+fn status_problem(status: StatusCode, detail: Option<String>) -> Problem {
+    Problem {
+        status: status.as_u16(),
+        ..Problem::new(ProblemCode::from_status(status), detail)
+    }
+}
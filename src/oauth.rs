@@ -6,6 +6,7 @@ use oauth2::{
 };
 use reqwest;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::app::AppState;
 
@@ -14,7 +15,7 @@ const DISCORD_AUTHORIZE_URL: &str = "https://discord.com/oauth2/authorize";
 const DISCORD_TOKEN_URL: &str = "https://discord.com/api/oauth2/token";
 const DISCORD_TOKEN_REVOKE_URL: &str = "https://discord.com/api/oauth2/token/revoke";
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
 pub struct DiscordInfo {
     pub id: String,
     pub username: String,
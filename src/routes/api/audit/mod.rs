@@ -0,0 +1,14 @@
+mod view;
+
+use axum::{Router, routing::get};
+
+use crate::app::AppState;
+
+pub struct AuditRoutes;
+
+impl AuditRoutes {
+    pub fn router() -> Router<AppState> {
+        // /audit?entity_type=event&actor=...&since=... to filter, newest first
+        Router::<AppState>::new().route("/audit", get(view::query_audit_log))
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{AuditFilters, AuditModel},
+    extractors::AuthenticatedApiUser,
+    routes::{ApiError, page_params},
+};
+
+#[tracing::instrument(skip(app_state))]
+pub async fn query_audit_log(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (limit, offset) = page_params(&query)?;
+    let filters = AuditFilters::parse(&query)?;
+
+    let page = app_state.db.query_audit_log(&filters, limit, offset).await?;
+
+    Ok(Json(page))
+}
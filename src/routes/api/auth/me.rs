@@ -1,7 +1,23 @@
 use axum::{Json, extract::State, response::IntoResponse};
 
-use crate::{app::AppState, extractors::ApiSession, routes::ApiError};
+use crate::{
+    app::AppState,
+    extractors::ApiSession,
+    oauth::DiscordInfo,
+    routes::{ApiError, Problem},
+};
 
+/// The Discord account the session is signed in with.
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "auth",
+    responses(
+        (status = OK, body = DiscordInfo),
+        (status = UNAUTHORIZED, body = Problem),
+    ),
+    security(("session" = [])),
+)]
 #[tracing::instrument(skip(app_state, session))]
 pub async fn me(
     State(app_state): State<AppState>,
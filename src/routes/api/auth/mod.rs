@@ -1,6 +1,7 @@
 mod me;
 
-use axum::{Router, routing::get};
+use axum::Router;
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::app::AppState;
 
@@ -8,6 +9,17 @@ pub struct AuthRoutes;
 
 impl AuthRoutes {
     pub fn router() -> Router<AppState> {
-        Router::<AppState>::new().route("/auth/me", get(me::me))
+        Self::routes().into()
+    }
+
+    /// The routes of [`Self::router`] along with their description, so that every route is registered once.
+    fn routes() -> OpenApiRouter<AppState> {
+        OpenApiRouter::new().routes(routes!(me::me))
+    }
+
+    /// Describes the routes of [`Self::router`], relative to where it is nested.
+    #[must_use]
+    pub fn openapi() -> utoipa::openapi::OpenApi {
+        Self::routes().into_openapi()
     }
 }
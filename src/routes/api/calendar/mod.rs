@@ -0,0 +1,14 @@
+mod view;
+
+use axum::{Router, routing::get};
+
+use crate::app::AppState;
+
+pub struct CalendarRoutes;
+
+impl CalendarRoutes {
+    pub fn router() -> Router<AppState> {
+        // /calendar?view=month&date=2026-10&tz=Europe/Berlin, plus any /events filter such as group_id
+        Router::<AppState>::new().route("/calendar", get(view::calendar))
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use time_tz::{OffsetDateTimeExt, TimeZone};
+
+use crate::{
+    app::AppState,
+    calendar::{self, CalendarDay, CalendarRange, CalendarView},
+    database::{ArchiveModel, EventFilters},
+    routes::ApiError,
+    validation::ValidationErrors,
+};
+
+const DEFAULT_EVENTS_PER_DAY: usize = 10;
+const MAX_EVENTS_PER_DAY: usize = 100;
+
+#[derive(Serialize)]
+struct CalendarResponse<'a> {
+    view: CalendarView,
+    tz: &'static str,
+    first_day: String,
+    last_day: String,
+    /// Distinct events in the range, counting multi-day events once.
+    total: usize,
+    days: Vec<CalendarDay<'a>>,
+}
+
+/// Buckets events into the days of a day, week or month view in the time zone `tz`. `date` defaults to today, and
+/// `max_per_day` limits how many events are listed per day, the rest are counted as overflow.
+#[tracing::instrument(skip(app_state))]
+pub async fn calendar(
+    State(app_state): State<AppState>,
+    Query(mut query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut errors = ValidationErrors::default();
+
+    let view = query
+        .remove("view")
+        .map_or(Some(CalendarView::Month), |view| CalendarView::parse(&view));
+    if view.is_none() {
+        errors.add("view", "must be one of day, week, month");
+    }
+
+    let tz = calendar::resolve_timezone(query.remove("tz").as_deref());
+    if tz.is_none() {
+        errors.add("tz", "must be an IANA time zone name such as 'Europe/Berlin'");
+    }
+
+    let max_per_day = match query.remove("max_per_day").map(|value| value.parse::<usize>()) {
+        None => Some(DEFAULT_EVENTS_PER_DAY),
+        Some(Ok(max_per_day)) => Some(max_per_day.clamp(1, MAX_EVENTS_PER_DAY)),
+        Some(Err(_)) => {
+            errors.add("max_per_day", "must be a positive number");
+            None
+        }
+    };
+
+    let date = query.remove("date");
+    let (Some(view), Some(tz), Some(max_per_day)) = (view, tz, max_per_day) else {
+        return Err(errors.into());
+    };
+
+    let date = date.unwrap_or_else(|| OffsetDateTime::now_utc().to_timezone(tz).date().to_string());
+    let Some(range) = CalendarRange::new(view, &date, tz) else {
+        errors.add(
+            "date",
+            "must be a date such as 2026-10-19, or a month such as 2026-10 for the month view",
+        );
+        return Err(errors.into());
+    };
+
+    // the remaining parameters filter like /events, limited to events overlapping the range. past ranges are
+    // mostly archived, so the archive is included
+    for key in ["from", "to", "starts_at", "ends_at"] {
+        query.remove(key);
+    }
+    let filters = EventFilters {
+        from: Some(range.start()),
+        to: Some(range.end()),
+        ..EventFilters::parse(&query)?
+    };
+    let events = app_state.db.query_events_with_archive(&filters).await?;
+
+    Ok(Json(CalendarResponse {
+        view,
+        tz: tz.name(),
+        first_day: range.first_day.to_string(),
+        last_day: range.last_day.to_string(),
+        total: events.len(),
+        days: calendar::bucket_events(&range, &events, max_per_day),
+    })
+    .into_response())
+}
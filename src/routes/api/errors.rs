@@ -1,24 +1,224 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
     response::{IntoResponse, Response},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::{database::DatabaseError, oauth::OAuthError};
+use crate::{
+    database::{ConflictingEvent, DatabaseError},
+    oauth::OAuthError,
+    validation::{FieldError, ValidationErrors},
+};
+
+pub const PROBLEM_JSON: HeaderValue = HeaderValue::from_static("application/problem+json");
+
+/// What went wrong, stable across releases so clients can branch on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemCode {
+    AlreadyExists,
+    BadRequest,
+    /// A client error without a more specific code, e.g. `409 Conflict` from outside the API's own handlers.
+    ClientError,
+    Conflict,
+    Forbidden,
+    IdempotencyKeyInUse,
+    IdempotencyKeyReused,
+    InUse,
+    InternalError,
+    InvalidReference,
+    MethodNotAllowed,
+    NotFound,
+    #[serde(rename = "oauth_failed")]
+    OAuthFailed,
+    PayloadTooLarge,
+    PreconditionFailed,
+    ScheduleConflict,
+    TooManyRequests,
+    Unauthorized,
+    UnsupportedMediaType,
+    ValidationFailed,
+}
+
+impl ProblemCode {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AlreadyExists => "already_exists",
+            Self::BadRequest => "bad_request",
+            Self::ClientError => "client_error",
+            Self::Conflict => "conflict",
+            Self::Forbidden => "forbidden",
+            Self::IdempotencyKeyInUse => "idempotency_key_in_use",
+            Self::IdempotencyKeyReused => "idempotency_key_reused",
+            Self::InUse => "in_use",
+            Self::InternalError => "internal_error",
+            Self::InvalidReference => "invalid_reference",
+            Self::MethodNotAllowed => "method_not_allowed",
+            Self::NotFound => "not_found",
+            Self::OAuthFailed => "oauth_failed",
+            Self::PayloadTooLarge => "payload_too_large",
+            Self::PreconditionFailed => "precondition_failed",
+            Self::ScheduleConflict => "schedule_conflict",
+            Self::TooManyRequests => "too_many_requests",
+            Self::Unauthorized => "unauthorized",
+            Self::UnsupportedMediaType => "unsupported_media_type",
+            Self::ValidationFailed => "validation_failed",
+        }
+    }
+
+    #[must_use]
+    pub const fn status(self) -> StatusCode {
+        match self {
+            Self::BadRequest | Self::ClientError => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::AlreadyExists | Self::Conflict | Self::IdempotencyKeyInUse | Self::InUse | Self::ScheduleConflict => {
+                StatusCode::CONFLICT
+            }
+            Self::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            Self::IdempotencyKeyReused | Self::InvalidReference | Self::ValidationFailed => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            Self::InternalError | Self::OAuthFailed => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The summary every problem with this code shares.
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::AlreadyExists => "the resource already exists",
+            Self::BadRequest => "the request was malformed",
+            Self::ClientError => "the request could not be handled",
+            Self::Conflict => "the request conflicts with the current state of the resource",
+            Self::Forbidden => "you are not allowed to do this",
+            Self::IdempotencyKeyInUse => "a request with this Idempotency-Key is still being processed",
+            Self::IdempotencyKeyReused => "the Idempotency-Key was already used for a different request",
+            Self::InUse => "the resource is still in use",
+            Self::InternalError => "internal server error",
+            Self::InvalidReference => "the request refers to a resource that does not exist",
+            Self::MethodNotAllowed => "the method is not allowed on this resource",
+            Self::NotFound => "resource not found",
+            Self::OAuthFailed => "signing in with Discord failed",
+            Self::PayloadTooLarge => "the request body is too large",
+            Self::PreconditionFailed => "the resource has been modified",
+            Self::ScheduleConflict => "the event overlaps other events of its group",
+            Self::TooManyRequests => "too many requests, retry later",
+            Self::Unauthorized => "you are not authorized to access this content",
+            Self::UnsupportedMediaType => "unsupported content type",
+            Self::ValidationFailed => "the request failed validation",
+        }
+    }
 
-#[derive(Serialize)]
-struct ApiErrorResponse<'a> {
-    message: &'a str,
-    detail: Option<String>,
+    /// The code for an error response that did not come from an [`ApiError`], e.g. one of axum's extractor
+    /// rejections. Statuses without a code of their own get `client_error` or `internal_error`, never the code of a
+    /// different status.
+    #[must_use]
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST => Self::BadRequest,
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => Self::MethodNotAllowed,
+            StatusCode::CONFLICT => Self::Conflict,
+            StatusCode::PRECONDITION_FAILED => Self::PreconditionFailed,
+            StatusCode::PAYLOAD_TOO_LARGE => Self::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => Self::UnsupportedMediaType,
+            StatusCode::UNPROCESSABLE_ENTITY => Self::ValidationFailed,
+            StatusCode::TOO_MANY_REQUESTS => Self::TooManyRequests,
+            status if status.is_client_error() => Self::ClientError,
+            _ => Self::InternalError,
+        }
+    }
+}
+
+/// The body of every error response, an RFC 7807 problem details object sent as `application/problem+json`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Problem {
+    /// `urn:vrc-calendar:problem:` followed by the `code`. It identifies the problem and does not resolve to a
+    /// document.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// The same for every problem with this `code`.
+    pub title: String,
+    pub status: u16,
+    pub code: ProblemCode,
+    /// What exactly went wrong with this request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The path of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// The `x-request-id` of the request, to quote when reporting a server error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Every field that failed validation, on `validation_failed` problems.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// The overlapping events, on `schedule_conflict` problems.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ConflictingEvent>,
+    /// The events that still belong to the resource, on `in_use` problems.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_ids: Vec<String>,
+}
+
+impl Problem {
+    #[must_use]
+    pub fn new(code: ProblemCode, detail: Option<String>) -> Self {
+        Self {
+            problem_type: format!("urn:vrc-calendar:problem:{}", code.as_str()),
+            title: code.title().to_string(),
+            status: code.status().as_u16(),
+            code,
+            detail,
+            instance: None,
+            request_id: None,
+            errors: Vec::new(),
+            conflicts: Vec::new(),
+            event_ids: Vec::new(),
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, [(CONTENT_TYPE, PROBLEM_JSON)], Json(self)).into_response()
+    }
 }
 
 pub enum ApiError {
     BadRequest,
+    /// The request conflicts with an existing resource, e.g. a duplicate ID.
+    Conflict(String),
     DatabaseError(DatabaseError),
+    /// A request with the same `Idempotency-Key` is still being handled.
+    IdempotencyKeyInUse,
+    /// The `Idempotency-Key` was already used for a request with a different body.
+    IdempotencyKeyReused,
+    /// The resource cannot be deleted while these events still belong to it.
+    InUse(String, Vec<String>),
+    /// The request refers to a resource that does not exist, e.g. an unknown `vrc_group_id`.
+    InvalidReference(String),
     OAuthError(String),
     NotFound,
+    /// The event overlaps these events of the same group and the client asked for that to be rejected.
+    ScheduleConflict(Vec<ConflictingEvent>),
+    /// An `If-Match` precondition did not hold, i.e. the resource was changed since the client last fetched it.
+    PreconditionFailed,
     Unauthorized(Option<String>),
+    UnsupportedMediaType,
+    ValidationFailed(ValidationErrors),
 }
 
 impl IntoResponse for ApiError {
@@ -29,60 +229,59 @@ impl IntoResponse for ApiError {
 
 impl From<ApiError> for Response {
     fn from(value: ApiError) -> Self {
+        Problem::from(value).into_response()
+    }
+}
+
+impl From<ApiError> for Problem {
+    fn from(value: ApiError) -> Self {
+        // what failed inside the server is only logged, the client gets the request id to report instead
+        const LOGGED: &str = "the cause was logged, quote the request_id when reporting this";
+
         match value {
-            ApiError::BadRequest => (
-                StatusCode::BAD_REQUEST,
-                Json(ApiErrorResponse {
-                    message: "the request was malformed",
-                    detail: None,
-                }),
-            ),
+            ApiError::BadRequest => Self::new(ProblemCode::BadRequest, None),
+            ApiError::Conflict(detail) => Self::new(ProblemCode::AlreadyExists, Some(detail)),
             ApiError::DatabaseError(error) => match error {
-                DatabaseError::RngError => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiErrorResponse {
-                        message: "internal server error",
-                        detail: Some("failed to generate random number".to_string()),
-                    }),
-                ),
-                DatabaseError::SerdeError(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiErrorResponse {
-                        message: "serialization error",
-                        detail: Some(e.to_string()),
-                    }),
-                ),
-                DatabaseError::SqlxError(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiErrorResponse {
-                        message: "database error",
-                        detail: Some(e.to_string()),
-                    }),
-                ),
+                error @ (DatabaseError::UniqueViolation(_)
+                | DatabaseError::ForeignKeyViolation(_)
+                | DatabaseError::CheckViolation(_)
+                | DatabaseError::GroupDeleted
+                | DatabaseError::InvalidData(_)) => ApiError::from(error).into(),
+                error => {
+                    tracing::error!(?error, "database error");
+                    Self::new(ProblemCode::InternalError, Some(LOGGED.to_string()))
+                }
             },
-            ApiError::OAuthError(detail) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiErrorResponse {
-                    message: "oauth error",
-                    detail: Some(detail),
-                }),
-            ),
-            ApiError::NotFound => (
-                StatusCode::NOT_FOUND,
-                Json(ApiErrorResponse {
-                    message: "resource not found",
-                    detail: None,
-                }),
+            ApiError::IdempotencyKeyInUse => Self::new(ProblemCode::IdempotencyKeyInUse, None),
+            ApiError::IdempotencyKeyReused => Self::new(ProblemCode::IdempotencyKeyReused, None),
+            ApiError::InUse(detail, event_ids) => Self {
+                event_ids,
+                ..Self::new(ProblemCode::InUse, Some(detail))
+            },
+            ApiError::InvalidReference(detail) => Self::new(ProblemCode::InvalidReference, Some(detail)),
+            ApiError::OAuthError(detail) => {
+                tracing::error!(detail, "oauth error");
+                Self::new(ProblemCode::OAuthFailed, Some(LOGGED.to_string()))
+            }
+            ApiError::NotFound => Self::new(ProblemCode::NotFound, None),
+            ApiError::PreconditionFailed => Self::new(
+                ProblemCode::PreconditionFailed,
+                Some("fetch the current version and retry with its ETag".to_string()),
             ),
-            ApiError::Unauthorized(detail) => (
-                StatusCode::UNAUTHORIZED,
-                Json(ApiErrorResponse {
-                    message: "you are not authorized to access this content",
-                    detail,
-                }),
+            ApiError::Unauthorized(detail) => Self::new(ProblemCode::Unauthorized, detail),
+            ApiError::UnsupportedMediaType => Self::new(
+                ProblemCode::UnsupportedMediaType,
+                Some("expected application/merge-patch+json".to_string()),
             ),
+            ApiError::ScheduleConflict(conflicts) => Self {
+                conflicts,
+                ..Self::new(ProblemCode::ScheduleConflict, None)
+            },
+            ApiError::ValidationFailed(errors) => Self {
+                errors: errors.errors().to_vec(),
+                ..Self::new(ProblemCode::ValidationFailed, None)
+            },
         }
-        .into_response()
     }
 }
 
@@ -100,6 +299,63 @@ impl From<OAuthError> for ApiError {
 
 impl From<DatabaseError> for ApiError {
     fn from(value: DatabaseError) -> Self {
-        Self::DatabaseError(value)
+        match value {
+            DatabaseError::UniqueViolation(constraint) => Self::Conflict(match constraint.as_str() {
+                "events_pkey" => "an event with this vrc_event_id already exists".to_string(),
+                "groups_pkey" => "a group with this vrc_group_id already exists".to_string(),
+                "groups_name_key" => "a group with this name already exists".to_string(),
+                "groups_short_code_key" => "a group with this short_code already exists".to_string(),
+                // constraint names are internal, the client only learns what kind of problem it was
+                _ => {
+                    tracing::warn!(constraint, "unique violation without a description");
+                    "a resource with these values already exists".to_string()
+                }
+            }),
+            DatabaseError::ForeignKeyViolation(constraint) => match constraint.as_str() {
+                // the lookup tables of the event enums, which the API validates against its own list first
+                "events_category_fkey" | "events_access_type_fkey" => {
+                    let field = constraint.trim_start_matches("events_").trim_end_matches("_fkey");
+                    let mut errors = ValidationErrors::default();
+                    errors.add(field, "is not a known value");
+                    Self::ValidationFailed(errors)
+                }
+                "events_vrc_group_id_fkey" => {
+                    Self::InvalidReference("vrc_group_id does not refer to an existing group".to_string())
+                }
+                _ => {
+                    tracing::warn!(constraint, "foreign key violation without a description");
+                    Self::InvalidReference("refers to a resource that does not exist".to_string())
+                }
+            },
+            DatabaseError::GroupDeleted => {
+                Self::InvalidReference("vrc_group_id refers to a deleted group, restore the group first".to_string())
+            }
+            DatabaseError::ScheduleConflict(conflicts) => Self::ScheduleConflict(conflicts),
+            DatabaseError::CheckViolation(constraint) => {
+                let mut errors = ValidationErrors::default();
+                match constraint.as_str() {
+                    "check_event_dates" => errors.add("ends_at", "must not be before starts_at"),
+                    "check_event_platforms" => errors.add("platforms", "must only contain known platforms"),
+                    _ => {
+                        tracing::warn!(constraint, "check violation without a description");
+                        errors.add("body", "contains a value that is not allowed");
+                    }
+                }
+                Self::ValidationFailed(errors)
+            }
+            DatabaseError::InvalidData(message) => {
+                tracing::warn!(message, "the database rejected a value");
+                let mut errors = ValidationErrors::default();
+                errors.add("body", "contains a value that cannot be stored, e.g. a NUL character");
+                Self::ValidationFailed(errors)
+            }
+            value => Self::DatabaseError(value),
+        }
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(value: ValidationErrors) -> Self {
+        Self::ValidationFailed(value)
     }
 }
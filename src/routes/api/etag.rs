@@ -0,0 +1,49 @@
+use axum::{
+    Json,
+    http::{StatusCode, header::ETAG},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{database::Event, extractors::Preconditions};
+
+/// The strong `ETag` of a single versioned resource.
+#[must_use]
+pub fn version_etag(version: i32) -> String {
+    format!("\"{version}\"")
+}
+
+/// The strong `ETag` of an event: its version followed by its attendee counts.
+///
+/// RSVPs change the counts without a new version, so check `If-Match` with
+/// [`Preconditions::check_if_match_version`], which only compares the version.
+#[must_use]
+pub fn event_etag(event: &Event) -> String {
+    format!("\"{}.{}.{}\"", event.version, event.going_count, event.interested_count)
+}
+
+/// A weak `ETag` for a list, which changes whenever one of its resources is added, removed or modified.
+///
+/// It hashes their IDs and `ETag`s with SHA-256, which unlike the std hasher is the same in every build.
+#[must_use]
+pub fn collection_etag<'a>(etags: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let mut hasher = Sha256::new();
+    for (id, etag) in etags {
+        hasher.update(id);
+        hasher.update(b" ");
+        hasher.update(etag);
+        hasher.update(b"\n");
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    format!("W/\"{}\"", &digest[..32])
+}
+
+/// Responds with `body` tagged with `etag`, or with `304 Not Modified` if the client already has it.
+pub fn conditional_json(preconditions: &Preconditions, etag: String, body: impl Serialize) -> Response {
+    if preconditions.is_not_modified(&etag) {
+        (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response()
+    } else {
+        ([(ETAG, etag)], Json(body)).into_response()
+    }
+}
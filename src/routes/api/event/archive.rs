@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{ArchiveModel, ArchivedEvent, ArchivedEventPage, EventFilters},
+    routes::{ApiError, Problem, page_params},
+};
+
+/// Browses events that ended long enough ago to be archived. Filters like `/events` and pages with
+/// `limit`/`offset`.
+#[utoipa::path(
+    get,
+    path = "/events/archive",
+    tag = "events",
+    params(
+        ("limit" = Option<i64>, Query, description = "The most events to return, 50 by default and at most 200"),
+        ("offset" = Option<i64>, Query, description = "How many events to skip"),
+        ("group_id" = Option<String>, Query, description = "Only events of this group"),
+        ("from" = Option<String>, Query, description = "Only events overlapping the range from this date or RFC 3339 timestamp"),
+        ("to" = Option<String>, Query, description = "Only events overlapping the range up to this date or RFC 3339 timestamp, a date includes the whole day"),
+        ("starts_at" = Option<String>, Query, description = "Only events starting at or after this date or RFC 3339 timestamp"),
+        ("ends_at" = Option<String>, Query, description = "Only events ending at or before this date or RFC 3339 timestamp"),
+        ("tz" = Option<String>, Query, description = "The IANA time zone dates are in, UTC by default"),
+        ("category" = Option<String>, Query, description = "Comma-separated categories, matching events with any of them"),
+        ("access_type" = Option<String>, Query, description = "Comma-separated access types, matching events with any of them"),
+        ("platforms" = Option<String>, Query, description = "Comma-separated platforms, matching events supporting any of them"),
+        ("tags" = Option<String>, Query, description = "Comma-separated tags or tag aliases, matching events with any of them"),
+    ),
+    responses(
+        (status = OK, body = ArchivedEventPage),
+        (status = BAD_REQUEST, description = "`limit` or `offset` is not a whole number", body = Problem),
+        (status = UNPROCESSABLE_ENTITY, description = "A filter is not a valid date, timestamp or time zone", body = Problem),
+    ),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn get_archived_events(
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (limit, offset) = page_params(&query)?;
+    let filters = EventFilters::parse(&query)?;
+
+    let page = app_state.db.query_archived_events(&filters, limit, offset).await?;
+
+    Ok(Json(page))
+}
+
+/// A single archived event.
+#[utoipa::path(
+    get,
+    path = "/events/archive/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = OK, body = ArchivedEvent),
+        (status = NOT_FOUND, body = Problem),
+    ),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn view_archived_event(
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let event = app_state.db.get_archived_event(id).await?.ok_or(ApiError::NotFound)?;
+
+    Ok(Json(event))
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, ConflictingEvent, CreateEvent, DatabaseError, EventModel, UpsertOutcome},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::{ApiError, Problem, flag_param},
+    validation::{FieldError, Validate, ValidationErrors},
+};
+
+/// The most events accepted by one bulk request.
+const MAX_BULK_EVENTS: usize = 500;
+
+#[derive(Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum BulkStatus {
+    Created,
+    Updated,
+    Unchanged,
+    Error,
+}
+
+#[derive(Serialize, ToSchema)]
+struct BulkResult {
+    vrc_event_id: String,
+    status: BulkStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+    /// The events of the same group this one overlaps, which failed it if `reject_conflicts` was set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<ConflictingEvent>,
+}
+
+#[derive(Default, Serialize, ToSchema)]
+struct BulkSummary {
+    created: usize,
+    updated: usize,
+    unchanged: usize,
+    failed: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+struct BulkResponse {
+    #[serde(flatten)]
+    summary: BulkSummary,
+    results: Vec<BulkResult>,
+}
+
+/// Creates or updates up to 500 events in one request. Every event succeeds or fails on its own, and the response
+/// reports how each one went in the order they were sent.
+///
+/// Overlaps with other events of the same group, including earlier events of the batch, are listed in each result's
+/// `conflicts`.
+#[utoipa::path(
+    post,
+    path = "/events/bulk",
+    tag = "events",
+    params(
+        ("reject_conflicts" = Option<bool>, Query, description = "Fail each event that overlaps other events of its group"),
+    ),
+    request_body = Vec<CreateEvent>,
+    responses(
+        (status = OK, body = BulkResponse),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, description = "More than 500 events were sent", body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state, create_events))]
+pub async fn bulk_upsert_events(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+    Json(create_events): Json<Vec<CreateEvent>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    if create_events.len() > MAX_BULK_EVENTS {
+        let mut errors = ValidationErrors::default();
+        errors.add("body", format!("must contain at most {MAX_BULK_EVENTS} events"));
+        return Err(errors.into());
+    }
+    let reject_conflicts = flag_param(&query, "reject_conflicts")?;
+
+    // invalid events are reported without being sent to the database
+    let mut results = Vec::with_capacity(create_events.len());
+    let mut valid_events = Vec::with_capacity(create_events.len());
+    for create_event in create_events {
+        let errors = create_event.validate().err().unwrap_or_default();
+        results.push(BulkResult {
+            vrc_event_id: create_event.vrc_event_id.clone(),
+            status: BulkStatus::Error,
+            errors: errors.errors().to_vec(),
+            conflicts: Vec::new(),
+        });
+        if errors.errors().is_empty() {
+            valid_events.push((results.len() - 1, create_event));
+        }
+    }
+
+    let (indices, valid_events): (Vec<_>, Vec<_>) = valid_events.into_iter().unzip();
+    let outcomes = app_state
+        .db
+        .upsert_events(valid_events, reject_conflicts, &audit)
+        .await?;
+
+    for (index, outcome) in indices.into_iter().zip(outcomes) {
+        let result = &mut results[index];
+        match outcome {
+            Ok((outcome, conflicts)) => {
+                result.conflicts = conflicts;
+                match outcome {
+                    UpsertOutcome::Created => result.status = BulkStatus::Created,
+                    UpsertOutcome::Updated => result.status = BulkStatus::Updated,
+                    UpsertOutcome::Unchanged => result.status = BulkStatus::Unchanged,
+                    UpsertOutcome::GroupMismatch => result.errors.push(FieldError {
+                        field: "vrc_group_id".to_string(),
+                        message: "cannot be changed".to_string(),
+                    }),
+                    UpsertOutcome::Deleted => result.errors.push(FieldError {
+                        field: "vrc_event_id".to_string(),
+                        message: "was deleted, restore it first".to_string(),
+                    }),
+                }
+            }
+            Err(DatabaseError::ScheduleConflict(conflicts)) => {
+                result.errors.push(FieldError {
+                    field: "starts_at".to_string(),
+                    message: "overlaps other events of its group".to_string(),
+                });
+                result.conflicts = conflicts;
+            }
+            Err(error) => result.errors = item_errors(error),
+        }
+    }
+
+    let mut summary = BulkSummary::default();
+    for result in &results {
+        match result.status {
+            BulkStatus::Created => summary.created += 1,
+            BulkStatus::Updated => summary.updated += 1,
+            BulkStatus::Unchanged => summary.unchanged += 1,
+            BulkStatus::Error => summary.failed += 1,
+        }
+    }
+
+    Ok(Json(BulkResponse { summary, results }))
+}
+
+/// Describes why a single event was rolled back the same way the single-event routes would. The rest of the batch
+/// is committed regardless, so this must not fail the whole request.
+fn item_errors(error: DatabaseError) -> Vec<FieldError> {
+    // the only unique and foreign keys on events are vrc_event_id and vrc_group_id
+    let (field, message) = match ApiError::from(error) {
+        ApiError::ValidationFailed(errors) => return errors.errors().to_vec(),
+        ApiError::Conflict(detail) => ("vrc_event_id", detail),
+        ApiError::InvalidReference(detail) => ("vrc_group_id", detail),
+        ApiError::DatabaseError(error) => {
+            tracing::error!(?error, "failed to store an event of a bulk request");
+            ("body", "could not be stored, the cause was logged".to_string())
+        }
+        _ => ("body", "could not be stored".to_string()),
+    };
+
+    vec![FieldError {
+        field: field.to_string(),
+        message,
+    }]
+}
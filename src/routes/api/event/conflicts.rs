@@ -0,0 +1,16 @@
+// the code `ToSchema` derives for the generic `WithConflicts` trips this lint
+#![allow(clippy::option_if_let_else)]
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::database::ConflictingEvent;
+
+/// A response listing the events of the same group that the submitted event overlaps, if there are any.
+#[derive(Serialize, ToSchema)]
+pub struct WithConflicts<T> {
+    #[serde(flatten)]
+    pub inner: T,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ConflictingEvent>,
+}
@@ -1,19 +1,68 @@
-use axum::{Json, extract::State, response::IntoResponse};
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{StatusCode, header::LOCATION},
+    response::IntoResponse,
+};
 
 use crate::{
     app::AppState,
-    database::{CreateEvent, EventModel},
-    extractors::AuthenticatedApiUser,
-    routes::ApiError,
+    database::{Actor, AuditContext, CreateEvent, CreatedEvent, EventModel},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::{ApiError, Problem, flag_param},
+    validation::Validate,
 };
 
+use super::conflicts::WithConflicts;
+
+/// Creates an event. Overlapping other events of its group is allowed, they are listed in `conflicts`.
+#[utoipa::path(
+    post,
+    path = "/events",
+    tag = "events",
+    params(
+        ("reject_conflicts" = Option<bool>, Query, description = "Reject the event with `409 Conflict` if it overlaps other events of its group"),
+    ),
+    request_body = CreateEvent,
+    responses(
+        (status = CREATED, body = WithConflicts<CreatedEvent>, headers(("Location" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = CONFLICT, description = "The `vrc_event_id` is taken, or the event overlaps others and `reject_conflicts` was set", body = Problem),
+        (status = UNPROCESSABLE_ENTITY, description = "The event failed validation or its group does not exist", body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn insert_event(
     AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
     Json(create_event): Json<CreateEvent>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let created_event = app_state.db.insert_event(create_event).await.map_err(ApiError::from)?;
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    create_event.validate()?;
+    let reject_conflicts = flag_param(&query, "reject_conflicts")?;
+
+    let (created_event, conflicts) = app_state
+        .db
+        .insert_event(create_event, reject_conflicts, &audit)
+        .await
+        .map_err(ApiError::from)?;
+    let location = format!("/api/v1/events/{}", created_event.vrc_event_id);
 
-    Ok(Json(created_event))
+    Ok((
+        StatusCode::CREATED,
+        [(LOCATION, location)],
+        Json(WithConflicts {
+            inner: created_event,
+            conflicts,
+        }),
+    ))
 }
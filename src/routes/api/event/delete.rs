@@ -2,20 +2,61 @@ use std::collections::HashMap;
 
 use axum::{
     extract::{Path, State},
+    http::StatusCode,
     response::IntoResponse,
 };
 
-use crate::{app::AppState, database::EventModel, extractors::AuthenticatedApiUser, routes::ApiError};
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, EventModel},
+    extractors::{AuthenticatedApiUser, Preconditions, RequestId},
+    routes::{ApiError, Problem},
+};
 
+/// Deletes an event, which can be restored later. With `If-Match`, only if it is still at that version.
+#[utoipa::path(
+    delete,
+    path = "/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = NO_CONTENT, description = "The event was deleted"),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn delete_event(
-    AuthenticatedApiUser(_user_agent): AuthenticatedApiUser,
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
+    preconditions: Preconditions,
 ) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
 
-    app_state.db.delete_event(id).await?;
+    let expected_version = if preconditions.has_if_match() {
+        let current = app_state.db.get_event(id).await?.ok_or(ApiError::NotFound)?;
+        preconditions.check_if_match_version(current.version)?;
+        Some(current.version)
+    } else {
+        None
+    };
+
+    if !app_state.db.delete_event(id, expected_version, &audit).await? {
+        return Err(if expected_version.is_some() {
+            ApiError::PreconditionFailed
+        } else {
+            ApiError::NotFound
+        });
+    }
 
-    Ok(())
+    Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{AuditEntry, AuditModel, EventModel},
+    extractors::AuthenticatedApiUser,
+    routes::{ApiError, Problem},
+};
+
+/// Every change to an event, oldest first.
+#[utoipa::path(
+    get,
+    path = "/events/{id}/history",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = OK, body = Vec<AuditEntry>),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn event_history(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    // history outlives the event, so deleted events still have one
+    let history = app_state.db.get_history("event", id).await?;
+    if history.is_empty() && app_state.db.get_event(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(Json(history))
+}
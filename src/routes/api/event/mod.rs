@@ -1,12 +1,19 @@
 use axum::{
     Router,
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
 };
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::app::AppState;
 
+mod archive;
+mod bulk;
+mod conflicts;
 mod create;
 mod delete;
+mod history;
+mod restore;
+mod rsvp;
 mod update;
 mod view;
 
@@ -14,12 +21,53 @@ pub struct EventRoutes;
 
 impl EventRoutes {
     pub fn router() -> Router<AppState> {
+        Self::routes().into()
+    }
+
+    /// The routes of [`Self::router`] along with their description, so that every route is registered once.
+    fn routes() -> OpenApiRouter<AppState> {
+        OpenApiRouter::new()
+            // /events to view upcoming, /events?group_id=... to query by group_id, ?include_past=true to include ended
+            .routes(routes!(view::get_all_events, create::insert_event))
+            .routes(routes!(view::get_current_events))
+            .routes(routes!(view::get_upcoming_events))
+            .routes(routes!(bulk::bulk_upsert_events))
+            .routes(routes!(archive::get_archived_events))
+            .routes(routes!(archive::view_archived_event))
+            .routes(routes!(
+                view::view_event,
+                update::update_event,
+                update::patch_event,
+                delete::delete_event
+            ))
+            .routes(routes!(history::event_history))
+            .routes(routes!(restore::restore_event))
+            .routes(routes!(rsvp::rsvp, rsvp::cancel_rsvp))
+    }
+
+    /// The unversioned paths [`Self::router`] had before `/api/v1`, kept until they are sunset.
+    pub fn legacy_router() -> Router<AppState> {
         Router::<AppState>::new()
-            // /events to view all, /events?group_id=... to query by group_id
             .route("/events", get(view::get_all_events))
+            .route("/events/now", get(view::get_current_events))
+            .route("/events/upcoming", get(view::get_upcoming_events))
+            .route("/events/bulk", post(bulk::bulk_upsert_events))
+            .route("/events/archive", get(archive::get_archived_events))
+            .route("/events/archive/{id}", get(archive::view_archived_event))
             .route("/event/{id}", get(view::view_event))
             .route("/event", post(create::insert_event))
             .route("/event/{id}", put(update::update_event))
+            .route("/event/{id}", patch(update::patch_event))
             .route("/event/{id}", delete(delete::delete_event))
+            .route("/event/{id}/history", get(history::event_history))
+            .route("/event/{id}/restore", post(restore::restore_event))
+            .route("/event/{id}/rsvp", post(rsvp::rsvp))
+            .route("/event/{id}/rsvp", delete(rsvp::cancel_rsvp))
+    }
+
+    /// Describes the routes of [`Self::router`], relative to where it is nested.
+    #[must_use]
+    pub fn openapi() -> utoipa::openapi::OpenApi {
+        Self::routes().into_openapi()
     }
 }
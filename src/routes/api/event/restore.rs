@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::header::ETAG,
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, Event, EventModel},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::{ApiError, Problem, event_etag},
+};
+
+/// Undoes the deletion of an event.
+#[utoipa::path(
+    post,
+    path = "/events/{id}/restore",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = OK, body = Event, headers(("ETag" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, description = "No deleted event with this ID", body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn restore_event(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let event = app_state
+        .db
+        .restore_event(id, &audit)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(([(ETAG, event_etag(&event))], Json(event)))
+}
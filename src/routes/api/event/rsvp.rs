@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{AttendeeCounts, AttendeeModel, CreateRsvp},
+    extractors::SessionUser,
+    routes::{ApiError, Problem},
+};
+
+/// Marks the signed-in user as going to or interested in an event.
+#[utoipa::path(
+    post,
+    path = "/events/{id}/rsvp",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    request_body = CreateRsvp,
+    responses(
+        (status = OK, body = AttendeeCounts),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("session" = [])),
+)]
+#[tracing::instrument(skip(app_state, user))]
+pub async fn rsvp(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Json(create_rsvp): Json<CreateRsvp>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let counts = app_state
+        .db
+        .set_rsvp(id, &user.id, create_rsvp.status)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(counts))
+}
+
+/// Withdraws the signed-in user's RSVP to an event.
+#[utoipa::path(
+    delete,
+    path = "/events/{id}/rsvp",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = OK, body = AttendeeCounts),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("session" = [])),
+)]
+#[tracing::instrument(skip(app_state, user))]
+pub async fn cancel_rsvp(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let counts = app_state
+        .db
+        .remove_rsvp(id, &user.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(counts))
+}
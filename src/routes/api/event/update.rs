@@ -2,27 +2,173 @@ use std::collections::HashMap;
 
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header::ETAG,
     response::IntoResponse,
 };
 
 use crate::{
     app::AppState,
-    database::{CreateEvent, EventModel},
-    extractors::AuthenticatedApiUser,
-    routes::ApiError,
+    database::{Actor, AuditContext, CreateEvent, DatabaseError, Event, EventModel},
+    extractors::{AuthenticatedApiUser, MergePatch, Preconditions, RequestId},
+    routes::{ApiError, Problem, event_etag, flag_param},
+    validation::{Validate, ValidationErrors},
 };
 
+use super::conflicts::WithConflicts;
+
+const IMMUTABLE_FIELDS: &[&str] = &[
+    "vrc_event_id",
+    "vrc_group_id",
+    "created_at",
+    "updated_at",
+    "version",
+    "deleted_at",
+    "going_count",
+    "interested_count",
+];
+const REQUIRED_FIELDS: &[&str] = &[
+    "name",
+    "description",
+    "starts_at",
+    "ends_at",
+    "category",
+    "access_type",
+    "platforms",
+];
+
+/// Replaces an event. With `If-Match`, only if it is still at that version.
+#[utoipa::path(
+    put,
+    path = "/events/{id}",
+    tag = "events",
+    params(
+        ("id" = String, Path, description = "The event's `vrc_event_id`"),
+        ("reject_conflicts" = Option<bool>, Query, description = "Reject the event with `409 Conflict` if it overlaps other events of its group"),
+    ),
+    request_body = CreateEvent,
+    responses(
+        (status = OK, body = WithConflicts<Event>, headers(("ETag" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = CONFLICT, description = "The event overlaps others and `reject_conflicts` was set", body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn update_event(
-    AuthenticatedApiUser(_user_agent): AuthenticatedApiUser,
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
+    Query(query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
     Json(create_event): Json<CreateEvent>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    create_event.validate()?;
+    let reject_conflicts = flag_param(&query, "reject_conflicts")?;
+
+    let expected_version = if preconditions.has_if_match() {
+        let current = app_state.db.get_event(id).await?.ok_or(ApiError::NotFound)?;
+        preconditions.check_if_match_version(current.version)?;
+        Some(current.version)
+    } else {
+        None
+    };
+
+    let (event, conflicts) = app_state
+        .db
+        .update_event(id, create_event, expected_version, reject_conflicts, &audit)
+        .await?
+        .ok_or(if expected_version.is_some() {
+            ApiError::PreconditionFailed
+        } else {
+            ApiError::NotFound
+        })?;
+
+    Ok((
+        [(ETAG, event_etag(&event))],
+        Json(WithConflicts {
+            inner: event,
+            conflicts,
+        }),
+    ))
+}
+
+/// Changes some fields of an event with a JSON merge patch. With `If-Match`, only if it is still at that version.
+#[utoipa::path(
+    patch,
+    path = "/events/{id}",
+    tag = "events",
+    params(
+        ("id" = String, Path, description = "The event's `vrc_event_id`"),
+        ("reject_conflicts" = Option<bool>, Query, description = "Reject the event with `409 Conflict` if it overlaps other events of its group"),
+    ),
+    request_body(content = Object, content_type = "application/merge-patch+json"),
+    responses(
+        (status = OK, body = WithConflicts<Event>, headers(("ETag" = String))),
+        (status = BAD_REQUEST, body = Problem),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = CONFLICT, description = "The event overlaps others and `reject_conflicts` was set", body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+        (status = UNSUPPORTED_MEDIA_TYPE, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn patch_event(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Query(query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
+    merge_patch: MergePatch,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
 
-    app_state.db.update_event(id, create_event).await?;
+    let event = app_state.db.get_event(id).await?.ok_or(ApiError::NotFound)?;
+    preconditions.check_if_match_version(event.version)?;
+    let version = event.version;
+    let mut document = serde_json::to_value(&event).map_err(DatabaseError::SerdeError)?;
+
+    let mut errors = ValidationErrors::default();
+    merge_patch.check_fields(&document, IMMUTABLE_FIELDS, REQUIRED_FIELDS, &mut errors);
+    errors.into_result()?;
+
+    merge_patch.apply(&mut document);
+    let create_event: CreateEvent = serde_json::from_value(document).map_err(ValidationErrors::from)?;
+    create_event.validate()?;
+    let reject_conflicts = flag_param(&query, "reject_conflicts")?;
+
+    // the patch was applied to `version`, so it must not overwrite a concurrent change
+    let (event, conflicts) = app_state
+        .db
+        .update_event(id, create_event, Some(version), reject_conflicts, &audit)
+        .await?
+        .ok_or(ApiError::PreconditionFailed)?;
 
-    Ok(())
+    Ok((
+        [(ETAG, event_etag(&event))],
+        Json(WithConflicts {
+            inner: event,
+            conflicts,
+        }),
+    ))
 }
@@ -1,39 +1,163 @@
 use std::collections::HashMap;
 
 use axum::{
-    Json,
     extract::{Path, Query, State},
     response::IntoResponse,
 };
+use time::{Duration, OffsetDateTime};
 
 use crate::{
     app::AppState,
-    database::{Event, EventModel},
-    routes::ApiError,
+    database::{Event, EventFilters, EventModel},
+    extractors::Preconditions,
+    routes::{
+        ApiError, Problem, collection_etag, conditional_json, duration_param, event_etag, flag_param, page_params,
+    },
 };
 
+const DEFAULT_UPCOMING_WINDOW: Duration = Duration::hours(2);
+const MAX_UPCOMING_WINDOW: Duration = Duration::days(7);
+
+/// Events that haven't ended yet, soonest first, optionally filtered.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(
+        ("include_past" = Option<bool>, Query, description = "Also list events that ended but are not archived yet"),
+        ("group_id" = Option<String>, Query, description = "Only events of this group"),
+        ("from" = Option<String>, Query, description = "Only events overlapping the range from this date or RFC 3339 timestamp"),
+        ("to" = Option<String>, Query, description = "Only events overlapping the range up to this date or RFC 3339 timestamp, a date includes the whole day"),
+        ("starts_at" = Option<String>, Query, description = "Only events starting at or after this date or RFC 3339 timestamp"),
+        ("ends_at" = Option<String>, Query, description = "Only events ending at or before this date or RFC 3339 timestamp"),
+        ("tz" = Option<String>, Query, description = "The IANA time zone dates are in, UTC by default"),
+        ("category" = Option<String>, Query, description = "Comma-separated categories, matching events with any of them"),
+        ("access_type" = Option<String>, Query, description = "Comma-separated access types, matching events with any of them"),
+        ("platforms" = Option<String>, Query, description = "Comma-separated platforms, matching events supporting any of them"),
+        ("tags" = Option<String>, Query, description = "Comma-separated tags or tag aliases, matching events with any of them"),
+    ),
+    responses(
+        (status = OK, body = Vec<Event>, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The events did not change since the `If-None-Match` ETag"),
+        (status = BAD_REQUEST, description = "`include_past` is not `true` or `false`", body = Problem),
+        (status = UNPROCESSABLE_ENTITY, description = "A filter is not a valid date, timestamp or time zone", body = Problem),
+    ),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn get_all_events(
     State(app_state): State<AppState>,
-    Query(query): Query<HashMap<String, String>>,
+    Query(mut query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
 ) -> Result<impl IntoResponse, ApiError> {
+    let include_past = flag_param(&query, "include_past")?;
+    query.remove("include_past");
+    let filters = EventFilters::parse(&query)?;
+
     let events = if query.is_empty() {
         app_state.db.get_all_events().await
     } else {
-        app_state.db.query_events(query).await
+        app_state.db.query_events(&filters).await
     }?;
 
-    Ok(Json(events))
+    // ended events stay listed with ?include_past=true until the archive job moves them to /events/archive
+    let now = OffsetDateTime::now_utc();
+    let events: Vec<&Event> = events
+        .iter()
+        .filter(|event| include_past || event.ends_at >= now)
+        .collect();
+
+    let etag = collection_etag(
+        events
+            .iter()
+            .map(|event| (event.vrc_event_id.as_str(), event_etag(event))),
+    );
+    Ok(conditional_json(&preconditions, etag, events))
 }
 
+/// A single event.
+#[utoipa::path(
+    get,
+    path = "/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "The event's `vrc_event_id`")),
+    responses(
+        (status = OK, body = Event, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The event did not change since the `If-None-Match` ETag"),
+        (status = NOT_FOUND, body = Problem),
+    ),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn view_event(
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
+    preconditions: Preconditions,
 ) -> Result<impl IntoResponse, ApiError> {
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
 
     let event: Event = app_state.db.get_event(id).await?.ok_or(ApiError::NotFound)?;
 
-    Ok(Json(event))
+    Ok(conditional_json(&preconditions, event_etag(&event), event))
+}
+
+/// Events in progress right now. Cheap enough to poll, and answers `304 Not Modified` while nothing changed.
+#[utoipa::path(
+    get,
+    path = "/events/now",
+    tag = "events",
+    responses(
+        (status = OK, body = Vec<Event>, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The events did not change since the `If-None-Match` ETag"),
+    ),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn get_current_events(
+    State(app_state): State<AppState>,
+    preconditions: Preconditions,
+) -> Result<impl IntoResponse, ApiError> {
+    let events = app_state.db.get_current_events().await?;
+
+    let etag = collection_etag(
+        events
+            .iter()
+            .map(|event| (event.vrc_event_id.as_str(), event_etag(event))),
+    );
+    Ok(conditional_json(&preconditions, etag, events))
+}
+
+/// Events starting within `within` from now, e.g. `?within=30m`, `2h` by default and at most a week. At most
+/// `limit` events are returned.
+#[utoipa::path(
+    get,
+    path = "/events/upcoming",
+    tag = "events",
+    params(
+        ("within" = Option<String>, Query, description = "How far ahead to look, a number followed by `s`, `m`, `h` or `d`"),
+        ("limit" = Option<i64>, Query, description = "The most events to return, 50 by default and at most 200"),
+    ),
+    responses(
+        (status = OK, body = Vec<Event>, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The events did not change since the `If-None-Match` ETag"),
+        (status = BAD_REQUEST, body = Problem),
+    ),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn get_upcoming_events(
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
+) -> Result<impl IntoResponse, ApiError> {
+    let within = duration_param(&query, "within", DEFAULT_UPCOMING_WINDOW)?;
+    if within.is_negative() || within > MAX_UPCOMING_WINDOW {
+        return Err(ApiError::BadRequest);
+    }
+    let (limit, _) = page_params(&query)?;
+
+    let events = app_state.db.get_upcoming_events(within, limit).await?;
+
+    let etag = collection_etag(
+        events
+            .iter()
+            .map(|event| (event.vrc_event_id.as_str(), event_etag(event))),
+    );
+    Ok(conditional_json(&preconditions, etag, events))
 }
@@ -0,0 +1,24 @@
+mod view;
+
+use axum::{Router, routing::get};
+
+use crate::app::AppState;
+
+pub struct FeedRoutes;
+
+impl FeedRoutes {
+    pub fn router() -> Router<AppState> {
+        // authenticated by the secret token in the path, since calendar apps can't send cookies or API keys
+        Router::<AppState>::new()
+            .route("/feeds/{token}/events.ics", get(view::ics_feed))
+            .route("/feeds/{token}/events.json", get(view::json_feed))
+    }
+
+    /// The unversioned paths [`Self::router`] had before `/api/v1`, kept until they are sunset. Calendar apps keep
+    /// polling the address they were subscribed with, so these are the last to go.
+    pub fn legacy_router() -> Router<AppState> {
+        Router::<AppState>::new()
+            .route("/feed/{token}/events.ics", get(view::ics_feed))
+            .route("/feed/{token}/events.json", get(view::json_feed))
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::header::CONTENT_TYPE,
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{Event, EventModel, User, UserModel},
+    ical,
+    routes::ApiError,
+};
+
+async fn feed_events(app_state: &AppState, path: &HashMap<String, String>) -> Result<(User, Vec<Event>), ApiError> {
+    let token = path.get("token").ok_or(ApiError::BadRequest)?;
+
+    let user = app_state
+        .db
+        .get_user_by_feed_token(token)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    let events = app_state.db.get_feed_events(&user.id).await?;
+
+    Ok((user, events))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn ics_feed(
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (user, events) = feed_events(&app_state, &path).await?;
+
+    let name = format!("{}'s events", user.global_name.as_ref().unwrap_or(&user.username));
+    let calendar = ical::write_calendar(&name, &events);
+
+    Ok(([(CONTENT_TYPE, "text/calendar; charset=utf-8")], calendar))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn json_feed(
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (_, events) = feed_events(&app_state, &path).await?;
+
+    Ok(Json(events))
+}
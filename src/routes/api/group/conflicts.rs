@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppState,
+    calendar,
+    database::{ConflictModel, EventConflict, GroupModel},
+    routes::{ApiError, Problem},
+    validation::ValidationErrors,
+};
+
+/// Lists the group's double-booked events: every pair of events overlapping each other within `from`..`to`. The
+/// bounds are RFC 3339 instants or dates in `tz` like the event filters, `from` defaults to now and `to` to no limit.
+#[utoipa::path(
+    get,
+    path = "/groups/{id}/conflicts",
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "The group's `vrc_group_id`"),
+        ("from" = Option<String>, Query, description = "Only overlaps from this date or RFC 3339 timestamp"),
+        ("to" = Option<String>, Query, description = "Only overlaps up to this date or RFC 3339 timestamp"),
+        ("tz" = Option<String>, Query, description = "The IANA time zone dates are in, UTC by default"),
+    ),
+    responses(
+        (status = OK, body = Vec<EventConflict>),
+        (status = NOT_FOUND, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn group_conflicts(
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let mut errors = ValidationErrors::default();
+    let Some(tz) = calendar::resolve_timezone(query.get("tz").map(String::as_str)) else {
+        errors.add("tz", "must be an IANA time zone name such as 'Europe/Berlin'");
+        return Err(errors.into());
+    };
+    let mut bound = |key: &str, end_of_day: bool| {
+        let value = query.get(key)?;
+        let instant = calendar::parse_instant(value, tz, end_of_day);
+        if instant.is_none() {
+            errors.add(key, "must be a date such as 2026-10-19 or an RFC 3339 timestamp");
+        }
+        instant
+    };
+    let from = bound("from", false).unwrap_or_else(OffsetDateTime::now_utc);
+    let to = bound("to", true);
+    errors.into_result()?;
+
+    if app_state.db.get_group(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let conflicts = app_state.db.get_group_conflicts(id, Some(from), to).await?;
+
+    Ok(Json(conflicts))
+}
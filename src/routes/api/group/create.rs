@@ -1,19 +1,47 @@
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{
+    Json,
+    extract::State,
+    http::{StatusCode, header::LOCATION},
+    response::IntoResponse,
+};
 
 use crate::{
     app::AppState,
-    database::{CreateGroup, GroupModel},
-    extractors::AuthenticatedApiUser,
-    routes::ApiError,
+    database::{Actor, AuditContext, CreateGroup, CreatedGroup, GroupModel},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::{ApiError, Problem},
+    validation::Validate,
 };
 
+/// Creates a group.
+#[utoipa::path(
+    post,
+    path = "/groups",
+    tag = "groups",
+    request_body = CreateGroup,
+    responses(
+        (status = CREATED, body = CreatedGroup, headers(("Location" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = CONFLICT, description = "The `vrc_group_id`, name or short code is taken", body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn insert_group(
-    AuthenticatedApiUser(_user_agent): AuthenticatedApiUser,
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
     Json(create_group): Json<CreateGroup>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let created_group = app_state.db.insert_group(create_group).await?;
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    create_group.validate()?;
+    let created_group = app_state.db.insert_group(create_group, &audit).await?;
+    let location = format!("/api/v1/groups/{}", created_group.group_id);
 
-    Ok(Json(created_group))
+    Ok((StatusCode::CREATED, [(LOCATION, location)], Json(created_group)))
 }
@@ -1,21 +1,108 @@
 use std::collections::HashMap;
 
 use axum::{
-    extract::{Path, State},
-    response::IntoResponse,
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
+use serde::Serialize;
+use utoipa::ToSchema;
 
-use crate::{app::AppState, database::GroupModel, extractors::AuthenticatedApiUser, routes::ApiError};
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, EventFilters, EventModel, GroupDeletion, GroupModel},
+    extractors::{AuthenticatedApiUser, Preconditions, RequestId},
+    routes::{ApiError, Problem, flag_param, version_etag},
+};
+
+/// What deleting a group would remove, returned for `?dry_run=true`.
+#[derive(Serialize, ToSchema)]
+struct DeletePreview {
+    vrc_group_id: String,
+    event_count: usize,
+    event_ids: Vec<String>,
+}
 
+/// Deletes a group. A group that still has events is only deleted with `?cascade=true`, and `?dry_run=true` lists
+/// the events that would be deleted along with it without deleting anything.
+#[utoipa::path(
+    delete,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "The group's `vrc_group_id`"),
+        ("cascade" = Option<bool>, Query, description = "Also delete the group's events"),
+        ("dry_run" = Option<bool>, Query, description = "Only list the events that would be deleted"),
+    ),
+    responses(
+        (status = OK, description = "What would be deleted, for `dry_run`", body = DeletePreview),
+        (status = NO_CONTENT, description = "The group was deleted"),
+        (status = BAD_REQUEST, body = Problem),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = CONFLICT, description = "The group still has events and `cascade` was not set, which are listed in `event_ids`", body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn delete_group(
-    AuthenticatedApiUser(_user_agent): AuthenticatedApiUser,
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
-) -> Result<impl IntoResponse, ApiError> {
+    Query(query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
+) -> Result<Response, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
+    let dry_run = flag_param(&query, "dry_run")?;
+    let cascade = flag_param(&query, "cascade")?;
+
+    if dry_run {
+        let group = app_state.db.get_group(id).await?.ok_or(ApiError::NotFound)?;
+        preconditions.check_if_match(&version_etag(group.version))?;
+
+        let events = app_state
+            .db
+            .query_events(&EventFilters {
+                group_id: Some(id.clone()),
+                ..EventFilters::default()
+            })
+            .await?;
+        let event_ids: Vec<String> = events.iter().map(|event| event.vrc_event_id.clone()).collect();
+
+        return Ok(Json(DeletePreview {
+            vrc_group_id: group.vrc_group_id,
+            event_count: event_ids.len(),
+            event_ids,
+        })
+        .into_response());
+    }
 
-    app_state.db.delete_group(id).await?;
+    let expected_version = if preconditions.has_if_match() {
+        let current = app_state.db.get_group(id).await?.ok_or(ApiError::NotFound)?;
+        preconditions.check_if_match(&version_etag(current.version))?;
+        Some(current.version)
+    } else {
+        None
+    };
 
-    Ok(())
+    match app_state.db.delete_group(id, expected_version, cascade, &audit).await? {
+        GroupDeletion::Deleted => Ok(StatusCode::NO_CONTENT.into_response()),
+        GroupDeletion::NotFound if expected_version.is_some() => Err(ApiError::PreconditionFailed),
+        GroupDeletion::NotFound => Err(ApiError::NotFound),
+        GroupDeletion::HasEvents(event_ids) => Err(ApiError::InUse(
+            format!(
+                "{} event(s) still belong to the group, pass ?cascade=true to delete them too",
+                event_ids.len()
+            ),
+            event_ids,
+        )),
+    }
 }
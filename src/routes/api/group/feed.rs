@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppState,
+    database::{FeedModel, GroupModel, IcalFeed, JobModel, NewJob, PutIcalFeed, SYNC_ICAL_FEED_JOB},
+    extractors::AuthenticatedApiUser,
+    ical::{self, FeedHostError},
+    routes::{ApiError, Problem},
+    validation::{Validate, ValidationErrors},
+};
+
+/// Every registered iCalendar feed and how its last import went.
+#[utoipa::path(
+    get,
+    path = "/groups/feeds",
+    tag = "groups",
+    responses(
+        (status = OK, body = Vec<IcalFeed>),
+        (status = UNAUTHORIZED, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn get_all_feeds(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let feeds = app_state.db.get_feeds().await?;
+
+    Ok(Json(feeds))
+}
+
+/// The group's iCalendar feed and how its last import went.
+#[utoipa::path(
+    get,
+    path = "/groups/{id}/feed",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, body = IcalFeed),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn view_feed(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let feed = app_state.db.get_feed(id).await?.ok_or(ApiError::NotFound)?;
+
+    Ok(Json(feed))
+}
+
+/// Registers or replaces the group's iCalendar feed and imports it right away.
+#[utoipa::path(
+    put,
+    path = "/groups/{id}/feed",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    request_body = PutIcalFeed,
+    responses(
+        (status = OK, body = IcalFeed),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn put_feed(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Json(put_feed): Json<PutIcalFeed>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    put_feed.validate()?;
+    check_public_host(&put_feed.url).await?;
+    if app_state.db.get_group(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let feed = app_state.db.put_feed(id, put_feed).await?;
+    schedule_sync(&app_state, id).await?;
+
+    Ok(Json(feed))
+}
+
+/// Stops importing the group's feed. Events imported so far are kept.
+#[utoipa::path(
+    delete,
+    path = "/groups/{id}/feed",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = NO_CONTENT, description = "The feed was removed"),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn delete_feed(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    if app_state.db.delete_feed(id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound)
+    }
+}
+
+/// Imports the group's feed now instead of waiting for the next periodic import. The result shows up in the
+/// feed's `last_synced_at` or `last_error`.
+#[utoipa::path(
+    post,
+    path = "/groups/{id}/feed/sync",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = ACCEPTED, description = "The import was scheduled"),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn sync_feed(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    if app_state.db.get_feed(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+    schedule_sync(&app_state, id).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn schedule_sync(app_state: &AppState, vrc_group_id: &str) -> Result<(), ApiError> {
+    let job = NewJob {
+        kind: SYNC_ICAL_FEED_JOB,
+        dedupe_key: Some(format!("{SYNC_ICAL_FEED_JOB}:{vrc_group_id}")),
+        payload: serde_json::json!({ "vrc_group_id": vrc_group_id }).to_string(),
+        run_at: OffsetDateTime::now_utc(),
+        max_attempts: 3,
+    };
+    app_state.db.schedule_job(job).await?;
+
+    Ok(())
+}
+
+/// Refuses feeds on hosts that resolve to private, loopback or link-local addresses. They are checked again on
+/// every import, since the host may resolve differently by then.
+async fn check_public_host(url: &str) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    match ical::feed_url(url) {
+        Some(url) => match ical::resolve_public_host(&url).await {
+            Ok(_) => {}
+            Err(FeedHostError::Unresolved) => errors.add("url", "must be on a host that can be resolved"),
+            Err(FeedHostError::Internal) => {
+                errors.add("url", "must not be on a private, loopback or link-local address");
+            }
+        },
+        None => errors.add("url", "must be an http, https or webcal URL"),
+    }
+    errors.into_result()
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::FollowModel,
+    extractors::SessionUser,
+    routes::{ApiError, Problem},
+};
+
+/// Follows a group on behalf of the signed-in user.
+#[utoipa::path(
+    post,
+    path = "/groups/{id}/follow",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, description = "The group is followed"),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("session" = [])),
+)]
+#[tracing::instrument(skip(app_state, user))]
+pub async fn follow_group(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    if !app_state.db.follow_group(&user.id, id).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Stops following a group on behalf of the signed-in user.
+#[utoipa::path(
+    delete,
+    path = "/groups/{id}/follow",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, description = "The group is no longer followed"),
+        (status = UNAUTHORIZED, body = Problem),
+    ),
+    security(("session" = [])),
+)]
+#[tracing::instrument(skip(app_state, user))]
+pub async fn unfollow_group(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    app_state.db.unfollow_group(&user.id, id).await?;
+
+    Ok(())
+}
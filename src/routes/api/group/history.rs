@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{AuditEntry, AuditModel, GroupModel},
+    extractors::AuthenticatedApiUser,
+    routes::{ApiError, Problem},
+};
+
+/// Every change to a group, oldest first.
+#[utoipa::path(
+    get,
+    path = "/groups/{id}/history",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, body = Vec<AuditEntry>),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn group_history(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    // history outlives the group, so deleted groups still have one
+    let history = app_state.db.get_history("group", id).await?;
+    if history.is_empty() && app_state.db.get_group(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(Json(history))
+}
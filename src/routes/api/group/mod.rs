@@ -1,12 +1,19 @@
+mod conflicts;
 mod create;
 mod delete;
+mod feed;
+mod follow;
+mod history;
+mod restore;
+mod sync;
 mod update;
 mod view;
 
 use axum::{
     Router,
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
 };
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::app::AppState;
 
@@ -14,12 +21,56 @@ pub struct GroupRoutes;
 
 impl GroupRoutes {
     pub fn router() -> Router<AppState> {
-        Router::<AppState>::new()
+        Self::routes().into()
+    }
+
+    /// The routes of [`Self::router`] along with their description, so that every route is registered once.
+    fn routes() -> OpenApiRouter<AppState> {
+        OpenApiRouter::new()
             // /groups to view all, /groups?name=... to query by name
+            .routes(routes!(view::get_all_groups, create::insert_group))
+            .routes(routes!(feed::get_all_feeds))
+            .routes(routes!(
+                view::view_group,
+                update::update_group,
+                update::patch_group,
+                delete::delete_group
+            ))
+            .routes(routes!(conflicts::group_conflicts))
+            .routes(routes!(feed::view_feed, feed::put_feed, feed::delete_feed))
+            .routes(routes!(feed::sync_feed))
+            .routes(routes!(history::group_history))
+            .routes(routes!(restore::restore_group))
+            .routes(routes!(sync::view_group_sync, sync::sync_group))
+            .routes(routes!(follow::follow_group, follow::unfollow_group))
+    }
+
+    /// The unversioned paths [`Self::router`] had before `/api/v1`, kept until they are sunset.
+    pub fn legacy_router() -> Router<AppState> {
+        Router::<AppState>::new()
             .route("/groups", get(view::get_all_groups))
             .route("/group/{id}", get(view::view_group))
+            .route("/feeds", get(feed::get_all_feeds))
             .route("/group", post(create::insert_group))
             .route("/group/{id}", put(update::update_group))
+            .route("/group/{id}", patch(update::patch_group))
             .route("/group/{id}", delete(delete::delete_group))
+            .route("/group/{id}/conflicts", get(conflicts::group_conflicts))
+            .route("/group/{id}/feed", get(feed::view_feed))
+            .route("/group/{id}/feed", put(feed::put_feed))
+            .route("/group/{id}/feed", delete(feed::delete_feed))
+            .route("/group/{id}/feed/sync", post(feed::sync_feed))
+            .route("/group/{id}/history", get(history::group_history))
+            .route("/group/{id}/restore", post(restore::restore_group))
+            .route("/group/{id}/sync", get(sync::view_group_sync))
+            .route("/group/{id}/sync", post(sync::sync_group))
+            .route("/group/{id}/follow", post(follow::follow_group))
+            .route("/group/{id}/follow", delete(follow::unfollow_group))
+    }
+
+    /// Describes the routes of [`Self::router`], relative to where it is nested.
+    #[must_use]
+    pub fn openapi() -> utoipa::openapi::OpenApi {
+        Self::routes().into_openapi()
     }
 }
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::header::ETAG,
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, Group, GroupModel},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::{ApiError, Problem, version_etag},
+};
+
+/// Undoes the deletion of a group.
+#[utoipa::path(
+    post,
+    path = "/groups/{id}/restore",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, body = Group, headers(("ETag" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, description = "No deleted group with this ID", body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn restore_group(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let group = app_state
+        .db
+        .restore_group(id, &audit)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(([(ETAG, version_etag(group.version))], Json(group)))
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppState,
+    database::{GroupModel, GroupSync, JobModel, NewJob, SYNC_VRCHAT_GROUP_JOB, SyncModel},
+    extractors::AuthenticatedApiUser,
+    routes::{ApiError, Problem},
+};
+
+/// How the last sync of the group's in-game calendar went. `404` until the group was synced once.
+#[utoipa::path(
+    get,
+    path = "/groups/{id}/sync",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, body = GroupSync),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn view_group_sync(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let sync = app_state.db.get_group_sync(id).await?.ok_or(ApiError::NotFound)?;
+
+    Ok(Json(sync))
+}
+
+/// Syncs the group's in-game calendar now instead of waiting for the next periodic sync.
+#[utoipa::path(
+    post,
+    path = "/groups/{id}/sync",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = ACCEPTED, description = "The sync was scheduled"),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn sync_group(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    if app_state.db.get_group(id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let job = NewJob {
+        kind: SYNC_VRCHAT_GROUP_JOB,
+        dedupe_key: Some(format!("{SYNC_VRCHAT_GROUP_JOB}:{id}")),
+        payload: serde_json::json!({ "vrc_group_id": id }).to_string(),
+        run_at: OffsetDateTime::now_utc(),
+        max_attempts: 3,
+    };
+    app_state.db.schedule_job(job).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
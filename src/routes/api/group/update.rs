@@ -3,26 +3,134 @@ use std::collections::HashMap;
 use axum::{
     Json,
     extract::{Path, State},
+    http::header::ETAG,
     response::IntoResponse,
 };
 
 use crate::{
     app::AppState,
-    database::{CreateGroup, GroupModel},
-    extractors::AuthenticatedApiUser,
-    routes::ApiError,
+    database::{Actor, AuditContext, CreateGroup, DatabaseError, Group, GroupModel, UpdateGroup},
+    extractors::{AuthenticatedApiUser, MergePatch, Preconditions, RequestId},
+    routes::{ApiError, Problem, version_etag},
+    validation::{Validate, ValidationErrors},
 };
 
+const IMMUTABLE_FIELDS: &[&str] = &["vrc_group_id", "created_at", "updated_at", "version", "deleted_at"];
+const REQUIRED_FIELDS: &[&str] = &["name", "visibility"];
+
+/// Updates the given fields of a group, leaving absent and `null` fields unchanged. With `If-Match`, only if it is
+/// still at that version.
+///
+/// Profile fields cannot be cleared this way, `PATCH` the group with the field set to `null` instead.
+#[utoipa::path(
+    put,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    request_body = UpdateGroup,
+    responses(
+        (status = OK, body = Group, headers(("ETag" = String))),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = CONFLICT, description = "The name or short code is taken", body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn update_group(
-    AuthenticatedApiUser(_user_agent): AuthenticatedApiUser,
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
-    Json(create_group): Json<CreateGroup>,
+    preconditions: Preconditions,
+    Json(update_group): Json<UpdateGroup>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
 
-    app_state.db.update_group(id, create_group).await?;
+    update_group.validate()?;
+
+    let expected_version = if preconditions.has_if_match() {
+        let current = app_state.db.get_group(id).await?.ok_or(ApiError::NotFound)?;
+        preconditions.check_if_match(&version_etag(current.version))?;
+        Some(current.version)
+    } else {
+        None
+    };
+
+    let group = app_state
+        .db
+        .update_group(id, update_group, expected_version, &audit)
+        .await?
+        .ok_or(if expected_version.is_some() {
+            ApiError::PreconditionFailed
+        } else {
+            ApiError::NotFound
+        })?;
+
+    Ok(([(ETAG, version_etag(group.version))], Json(group)))
+}
+
+/// Changes some fields of a group with a JSON merge patch. With `If-Match`, only if it is still at that version.
+#[utoipa::path(
+    patch,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    request_body(content = Object, content_type = "application/merge-patch+json"),
+    responses(
+        (status = OK, body = Group, headers(("ETag" = String))),
+        (status = BAD_REQUEST, body = Problem),
+        (status = UNAUTHORIZED, body = Problem),
+        (status = NOT_FOUND, body = Problem),
+        (status = CONFLICT, description = "The name or short code is taken", body = Problem),
+        (status = PRECONDITION_FAILED, body = Problem),
+        (status = UNSUPPORTED_MEDIA_TYPE, body = Problem),
+        (status = UNPROCESSABLE_ENTITY, body = Problem),
+    ),
+    security(("api_key" = [])),
+)]
+#[tracing::instrument(skip(app_state))]
+pub async fn patch_group(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    preconditions: Preconditions,
+    merge_patch: MergePatch,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let id = path.get("id").ok_or(ApiError::BadRequest)?;
+
+    let group = app_state.db.get_group(id).await?.ok_or(ApiError::NotFound)?;
+    preconditions.check_if_match(&version_etag(group.version))?;
+    let version = group.version;
+    let mut document = serde_json::to_value(&group).map_err(DatabaseError::SerdeError)?;
+
+    let mut errors = ValidationErrors::default();
+    merge_patch.check_fields(&document, IMMUTABLE_FIELDS, REQUIRED_FIELDS, &mut errors);
+    errors.into_result()?;
+
+    merge_patch.apply(&mut document);
+    let create_group: CreateGroup = serde_json::from_value(document).map_err(ValidationErrors::from)?;
+    create_group.validate()?;
+
+    // the patch was applied to `version`, so it must not overwrite a concurrent change
+    let group = app_state
+        .db
+        .replace_group(id, create_group, Some(version), &audit)
+        .await?
+        .ok_or(ApiError::PreconditionFailed)?;
 
-    Ok(())
+    Ok(([(ETAG, version_etag(group.version))], Json(group)))
 }
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use axum::{
-    Json,
     extract::{Path, Query, State},
     response::IntoResponse,
 };
@@ -9,13 +8,29 @@ use axum::{
 use crate::{
     app::AppState,
     database::{Group, GroupModel},
-    routes::ApiError,
+    extractors::Preconditions,
+    routes::{ApiError, Problem, collection_etag, conditional_json, version_etag},
 };
 
+/// Public groups by name, optionally filtered.
+#[utoipa::path(
+    get,
+    path = "/groups",
+    tag = "groups",
+    params(
+        ("name" = Option<String>, Query, description = "Only the group with this name"),
+        ("short_code" = Option<String>, Query, description = "Only the group with this short code"),
+    ),
+    responses(
+        (status = OK, body = Vec<Group>, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The groups did not change since the `If-None-Match` ETag"),
+    ),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn get_all_groups(
     State(app_state): State<AppState>,
     Query(query): Query<HashMap<String, String>>,
+    preconditions: Preconditions,
 ) -> Result<impl IntoResponse, ApiError> {
     let groups: Vec<Group> = if query.is_empty() {
         app_state.db.get_all_groups().await
@@ -23,17 +38,35 @@ pub async fn get_all_groups(
         app_state.db.query_groups(query).await
     }?;
 
-    Ok(Json(groups))
+    let etag = collection_etag(
+        groups
+            .iter()
+            .map(|group| (group.vrc_group_id.as_str(), version_etag(group.version))),
+    );
+    Ok(conditional_json(&preconditions, etag, groups))
 }
 
+/// A single group.
+#[utoipa::path(
+    get,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = String, Path, description = "The group's `vrc_group_id`")),
+    responses(
+        (status = OK, body = Group, headers(("ETag" = String))),
+        (status = NOT_MODIFIED, description = "The group did not change since the `If-None-Match` ETag"),
+        (status = NOT_FOUND, body = Problem),
+    ),
+)]
 #[tracing::instrument(skip(app_state))]
 pub async fn view_group(
     State(app_state): State<AppState>,
     Path(path): Path<HashMap<String, String>>,
+    preconditions: Preconditions,
 ) -> Result<impl IntoResponse, ApiError> {
     let id = path.get("id").ok_or(ApiError::BadRequest)?;
 
     let group: Group = app_state.db.get_group(id).await?.ok_or(ApiError::NotFound)?;
 
-    Ok(Json(group))
+    Ok(conditional_json(&preconditions, version_etag(group.version), group))
 }
@@ -0,0 +1,14 @@
+mod view;
+
+use axum::{Router, routing::get};
+
+use crate::app::AppState;
+
+pub struct LegacyRoutes;
+
+impl LegacyRoutes {
+    pub fn router() -> Router<AppState> {
+        // which clients still call the unversioned routes, and how often
+        Router::<AppState>::new().route("/legacy-routes", get(view::get_legacy_usage))
+    }
+}
@@ -0,0 +1,15 @@
+use axum::{Json, extract::State, response::IntoResponse};
+
+use crate::{app::AppState, database::LegacyRouteModel, extractors::AuthenticatedApiUser, routes::ApiError};
+
+#[tracing::instrument(skip(app_state))]
+pub async fn get_legacy_usage(
+    AuthenticatedApiUser(_): AuthenticatedApiUser,
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    // so that the calls counted since the last flush are included
+    app_state.legacy_usage.flush().await?;
+    let usage = app_state.db.get_legacy_usage().await?;
+
+    Ok(Json(usage))
+}
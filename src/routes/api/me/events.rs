@@ -0,0 +1,13 @@
+use axum::{Json, extract::State, response::IntoResponse};
+
+use crate::{app::AppState, database::EventModel, extractors::SessionUser, routes::ApiError};
+
+#[tracing::instrument(skip(app_state, user))]
+pub async fn my_events(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let events = app_state.db.get_attending_events(&user.id).await?;
+
+    Ok(Json(events))
+}
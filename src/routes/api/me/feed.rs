@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::EventModel,
+    extractors::SessionUser,
+    routes::{ApiError, page_params},
+};
+
+#[tracing::instrument(skip(app_state, user))]
+pub async fn my_feed(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (limit, offset) = page_params(&query)?;
+
+    let page = app_state.db.get_followed_events(&user.id, limit, offset).await?;
+
+    Ok(Json(page))
+}
@@ -0,0 +1,49 @@
+use axum::{Json, extract::State, response::IntoResponse};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::{app::AppState, database::UserModel, extractors::SessionUser, routes::ApiError};
+
+#[derive(Serialize)]
+struct FeedLinks {
+    token: String,
+    ics_path: String,
+    json_path: String,
+}
+
+impl FeedLinks {
+    fn new(token: String) -> Self {
+        Self {
+            ics_path: format!("/api/v1/feeds/{token}/events.ics"),
+            json_path: format!("/api/v1/feeds/{token}/events.json"),
+            token,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FeedTokenInfo {
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+}
+
+/// When the user's feed token was created, or `404 Not Found` until one was created with a `POST`. Only a hash of
+/// the token is stored, so the links are only shown by the `POST`.
+#[tracing::instrument(skip(user))]
+pub async fn get_feed_token(SessionUser(user): SessionUser) -> Result<impl IntoResponse, ApiError> {
+    let created_at = user.feed_token_created_at.ok_or(ApiError::NotFound)?;
+
+    Ok(Json(FeedTokenInfo { created_at }))
+}
+
+/// Creates the user's feed token, or replaces it and thereby revokes the old feed links. The response is the only
+/// time the token is shown.
+#[tracing::instrument(skip(app_state, user))]
+pub async fn regenerate_feed_token(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let token = app_state.db.regenerate_feed_token(&user.id).await?;
+
+    Ok(Json(FeedLinks::new(token)))
+}
@@ -0,0 +1,13 @@
+use axum::{Json, extract::State, response::IntoResponse};
+
+use crate::{app::AppState, database::FollowModel, extractors::SessionUser, routes::ApiError};
+
+#[tracing::instrument(skip(app_state, user))]
+pub async fn my_groups(
+    SessionUser(user): SessionUser,
+    State(app_state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let groups = app_state.db.get_followed_groups(&user.id).await?;
+
+    Ok(Json(groups))
+}
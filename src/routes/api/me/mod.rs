@@ -0,0 +1,24 @@
+mod events;
+mod feed;
+mod feed_token;
+mod groups;
+
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::app::AppState;
+
+pub struct MeRoutes;
+
+impl MeRoutes {
+    pub fn router() -> Router<AppState> {
+        Router::<AppState>::new()
+            .route("/me/events", get(events::my_events))
+            .route("/me/feed", get(feed::my_feed))
+            .route("/me/groups", get(groups::my_groups))
+            .route("/me/feed-token", get(feed_token::get_feed_token))
+            .route("/me/feed-token", post(feed_token::regenerate_feed_token))
+    }
+}
@@ -1,9 +1,29 @@
+mod audit;
 mod auth;
+mod calendar;
 mod errors;
+mod etag;
 mod event;
+mod feed;
 mod group;
+mod legacy;
+mod me;
+mod openapi;
+mod pagination;
+mod params;
+mod tag;
 
+pub use audit::*;
 pub use auth::*;
+pub use calendar::*;
 pub use errors::*;
+pub use etag::*;
 pub use event::*;
+pub use feed::*;
 pub use group::*;
+pub use legacy::*;
+pub use me::*;
+pub use openapi::*;
+pub use pagination::*;
+pub use params::*;
+pub use tag::*;
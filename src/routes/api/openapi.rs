@@ -0,0 +1,123 @@
+use axum::{Json, Router, http::StatusCode, response::Redirect, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        RefOr,
+        security::{ApiKey, ApiKeyValue, SecurityScheme},
+    },
+};
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use crate::{
+    app::AppState,
+    routes::{AuthRoutes, EventRoutes, GroupRoutes, Problem, ProblemCode},
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "VRChat group event calendar",
+        description = "Events of VRChat groups. Reading is open to everyone, changes need an API key sent in \
+                       `x-api-key` along with a `User-Agent` naming the client. Errors are RFC 7807 \
+                       `application/problem+json` bodies whose `code` tells them apart. The unversioned paths under \
+                       `/api` are deprecated aliases of these, answered with `Deprecation` and `Sunset` headers until \
+                       they are removed."
+    ),
+    components(schemas(Problem, ProblemCode)),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "events", description = "Scheduled events, their archive and RSVPs"),
+        (name = "groups", description = "Groups and the calendars their events are imported from"),
+        (name = "auth", description = "The signed-in website user"),
+    )
+)]
+struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "x-api-key",
+                "A key issued to a bot. Requests must also send a User-Agent header.",
+            ))),
+        );
+        components.add_security_scheme(
+            "session",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                "__Host-Http-Session",
+                "The session of a user signed in on the website with Discord.",
+            ))),
+        );
+    }
+}
+
+/// Describes the routers bots use, as nested under `/api/v1`.
+#[must_use]
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi()
+        .nest("/api/v1", EventRoutes::openapi())
+        .nest("/api/v1", GroupRoutes::openapi())
+        .nest("/api/v1", AuthRoutes::openapi());
+    // the crate has no license to name
+    openapi.info.license = None;
+
+    // responses only described by their body get the status' reason phrase instead of an empty description, and
+    // error bodies are problem details
+    for path_item in openapi.paths.paths.values_mut() {
+        let operations = [
+            &mut path_item.get,
+            &mut path_item.put,
+            &mut path_item.post,
+            &mut path_item.delete,
+            &mut path_item.patch,
+        ];
+        for operation in operations.into_iter().flatten() {
+            for (status, response) in &mut operation.responses.responses {
+                let RefOr::T(response) = response else {
+                    continue;
+                };
+                let status = status.parse::<StatusCode>().ok();
+                if response.description.is_empty() {
+                    response.description = status
+                        .and_then(|status| status.canonical_reason())
+                        .unwrap_or_default()
+                        .to_string();
+                }
+                if status.is_some_and(|status| status.is_client_error() || status.is_server_error())
+                    && let Some(content) = response.content.shift_remove("application/json")
+                {
+                    response.content.insert("application/problem+json".to_string(), content);
+                }
+            }
+        }
+    }
+
+    openapi
+}
+
+pub struct OpenApiRoutes;
+
+impl OpenApiRoutes {
+    pub fn router() -> Router<AppState> {
+        let openapi = openapi();
+        Router::<AppState>::new().route("/openapi.json", get(|| async { Json(openapi) }))
+    }
+
+    /// The unversioned paths [`Self::router`] and [`Self::docs_router`] had before `/api/v1`, kept until they are
+    /// sunset.
+    pub fn legacy_router() -> Router<AppState> {
+        Self::router().route("/docs", get(|| async { Redirect::permanent("/api/v1/docs/") }))
+    }
+
+    /// Serves a bundled Swagger UI at `/api/v1/docs`. Merged rather than nested, since the UI redirects to its own
+    /// path.
+    pub fn docs_router() -> Router<AppState> {
+        SwaggerUi::new("/api/v1/docs")
+            .config(Config::from("/api/v1/openapi.json"))
+            .into()
+    }
+}
@@ -0,0 +1,29 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use crate::routes::ApiError;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+/// Pages further in are not served, which also keeps `offset + limit` far from overflowing.
+const MAX_OFFSET: i64 = 1_000_000;
+
+/// Reads `limit` and `offset` from the query string, clamping `limit` to a sane page size.
+///
+/// # Errors
+///
+/// Returns [`ApiError::BadRequest`] if either is not a number, or `offset` is past the deepest page served.
+pub fn page_params<S: BuildHasher>(query: &HashMap<String, String, S>) -> Result<(i64, i64), ApiError> {
+    let limit = parse_param(query, "limit", DEFAULT_PAGE_SIZE)?.clamp(1, MAX_PAGE_SIZE);
+    let offset = parse_param(query, "offset", 0)?.max(0);
+    if offset > MAX_OFFSET {
+        return Err(ApiError::BadRequest);
+    }
+
+    Ok((limit, offset))
+}
+
+fn parse_param<S: BuildHasher>(query: &HashMap<String, String, S>, key: &str, default: i64) -> Result<i64, ApiError> {
+    query
+        .get(key)
+        .map_or(Ok(default), |value| value.parse().map_err(|_| ApiError::BadRequest))
+}
@@ -0,0 +1,43 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use time::Duration;
+
+use crate::routes::ApiError;
+
+/// Reads a `true`/`false` query parameter, which defaults to `false` when absent.
+///
+/// # Errors
+///
+/// Returns [`ApiError::BadRequest`] if the value is not a boolean.
+pub fn flag_param<S: BuildHasher>(query: &HashMap<String, String, S>, key: &str) -> Result<bool, ApiError> {
+    query
+        .get(key)
+        .map_or(Ok(false), |value| value.parse().map_err(|_| ApiError::BadRequest))
+}
+
+/// Reads a duration query parameter such as `90s`, `30m`, `2h` or `1d`, using `default` when absent.
+///
+/// # Errors
+///
+/// Returns [`ApiError::BadRequest`] if the value is not a whole number followed by one of those units.
+pub fn duration_param<S: BuildHasher>(
+    query: &HashMap<String, String, S>,
+    key: &str,
+    default: Duration,
+) -> Result<Duration, ApiError> {
+    let Some(value) = query.get(key) else {
+        return Ok(default);
+    };
+
+    let (amount, seconds_per_unit) = [("s", 1), ("m", 60), ("h", 60 * 60), ("d", 24 * 60 * 60)]
+        .into_iter()
+        .find_map(|(unit, seconds)| Some((value.strip_suffix(unit)?, seconds)))
+        .ok_or(ApiError::BadRequest)?;
+
+    amount
+        .parse::<i64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(seconds_per_unit))
+        .map(Duration::seconds)
+        .ok_or(ApiError::BadRequest)
+}
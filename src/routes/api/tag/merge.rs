@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, MergeTag, TagModel, normalize_tag},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::ApiError,
+    validation::{Validate, ValidationErrors},
+};
+
+/// Merges the tag into `into`: events tagged with it are retagged, and it becomes an alias so that future events
+/// using it get `into` instead.
+#[tracing::instrument(skip(app_state))]
+pub async fn merge_tag(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Json(merge_tag): Json<MergeTag>,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let slug = normalize_tag(path.get("slug").ok_or(ApiError::BadRequest)?);
+
+    merge_tag.validate()?;
+    let target = normalize_tag(&merge_tag.into);
+    if target == slug {
+        let mut errors = ValidationErrors::default();
+        errors.add("into", "must be a different tag");
+        return Err(errors.into());
+    }
+
+    let merge = app_state
+        .db
+        .merge_tags(&slug, &target, &audit)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(merge))
+}
@@ -0,0 +1,32 @@
+mod merge;
+mod update;
+mod view;
+
+use axum::{
+    Router,
+    routing::{get, post, put},
+};
+
+use crate::app::AppState;
+
+pub struct TagRoutes;
+
+impl TagRoutes {
+    pub fn router() -> Router<AppState> {
+        Router::<AppState>::new()
+            // /tags for the most used tags, /tags?q=... to autocomplete
+            .route("/tags", get(view::get_tags))
+            .route("/tags/{slug}", get(view::view_tag))
+            .route("/tags/{slug}", put(update::update_tag))
+            .route("/tags/{slug}/merge", post(merge::merge_tag))
+    }
+
+    /// The unversioned paths [`Self::router`] had before `/api/v1`, kept until they are sunset.
+    pub fn legacy_router() -> Router<AppState> {
+        Router::<AppState>::new()
+            .route("/tags", get(view::get_tags))
+            .route("/tag/{slug}", get(view::view_tag))
+            .route("/tag/{slug}", put(update::update_tag))
+            .route("/tag/{slug}/merge", post(merge::merge_tag))
+    }
+}
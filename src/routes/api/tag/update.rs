@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{Actor, AuditContext, TagModel, UpdateTag, normalize_tag},
+    extractors::{AuthenticatedApiUser, RequestId},
+    routes::ApiError,
+    validation::Validate,
+};
+
+#[tracing::instrument(skip(app_state))]
+pub async fn update_tag(
+    AuthenticatedApiUser(user_agent): AuthenticatedApiUser,
+    RequestId(request_id): RequestId,
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+    Json(update_tag): Json<UpdateTag>,
+) -> Result<impl IntoResponse, ApiError> {
+    let audit = AuditContext {
+        actor: Actor::ApiUser(user_agent),
+        request_id,
+    };
+
+    let slug = normalize_tag(path.get("slug").ok_or(ApiError::BadRequest)?);
+
+    update_tag.validate()?;
+
+    let tag = app_state
+        .db
+        .update_tag(&slug, update_tag, &audit)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(tag))
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+
+use crate::{
+    app::AppState,
+    database::{TagModel, normalize_tag},
+    routes::{ApiError, page_params},
+};
+
+#[tracing::instrument(skip(app_state))]
+pub async fn get_tags(
+    State(app_state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (limit, _) = page_params(&query)?;
+
+    let tags = app_state.db.get_tags(query.get("q").map(String::as_str), limit).await?;
+
+    Ok(Json(tags))
+}
+
+#[tracing::instrument(skip(app_state))]
+pub async fn view_tag(
+    State(app_state): State<AppState>,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let slug = normalize_tag(path.get("slug").ok_or(ApiError::BadRequest)?);
+
+    let tag = app_state.db.get_tag(&slug).await?.ok_or(ApiError::NotFound)?;
+
+    Ok(Json(tag))
+}
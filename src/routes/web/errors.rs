@@ -30,6 +30,12 @@ impl From<DatabaseError> for WebError {
             DatabaseError::RngError => Self::InternalServerError("random number generator".to_string()),
             DatabaseError::SerdeError(e) => Self::InternalServerError(e.to_string()),
             DatabaseError::SqlxError(e) => Self::InternalServerError(e.to_string()),
+            DatabaseError::UniqueViolation(constraint)
+            | DatabaseError::ForeignKeyViolation(constraint)
+            | DatabaseError::CheckViolation(constraint) => Self::InternalServerError(constraint),
+            DatabaseError::GroupDeleted => Self::InternalServerError("group deleted".to_string()),
+            DatabaseError::ScheduleConflict(_) => Self::InternalServerError("schedule conflict".to_string()),
+            DatabaseError::InvalidData(message) => Self::InternalServerError(message),
         }
     }
 }
@@ -5,5 +5,6 @@ use crate::{extractors::WebSession, routes::WebError};
 #[tracing::instrument(skip(session))]
 pub async fn logout(WebSession(mut session): WebSession) -> Result<impl IntoResponse, WebError> {
     session.remove("token").await?;
+    session.remove("user_id").await?;
     Ok(Redirect::to("/"))
 }
@@ -8,7 +8,7 @@ use axum_extra::extract::{PrivateCookieJar, cookie::Cookie};
 use oauth2::PkceCodeVerifier;
 use time::Duration;
 
-use crate::{app::AppState, extractors::WebSession, routes::WebError};
+use crate::{app::AppState, database::UserModel, extractors::WebSession, routes::WebError};
 
 #[tracing::instrument(skip(jar))]
 pub async fn redirect(
@@ -68,7 +68,11 @@ pub async fn finalize(
         .get_token(PkceCodeVerifier::new(pkce_verifier), &code)
         .await?;
 
+    let info = app_state.oauth.get_discord_info(&token).await?;
+    let user = app_state.db.upsert_user(&info).await?;
+
     session.set("token", token).await?;
+    session.set("user_id", user.id).await?;
 
     Ok((jar.remove("verifier").remove("discord_token"), Redirect::to("/")))
 }
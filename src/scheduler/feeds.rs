@@ -0,0 +1,166 @@
+use std::{collections::HashSet, sync::Arc};
+
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    database::{Actor, AuditContext, DatabaseError, EventModel, FeedModel, UpsertOutcome},
+    ical,
+    scheduler::{JobOutcome, Scheduler},
+    validation::Validate,
+};
+
+/// How many feeds are fetched and imported at the same time.
+const MAX_CONCURRENT_FEED_SYNCS: usize = 8;
+
+#[derive(Deserialize)]
+struct FeedSync {
+    vrc_group_id: String,
+}
+
+impl Scheduler {
+    pub(super) async fn sync_ical_feeds(&self) -> Result<JobOutcome, String> {
+        let feeds = self.db.get_feeds().await.map_err(|e| format!("{e:?}"))?;
+
+        // a failing feed is recorded on the feed, it shouldn't hold up the others
+        let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_FEED_SYNCS));
+        let mut syncs = JoinSet::new();
+        for feed in feeds {
+            let Ok(slot) = Arc::clone(&slots).acquire_owned().await else {
+                break;
+            };
+            let scheduler = self.clone();
+            syncs.spawn(async move {
+                if let Err(error) = scheduler.sync_feed(&feed.vrc_group_id, &feed.url).await {
+                    tracing::error!(error, vrc_group_id = feed.vrc_group_id, "failed to sync feed");
+                }
+                drop(slot);
+            });
+        }
+        while let Some(result) = syncs.join_next().await {
+            if let Err(e) = result {
+                tracing::error!(error = ?e, "feed sync panicked");
+            }
+        }
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.feed_sync_interval,
+        ))
+    }
+
+    pub(super) async fn sync_ical_feed(&self, payload: &str) -> Result<JobOutcome, String> {
+        let sync: FeedSync = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+
+        let Some(feed) = self
+            .db
+            .get_feed(&sync.vrc_group_id)
+            .await
+            .map_err(|e| format!("{e:?}"))?
+        else {
+            // removed after the sync was requested
+            return Ok(JobOutcome::Done);
+        };
+
+        self.sync_feed(&feed.vrc_group_id, &feed.url).await?;
+
+        Ok(JobOutcome::Done)
+    }
+
+    /// Imports one feed and records how it went on the feed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the database error the import failed on. Feeds that can't be fetched or read are
+    /// not an error, they are recorded as the feed's `last_error`.
+    #[tracing::instrument(skip(self))]
+    pub async fn sync_feed(&self, vrc_group_id: &str, url: &str) -> Result<(), String> {
+        let result = self.import_feed(vrc_group_id, url).await;
+
+        match result {
+            Ok(event_count) => self.db.record_feed_sync(vrc_group_id, event_count).await,
+            Err(ImportError::Feed(error)) => {
+                tracing::warn!(error, "failed to import feed");
+                self.db.record_feed_error(vrc_group_id, &error).await
+            }
+            Err(ImportError::Database(error)) => return Err(error),
+        }
+        .map_err(|e| format!("{e:?}"))
+    }
+
+    /// Upserts the feed's events and deletes the ones it cancelled or no longer lists. Returns how many upcoming
+    /// events the feed has.
+    async fn import_feed(&self, vrc_group_id: &str, url: &str) -> Result<i32, ImportError> {
+        let audit = AuditContext {
+            actor: Actor::System("ical_import".to_string()),
+            request_id: None,
+        };
+
+        let body = ical::fetch_feed(url, &self.config.feed_allowed_hosts)
+            .await
+            .map_err(ImportError::Feed)?;
+        let calendar = ical::parse_calendar(&body).map_err(ImportError::Feed)?;
+        for reason in &calendar.skipped {
+            tracing::debug!(reason, "skipped a feed event");
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let mut listed = HashSet::new();
+        let mut cancelled = Vec::new();
+        let mut events = Vec::new();
+        for event in &calendar.occurrences(now, now + self.config.feed_sync_window) {
+            let create_event = event.to_create_event(vrc_group_id);
+            listed.insert(create_event.vrc_event_id.clone());
+            if event.cancelled {
+                cancelled.push(create_event.vrc_event_id);
+            } else if let Err(errors) = create_event.validate() {
+                tracing::debug!(uid = event.uid, ?errors, "skipped an invalid feed event");
+            } else {
+                events.push(create_event);
+            }
+        }
+
+        let ids: Vec<String> = events.iter().map(|event| event.vrc_event_id.clone()).collect();
+        // the feed's own calendar decides what may overlap, so overlaps are imported as they are
+        let outcomes = self.db.upsert_events(events, false, &audit).await?;
+        for (id, outcome) in ids.iter().zip(outcomes) {
+            match outcome {
+                // deleted here, which an import should not undo
+                Ok((UpsertOutcome::Deleted, _)) => tracing::debug!(vrc_event_id = id, "skipped a deleted feed event"),
+                Ok(_) => {}
+                Err(error) => tracing::warn!(vrc_event_id = id, error = ?error, "failed to import a feed event"),
+            }
+        }
+
+        // feeds tend to drop past events, so only upcoming events that disappeared count as cancelled
+        let stale = self
+            .db
+            .get_unended_event_ids(vrc_group_id, ical::FEED_EVENT_ID_PREFIX)
+            .await?
+            .into_iter()
+            .filter(|id| !listed.contains(id));
+        for id in cancelled.into_iter().chain(stale) {
+            self.db.delete_event(&id, None, &audit).await?;
+        }
+
+        let event_count = self
+            .db
+            .get_unended_event_ids(vrc_group_id, ical::FEED_EVENT_ID_PREFIX)
+            .await?
+            .len();
+
+        Ok(i32::try_from(event_count).unwrap_or(i32::MAX))
+    }
+}
+
+enum ImportError {
+    /// The feed could not be fetched or read.
+    Feed(String),
+    Database(String),
+}
+
+impl From<DatabaseError> for ImportError {
+    fn from(error: DatabaseError) -> Self {
+        Self::Database(format!("{error:?}"))
+    }
+}
@@ -0,0 +1,109 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::{
+    database::{ArchiveModel, EventModel, GroupModel, IdempotencyModel, JobModel, SessionModel},
+    scheduler::{JobOutcome, Scheduler},
+};
+
+#[derive(Deserialize)]
+struct EventReminder {
+    vrc_event_id: String,
+}
+
+impl Scheduler {
+    pub(super) async fn send_event_reminder(&self, payload: &str) -> Result<JobOutcome, String> {
+        let reminder: EventReminder = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+
+        let Some(event) = self
+            .db
+            .get_event(&reminder.vrc_event_id)
+            .await
+            .map_err(|e| format!("{e:?}"))?
+        else {
+            // deleted after the reminder was claimed
+            return Ok(JobOutcome::Done);
+        };
+
+        let timestamp = event.starts_at.unix_timestamp();
+        let content = format!("**{}** starts <t:{timestamp}:R> (<t:{timestamp}:f>)", event.name);
+
+        if let Some(webhook_url) = &self.config.reminder_webhook_url {
+            self.http_client
+                .post(webhook_url)
+                .json(&serde_json::json!({ "content": content }))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| e.to_string())?;
+        } else {
+            tracing::info!(vrc_event_id = event.vrc_event_id, content, "event reminder");
+        }
+
+        Ok(JobOutcome::Done)
+    }
+
+    pub(super) async fn reap_sessions(&self) -> Result<JobOutcome, String> {
+        self.db.delete_expired_sessions().await.map_err(|e| format!("{e:?}"))?;
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.session_reap_interval,
+        ))
+    }
+
+    pub(super) async fn purge_idempotency_keys(&self) -> Result<JobOutcome, String> {
+        let purged = self.db.purge_idempotency_keys().await.map_err(|e| format!("{e:?}"))?;
+        tracing::debug!(purged, "purged expired idempotency keys");
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.idempotency_purge_interval,
+        ))
+    }
+
+    pub(super) async fn purge_deleted(&self) -> Result<JobOutcome, String> {
+        let deleted_before = OffsetDateTime::now_utc() - self.config.deleted_retention;
+        let events = self
+            .db
+            .purge_deleted_events(deleted_before)
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        let groups = self
+            .db
+            .purge_deleted_groups(deleted_before)
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        tracing::debug!(events, groups, "purged deleted events and groups");
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.deleted_purge_interval,
+        ))
+    }
+
+    pub(super) async fn purge_finished_jobs(&self) -> Result<JobOutcome, String> {
+        let purged = self
+            .db
+            .purge_finished_jobs(OffsetDateTime::now_utc() - self.config.finished_job_retention)
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        tracing::debug!(purged, "purged finished jobs");
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.finished_job_purge_interval,
+        ))
+    }
+
+    pub(super) async fn archive_events(&self) -> Result<JobOutcome, String> {
+        if let Some(archive_after) = self.config.archive_after {
+            let archived = self
+                .db
+                .archive_events(OffsetDateTime::now_utc() - archive_after)
+                .await
+                .map_err(|e| format!("{e:?}"))?;
+            tracing::debug!(archived, "archived ended events");
+        }
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.archive_interval,
+        ))
+    }
+}
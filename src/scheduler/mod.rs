@@ -0,0 +1,223 @@
+mod feeds;
+mod jobs;
+mod vrchat;
+
+use std::{convert::Infallible, sync::Arc, time::Duration as StdDuration};
+
+use time::{Duration, OffsetDateTime};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::database::{
+    ARCHIVE_EVENTS_JOB, EVENT_REMINDER_JOB, Job, JobModel, NewJob, PURGE_DELETED_JOB, PURGE_FINISHED_JOBS_JOB,
+    PURGE_IDEMPOTENCY_KEYS_JOB, PostgresDatabase, REAP_SESSIONS_JOB, SYNC_ICAL_FEED_JOB, SYNC_ICAL_FEEDS_JOB,
+    SYNC_VRCHAT_GROUP_JOB, SYNC_VRCHAT_GROUPS_JOB,
+};
+
+/// Jobs whose lease was not renewed within this window are assumed abandoned and picked up again.
+const JOB_LEASE: Duration = Duration::minutes(10);
+/// How often a running job renews its lease, well within [`JOB_LEASE`].
+const LEASE_RENEWAL_INTERVAL: StdDuration = StdDuration::from_mins(1);
+/// How many jobs one process runs at the same time, so that a long sync does not hold up due reminders.
+const MAX_RUNNING_JOBS: usize = 16;
+/// Jobs that reschedule themselves once they ran. They are scheduled once at startup, with the dedupe key keeping a
+/// single instance of each.
+const MAINTENANCE_JOBS: [&str; 7] = [
+    REAP_SESSIONS_JOB,
+    PURGE_IDEMPOTENCY_KEYS_JOB,
+    PURGE_DELETED_JOB,
+    PURGE_FINISHED_JOBS_JOB,
+    ARCHIVE_EVENTS_JOB,
+    SYNC_ICAL_FEEDS_JOB,
+    SYNC_VRCHAT_GROUPS_JOB,
+];
+
+#[derive(Clone, Debug)]
+pub struct SchedulerConfig {
+    pub poll_interval: StdDuration,
+    pub session_reap_interval: Duration,
+    pub idempotency_purge_interval: Duration,
+    /// How long soft deleted events and groups can be restored before they are purged.
+    pub deleted_retention: Duration,
+    pub deleted_purge_interval: Duration,
+    /// How long finished jobs are kept before they are purged.
+    pub finished_job_retention: Duration,
+    pub finished_job_purge_interval: Duration,
+    /// How long after they end events are moved to the archive. Events are never archived when unset.
+    pub archive_after: Option<Duration>,
+    pub archive_interval: Duration,
+    /// How often external iCalendar feeds are imported.
+    pub feed_sync_interval: Duration,
+    /// How far ahead the occurrences of recurring feed events are imported.
+    pub feed_sync_window: Duration,
+    /// Hosts that feeds are fetched from even though they resolve to internal addresses, e.g. a calendar server on
+    /// the same network. Feeds on any other internal host are refused.
+    pub feed_allowed_hosts: Vec<String>,
+    /// Base URL of the game's web API, e.g. `https://api.vrchat.cloud/api/1`. Group calendars are only synced when
+    /// set.
+    pub vrchat_api_url: Option<String>,
+    /// The `auth` cookie of the account the calendar sync signs in as.
+    pub vrchat_auth_cookie: Option<String>,
+    pub vrchat_sync_interval: Duration,
+    /// Discord webhook that event reminders are posted to. Reminders are only logged when unset.
+    pub reminder_webhook_url: Option<String>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: StdDuration::from_secs(5),
+            session_reap_interval: Duration::hours(1),
+            idempotency_purge_interval: Duration::hours(1),
+            deleted_retention: Duration::days(30),
+            deleted_purge_interval: Duration::hours(1),
+            finished_job_retention: Duration::days(7),
+            finished_job_purge_interval: Duration::hours(1),
+            archive_after: Some(Duration::days(30)),
+            archive_interval: Duration::hours(1),
+            feed_sync_interval: Duration::minutes(30),
+            feed_sync_window: Duration::days(90),
+            feed_allowed_hosts: Vec::new(),
+            vrchat_api_url: None,
+            vrchat_auth_cookie: None,
+            vrchat_sync_interval: Duration::minutes(15),
+            reminder_webhook_url: None,
+        }
+    }
+}
+
+enum JobOutcome {
+    Done,
+    Reschedule(OffsetDateTime),
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    db: PostgresDatabase,
+    config: SchedulerConfig,
+    http_client: reqwest::Client,
+}
+
+impl Scheduler {
+    #[must_use]
+    pub fn new(db: PostgresDatabase, config: SchedulerConfig) -> Self {
+        Self {
+            db,
+            config,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Polls the `jobs` table forever. Several app processes may run this concurrently, since due jobs are
+    /// claimed with `FOR UPDATE SKIP LOCKED`.
+    pub async fn run(self) {
+        for kind in MAINTENANCE_JOBS {
+            let job = NewJob {
+                kind,
+                dedupe_key: Some(kind.to_string()),
+                payload: "{}".to_string(),
+                run_at: OffsetDateTime::now_utc(),
+                // never dead-lettered, a failing run is retried with the capped backoff until it reschedules itself
+                max_attempts: i32::MAX,
+            };
+            if let Err(e) = self.db.schedule_job(job).await {
+                tracing::error!(error = ?e, kind, "failed to schedule maintenance job");
+            }
+        }
+
+        let slots = Arc::new(Semaphore::new(MAX_RUNNING_JOBS));
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+
+            // only claim as many jobs as can start right away, the rest are left to other processes
+            let free_slots = slots.available_permits();
+            if free_slots == 0 {
+                continue;
+            }
+
+            match self
+                .db
+                .claim_due_jobs(i64::try_from(free_slots).unwrap_or_default(), JOB_LEASE)
+                .await
+            {
+                Ok(jobs) => {
+                    for job in jobs {
+                        let Ok(slot) = Arc::clone(&slots).acquire_owned().await else {
+                            return;
+                        };
+                        let scheduler = self.clone();
+                        tokio::spawn(async move {
+                            scheduler.run_job(job).await;
+                            drop(slot);
+                        });
+                    }
+                }
+                Err(e) => tracing::error!(error = ?e, "failed to claim jobs"),
+            }
+        }
+    }
+
+    /// Keeps renewing the lease of a running job, until the job finishes and this future is dropped.
+    async fn renew_lease(&self, id: Uuid) -> Infallible {
+        let mut interval = tokio::time::interval(LEASE_RENEWAL_INTERVAL);
+        // the first tick completes immediately, right after the job was claimed
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.db.renew_job_lease(id).await {
+                tracing::warn!(error = ?e, "failed to renew job lease");
+            }
+        }
+    }
+
+    #[tracing::instrument(skip(self, job), fields(id = %job.id, kind = job.kind, attempt = job.attempts))]
+    async fn run_job(&self, job: Job) {
+        let outcome = tokio::select! {
+            outcome = self.execute(&job) => outcome,
+            never = self.renew_lease(job.id) => match never {},
+        };
+
+        let result = match outcome {
+            Ok(JobOutcome::Done) => self.db.complete_job(job.id, job.run_at).await,
+            Ok(JobOutcome::Reschedule(run_at)) => self.db.reschedule_job(job.id, run_at).await,
+            Err(error) => {
+                if job.attempts >= job.max_attempts {
+                    tracing::error!(error, "job failed for the last time, moving to dead-letter");
+                } else {
+                    tracing::warn!(error, "job failed, will retry");
+                }
+                self.db
+                    .fail_job(job.id, job.run_at, &error, retry_at(job.attempts))
+                    .await
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!(error = ?e, "failed to record job result");
+        }
+    }
+
+    async fn execute(&self, job: &Job) -> Result<JobOutcome, String> {
+        match job.kind.as_str() {
+            EVENT_REMINDER_JOB => self.send_event_reminder(&job.payload).await,
+            REAP_SESSIONS_JOB => self.reap_sessions().await,
+            PURGE_IDEMPOTENCY_KEYS_JOB => self.purge_idempotency_keys().await,
+            PURGE_DELETED_JOB => self.purge_deleted().await,
+            PURGE_FINISHED_JOBS_JOB => self.purge_finished_jobs().await,
+            ARCHIVE_EVENTS_JOB => self.archive_events().await,
+            SYNC_ICAL_FEEDS_JOB => self.sync_ical_feeds().await,
+            SYNC_ICAL_FEED_JOB => self.sync_ical_feed(&job.payload).await,
+            SYNC_VRCHAT_GROUPS_JOB => self.sync_vrchat_groups().await,
+            SYNC_VRCHAT_GROUP_JOB => self.sync_vrchat_group(&job.payload).await,
+            kind => Err(format!("unknown job kind '{kind}'")),
+        }
+    }
+}
+
+/// Exponential backoff starting at 30 seconds, capped at an hour.
+fn retry_at(attempts: i32) -> OffsetDateTime {
+    let exponent = u32::try_from(attempts.clamp(1, 8) - 1).unwrap_or_default();
+    let delay = Duration::seconds(30 * 2i64.pow(exponent)).min(Duration::hours(1));
+    OffsetDateTime::now_utc() + delay
+}
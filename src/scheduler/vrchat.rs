@@ -0,0 +1,193 @@
+use std::{collections::HashSet, sync::Arc, time::Duration as StdDuration};
+
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    database::{Actor, AuditContext, DatabaseError, EventModel, GroupModel, SyncModel, SyncSummary, UpsertOutcome},
+    scheduler::{JobOutcome, Scheduler},
+    validation::Validate,
+    vrchat::{CalendarEvent, VRCHAT_EVENT_ID_PREFIX, VrchatClient},
+};
+
+/// How many group calendars are synced at the same time, to go easy on the game's API.
+const MAX_CONCURRENT_GROUP_SYNCS: usize = 4;
+/// How long one run of the periodic sync may take, well within the job lease. Groups it didn't get to are synced
+/// first on the next run.
+const GROUP_SYNC_BUDGET: StdDuration = StdDuration::from_mins(5);
+
+#[derive(Deserialize)]
+struct GroupSyncRequest {
+    vrc_group_id: String,
+}
+
+impl Scheduler {
+    pub(super) async fn sync_vrchat_groups(&self) -> Result<JobOutcome, String> {
+        if let Some(client) = self.vrchat_client() {
+            let group_ids = self.db.get_syncable_group_ids().await.map_err(|e| format!("{e:?}"))?;
+
+            // a failing group is recorded on the group, it shouldn't hold up the others
+            let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_GROUP_SYNCS));
+            let mut syncs = JoinSet::new();
+            let run = async {
+                for group_id in group_ids {
+                    let Ok(slot) = Arc::clone(&slots).acquire_owned().await else {
+                        break;
+                    };
+                    let (scheduler, client) = (self.clone(), client.clone());
+                    syncs.spawn(async move {
+                        if let Err(error) = scheduler.sync_group(&client, &group_id).await {
+                            tracing::error!(error, vrc_group_id = group_id, "failed to sync group");
+                        }
+                        drop(slot);
+                    });
+                }
+                while let Some(result) = syncs.join_next().await {
+                    if let Err(e) = result {
+                        tracing::error!(error = ?e, "group sync panicked");
+                    }
+                }
+            };
+
+            if tokio::time::timeout(GROUP_SYNC_BUDGET, run).await.is_err() {
+                tracing::warn!(
+                    unfinished = syncs.len(),
+                    "group sync ran out of time, the remaining groups are synced on the next run"
+                );
+                syncs.abort_all();
+            }
+        }
+
+        Ok(JobOutcome::Reschedule(
+            OffsetDateTime::now_utc() + self.config.vrchat_sync_interval,
+        ))
+    }
+
+    pub(super) async fn sync_vrchat_group(&self, payload: &str) -> Result<JobOutcome, String> {
+        let request: GroupSyncRequest = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+
+        let Some(client) = self.vrchat_client() else {
+            self.db
+                .record_group_sync_error(&request.vrc_group_id, "calendar sync is not configured")
+                .await
+                .map_err(|e| format!("{e:?}"))?;
+            return Ok(JobOutcome::Done);
+        };
+
+        if self
+            .db
+            .get_group(&request.vrc_group_id)
+            .await
+            .map_err(|e| format!("{e:?}"))?
+            .is_some()
+        {
+            self.sync_group(&client, &request.vrc_group_id).await?;
+        }
+
+        Ok(JobOutcome::Done)
+    }
+
+    fn vrchat_client(&self) -> Option<VrchatClient> {
+        let base_url = self.config.vrchat_api_url.as_deref()?;
+
+        Some(VrchatClient::new(
+            self.http_client.clone(),
+            base_url,
+            self.config.vrchat_auth_cookie.clone(),
+        ))
+    }
+
+    /// Syncs one group and records how it went on the group. Only database errors fail the job.
+    #[tracing::instrument(skip(self, client))]
+    async fn sync_group(&self, client: &VrchatClient, vrc_group_id: &str) -> Result<(), String> {
+        let recorded = match client.get_group_events(vrc_group_id).await {
+            Ok(calendar) => {
+                let summary = self
+                    .apply_calendar(vrc_group_id, &calendar)
+                    .await
+                    .map_err(|e| format!("{e:?}"))?;
+                self.db.record_group_sync(vrc_group_id, summary).await
+            }
+            Err(error) => {
+                tracing::warn!(error, "failed to fetch group calendar");
+                self.db.record_group_sync_error(vrc_group_id, &error).await
+            }
+        };
+
+        recorded.map_err(|e| format!("{e:?}"))
+    }
+
+    /// Creates and updates the calendar's events and deletes the upcoming ones it no longer lists.
+    async fn apply_calendar(
+        &self,
+        vrc_group_id: &str,
+        calendar: &[CalendarEvent],
+    ) -> Result<SyncSummary, DatabaseError> {
+        let audit = AuditContext {
+            actor: Actor::System("vrchat_sync".to_string()),
+            request_id: None,
+        };
+        let now = OffsetDateTime::now_utc();
+
+        let mut listed = HashSet::new();
+        let mut events = Vec::new();
+        for calendar_event in calendar {
+            let create_event = calendar_event.to_create_event();
+            if create_event.vrc_group_id != vrc_group_id {
+                tracing::warn!(
+                    vrc_event_id = create_event.vrc_event_id,
+                    "skipped another group's event"
+                );
+                continue;
+            }
+
+            // an invalid event keeps its stored version rather than being deleted
+            listed.insert(create_event.vrc_event_id.clone());
+            if let Err(errors) = create_event.validate() {
+                tracing::warn!(
+                    vrc_event_id = create_event.vrc_event_id,
+                    ?errors,
+                    "skipped an invalid event"
+                );
+            } else {
+                events.push(create_event);
+            }
+        }
+
+        let mut summary = SyncSummary {
+            event_count: i32::try_from(events.iter().filter(|event| event.ends_at > now).count()).unwrap_or(i32::MAX),
+            ..SyncSummary::default()
+        };
+
+        let ids: Vec<String> = events.iter().map(|event| event.vrc_event_id.clone()).collect();
+        // the game already scheduled these, refusing an overlap here would only leave the calendars out of sync
+        let outcomes = self.db.upsert_events(events, false, &audit).await?;
+        for (id, outcome) in ids.iter().zip(outcomes) {
+            match outcome {
+                Ok((UpsertOutcome::Created, _)) => summary.created += 1,
+                Ok((UpsertOutcome::Updated, _)) => summary.updated += 1,
+                Ok((UpsertOutcome::Unchanged, _)) => {}
+                // deleted here, which a sync should not undo
+                Ok((UpsertOutcome::Deleted, _)) => tracing::debug!(vrc_event_id = id, "skipped a deleted event"),
+                Ok((UpsertOutcome::GroupMismatch, _)) => tracing::warn!(vrc_event_id = id, "skipped a moved event"),
+                Err(error) => tracing::warn!(vrc_event_id = id, error = ?error, "failed to sync an event"),
+            }
+        }
+
+        // past events drop off calendars, so only upcoming ones that disappeared were deleted in game
+        let stale = self
+            .db
+            .get_unended_event_ids(vrc_group_id, VRCHAT_EVENT_ID_PREFIX)
+            .await?
+            .into_iter()
+            .filter(|id| !listed.contains(id));
+        for id in stale {
+            if self.db.delete_event(&id, None, &audit).await? {
+                summary.deleted += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,109 @@
+//! Field-level validation of request bodies.
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found in a request body, so clients can fix them all in one round trip.
+#[derive(Debug, Default)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// # Errors
+    ///
+    /// Returns `self` if any errors were added.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.0.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    pub fn check_length(&mut self, field: &str, value: &str, min: usize, max: usize) {
+        let length = value.chars().count();
+        if length < min {
+            if min == 1 {
+                self.add(field, "must not be empty");
+            } else {
+                self.add(field, format!("must be at least {min} characters"));
+            }
+        } else if length > max {
+            self.add(field, format!("must be at most {max} characters"));
+        }
+    }
+
+    /// Checks that `value` is an absolute `http` or `https` URL, optionally restricted to `hosts`.
+    pub fn check_url(&mut self, field: &str, value: &str, hosts: &[&str]) {
+        const MAX_URL_LENGTH: usize = 2048;
+
+        if value.len() > MAX_URL_LENGTH {
+            self.add(field, format!("must be at most {MAX_URL_LENGTH} characters"));
+            return;
+        }
+
+        match Url::parse(value) {
+            Ok(url) if !matches!(url.scheme(), "http" | "https") => self.add(field, "must be an http or https URL"),
+            Ok(url) if !hosts.is_empty() && !url.host_str().is_some_and(|host| hosts.contains(&host)) => {
+                self.add(field, format!("must be a link to {}", hosts.join(" or ")));
+            }
+            Ok(_) => {}
+            Err(_) => self.add(field, "must be a valid URL"),
+        }
+    }
+
+    pub fn check_one_of(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value) {
+            self.add(field, format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    pub fn check_timezone(&mut self, field: &str, value: &str) {
+        if time_tz::timezones::get_by_name(value).is_none() {
+            self.add(field, "must be an IANA time zone name such as 'Europe/Berlin'");
+        }
+    }
+
+    /// Accepts simple BCP 47 language tags such as `en`, `ja` or `pt-BR`.
+    pub fn check_language(&mut self, field: &str, value: &str) {
+        let mut subtags = value.split('-');
+        let primary_ok = subtags
+            .next()
+            .is_some_and(|primary| (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_lowercase()));
+        let rest_ok =
+            subtags.all(|subtag| (2..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        if !(primary_ok && rest_ok) {
+            self.add(field, "must be a language tag such as 'en' or 'pt-BR'");
+        }
+    }
+}
+
+impl From<serde_json::Error> for ValidationErrors {
+    fn from(value: serde_json::Error) -> Self {
+        let mut errors = Self::default();
+        errors.add("body", value.to_string());
+        errors
+    }
+}
+
+pub trait Validate {
+    /// # Errors
+    ///
+    /// Returns every field that failed validation.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
@@ -0,0 +1,164 @@
+//! Client for the game's web API, of which the sync worker reads group calendars.
+
+use std::time::Duration as StdDuration;
+
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::database::{CreateEvent, EventAccessType, EventCategory, EventPlatform, import_tags};
+
+/// The IDs of events on in-game calendars start with this. Only events with such IDs are deleted when a calendar
+/// stops listing them, so events from other sources survive a sync.
+pub const VRCHAT_EVENT_ID_PREFIX: &str = "cal_";
+const USER_AGENT: &str = concat!("vrc-calendar/", env!("CARGO_PKG_VERSION"), " calendar sync");
+const REQUEST_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+const EVENTS_PER_PAGE: usize = 100;
+/// Stops paging a calendar after this many pages, in case the API keeps reporting more.
+const MAX_PAGES: usize = 20;
+
+/// An event of a group calendar as returned by the API.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub id: String,
+    pub owner_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub starts_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ends_at: OffsetDateTime,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub access_type: Option<String>,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarPage {
+    results: Vec<CalendarEvent>,
+    #[serde(default)]
+    has_next: bool,
+}
+
+impl CalendarEvent {
+    /// The event as stored. Values this build doesn't know fall back like existing rows were migrated: the
+    /// category to `other`, the access type to `group`, and unknown platforms are dropped.
+    #[must_use]
+    pub fn to_create_event(&self) -> CreateEvent {
+        let category = self
+            .category
+            .clone()
+            .map(EventCategory::from)
+            .filter(EventCategory::is_known)
+            .unwrap_or(EventCategory::Other);
+        let access_type = self
+            .access_type
+            .clone()
+            .map(EventAccessType::from)
+            .filter(EventAccessType::is_known)
+            .unwrap_or(EventAccessType::Group);
+        let mut platforms: Vec<EventPlatform> = Vec::new();
+        for platform in self.platforms.iter().cloned().map(EventPlatform::from) {
+            if platform.is_known() && !platforms.contains(&platform) {
+                platforms.push(platform);
+            }
+        }
+        let tags = import_tags(&self.tags);
+
+        CreateEvent {
+            vrc_event_id: self.id.clone(),
+            vrc_group_id: self.owner_id.clone(),
+            name: self.title.clone(),
+            description: self.description.clone(),
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            category,
+            access_type,
+            platforms,
+            image_url: self.image_url.clone().filter(|url| !url.is_empty()),
+            tags: (!tags.is_empty()).then_some(tags),
+        }
+    }
+}
+
+/// Reads group calendars from the API at `base_url`, e.g. `https://api.vrchat.cloud/api/1` or a local mock.
+#[derive(Clone)]
+pub struct VrchatClient {
+    http_client: reqwest::Client,
+    base_url: String,
+    auth_cookie: Option<String>,
+}
+
+impl VrchatClient {
+    /// `auth_cookie` is the value of the `auth` cookie of the account the worker signs in as.
+    #[must_use]
+    pub fn new(http_client: reqwest::Client, base_url: &str, auth_cookie: Option<String>) -> Self {
+        Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth_cookie,
+        }
+    }
+
+    /// Every event on the group's calendar.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the failure if `vrc_group_id` is not a group ID, a request fails, the API does not
+    /// respond with a success status, or the response is not a calendar page.
+    pub async fn get_group_events(&self, vrc_group_id: &str) -> Result<Vec<CalendarEvent>, String> {
+        // the ID becomes part of the path, so it must not be able to change which endpoint is requested
+        if !is_group_id(vrc_group_id) {
+            return Err("the group ID is not a valid group ID".to_string());
+        }
+
+        let mut events = Vec::new();
+
+        for page in 0..MAX_PAGES {
+            let mut request = self
+                .http_client
+                .get(format!("{}/calendar/{vrc_group_id}", self.base_url))
+                .query(&[("n", EVENTS_PER_PAGE), ("offset", page * EVENTS_PER_PAGE)])
+                .header(reqwest::header::USER_AGENT, USER_AGENT)
+                .timeout(REQUEST_TIMEOUT);
+            if let Some(auth_cookie) = &self.auth_cookie {
+                request = request.header(reqwest::header::COOKIE, format!("auth={auth_cookie}"));
+            }
+
+            let page: CalendarPage = request
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| e.without_url().to_string())?
+                .json()
+                .await
+                .map_err(|e| e.without_url().to_string())?;
+
+            let last = !page.has_next || page.results.is_empty();
+            events.extend(page.results);
+            if last {
+                return Ok(events);
+            }
+        }
+
+        Err(format!(
+            "the calendar has more than {} events",
+            MAX_PAGES * EVENTS_PER_PAGE
+        ))
+    }
+}
+
+/// Whether `id` looks like a group ID, such as `grp_c1644b5b-3ca4-45b4-97c6-a2a0de70d469`.
+fn is_group_id(id: &str) -> bool {
+    id.strip_prefix("grp_")
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+}
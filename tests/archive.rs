@@ -0,0 +1,121 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call};
+use rust_vue_skeleton::database::{ArchiveModel, AttendeeModel, PostgresDatabase, RsvpStatus};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+async fn archive(db: &PostgresDatabase) -> u64 {
+    db.archive_events(OffsetDateTime::now_utc())
+        .await
+        .expect("events are archived")
+}
+
+async fn archived_rsvps(pool: &PgPool, vrc_event_id: &str) -> Vec<(String, String)> {
+    sqlx::query_as("SELECT user_id, status FROM event_attendees_archive WHERE vrc_event_id = $1 ORDER BY user_id")
+        .bind(vrc_event_id)
+        .fetch_all(pool)
+        .await
+        .expect("archived rsvps load")
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn ended_events_are_archived_with_their_rsvps(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    db.set_rsvp("evt_past", "user_1", RsvpStatus::Going)
+        .await
+        .expect("rsvp is stored");
+    db.set_rsvp("evt_past", "user_2", RsvpStatus::Interested)
+        .await
+        .expect("rsvp is stored");
+
+    assert_eq!(archive(&db).await, 1);
+
+    let archived = db
+        .get_archived_event("evt_past")
+        .await
+        .expect("archived event loads")
+        .expect("event is archived");
+    assert_eq!(archived.event.going_count, 1);
+    assert_eq!(archived.event.interested_count, 1);
+    assert_eq!(
+        archived_rsvps(&pool, "evt_past").await,
+        [
+            ("user_1".to_string(), "going".to_string()),
+            ("user_2".to_string(), "interested".to_string())
+        ]
+    );
+
+    // the event is gone from the live tables, and RSVPs to it are refused
+    assert!(
+        db.get_archived_event("evt_test")
+            .await
+            .expect("archive loads")
+            .is_none()
+    );
+    let rsvp = db
+        .set_rsvp("evt_past", "user_3", RsvpStatus::Going)
+        .await
+        .expect("rsvp runs");
+    assert!(rsvp.is_none());
+    assert_eq!(archive(&db).await, 0);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn rearchived_events_replace_their_archived_copy(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    db.set_rsvp("evt_past", "user_1", RsvpStatus::Going)
+        .await
+        .expect("rsvp is stored");
+    archive(&db).await;
+
+    // recreated under the same ID, and ended again
+    sqlx::query(
+        "INSERT INTO events (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, \
+         access_type, platforms) \
+         VALUES ('evt_past', 'grp_test', 'Again', '', now() - interval '2 hours', now() - interval '1 hour', \
+         'music', 'public', '{android}')",
+    )
+    .execute(&pool)
+    .await
+    .expect("event is recreated");
+    assert_eq!(archive(&db).await, 1);
+
+    let archived = db
+        .get_archived_event("evt_past")
+        .await
+        .expect("archived event loads")
+        .expect("event is archived");
+    assert_eq!(archived.event.name, "Again");
+    assert!(archived_rsvps(&pool, "evt_past").await.is_empty());
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn the_calendar_shows_archived_events(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    archive(&db).await;
+    let app = app(pool);
+
+    let date = (OffsetDateTime::now_utc() - time::Duration::days(2)).date();
+    let calendar = call(
+        &app,
+        "GET",
+        &format!("/api/v1/calendar?view=week&date={date}&tz=UTC"),
+        None,
+    )
+    .await;
+    assert_eq!(calendar.status, StatusCode::OK);
+    assert_eq!(calendar.body["total"], 1);
+
+    // the default listing stays on live events
+    let events = call(&app, "GET", "/api/v1/events?include_past=true", None).await;
+    let ids: Vec<_> = events
+        .body
+        .as_array()
+        .expect("events are a list")
+        .iter()
+        .map(|event| event["vrc_event_id"].clone())
+        .collect();
+    assert_eq!(ids, ["evt_test"]);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use axum::http::{Request, StatusCode};
+use common::{API_KEY, app, call, event_json, json_body, send};
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn changes_are_attributed_to_the_api_key_not_the_user_agent(pool: PgPool) {
+    let app = app(pool.clone());
+    let event = event_json("evt_new", 5);
+    let request = Request::post("/api/v1/events")
+        .header("x-api-key", API_KEY)
+        .header("user-agent", "someone-else")
+        .header("content-type", "application/json")
+        .body(json_body(Some(&event)))
+        .expect("request is valid");
+
+    let response = send(&app, request).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let (actor_type, actor): (String, String) =
+        sqlx::query_as("SELECT actor_type, actor FROM audit_log WHERE entity_id = 'evt_new'")
+            .fetch_one(&pool)
+            .await
+            .expect("change is audited");
+    assert_eq!(actor_type, "api");
+    assert_eq!(actor, "test-bot");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn pages_past_the_deepest_offset_are_refused(pool: PgPool) {
+    let app = app(pool);
+
+    let first = call(&app, "GET", "/api/v1/audit?limit=1", None).await;
+    assert_eq!(first.status, StatusCode::OK);
+
+    let overflowing = call(&app, "GET", "/api/v1/audit?offset=9223372036854775807", None).await;
+    assert_eq!(overflowing.status, StatusCode::BAD_REQUEST);
+    assert_eq!(overflowing.body["code"], "bad_request");
+}
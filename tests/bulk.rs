@@ -0,0 +1,143 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, event_json};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+async fn event_exists(pool: &PgPool, vrc_event_id: &str) -> bool {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM events WHERE vrc_event_id = $1)")
+        .bind(vrc_event_id)
+        .fetch_one(pool)
+        .await
+        .expect("event is looked up")
+}
+
+async fn audit_entries(pool: &PgPool, vrc_event_id: &str) -> i64 {
+    sqlx::query_scalar("SELECT count(*) FROM audit_log WHERE entity_type = 'event' AND entity_id = $1")
+        .bind(vrc_event_id)
+        .fetch_one(pool)
+        .await
+        .expect("audit entries are counted")
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn events_fail_on_their_own(pool: PgPool) {
+    let app = app(pool.clone());
+    let mut invalid = event_json("evt_invalid", 5);
+    invalid["name"] = json!("");
+    let mut unknown_group = event_json("evt_unknown_group", 5);
+    unknown_group["vrc_group_id"] = json!("grp_missing");
+    let created = call(&app, "POST", "/api/v1/events", Some(&event_json("evt_unchanged", 5))).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let unchanged = call(&app, "GET", "/api/v1/events/evt_unchanged", None).await.body;
+
+    let batch = json!([event_json("evt_new", 5), invalid, unknown_group, unchanged]);
+    let response = call(&app, "POST", "/api/v1/events/bulk", Some(&batch)).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["created"], 1);
+    assert_eq!(response.body["updated"], 0);
+    assert_eq!(response.body["unchanged"], 1);
+    assert_eq!(response.body["failed"], 2);
+
+    let results = response.body["results"].as_array().expect("results are a list");
+    let statuses: Vec<_> = results.iter().map(|result| result["status"].as_str()).collect();
+    assert_eq!(
+        statuses,
+        [Some("created"), Some("error"), Some("error"), Some("unchanged")]
+    );
+    assert_eq!(results[1]["vrc_event_id"], "evt_invalid");
+    assert_eq!(results[1]["errors"][0]["field"], "name");
+    assert_eq!(results[2]["errors"][0]["field"], "vrc_group_id");
+    assert!(results[0].get("errors").is_none());
+
+    assert!(event_exists(&pool, "evt_new").await);
+    assert!(!event_exists(&pool, "evt_invalid").await);
+    assert!(!event_exists(&pool, "evt_unknown_group").await);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn events_the_database_rejects_are_rolled_back(pool: PgPool) {
+    // fails the audit entry of one event, after its row was already written
+    sqlx::query(
+        "CREATE FUNCTION reject_audit() RETURNS trigger AS $$ \
+         BEGIN IF NEW.entity_id = 'evt_rejected' THEN RAISE EXCEPTION 'rejected'; END IF; RETURN NEW; END \
+         $$ LANGUAGE plpgsql",
+    )
+    .execute(&pool)
+    .await
+    .expect("function is created");
+    sqlx::query("CREATE TRIGGER reject_audit BEFORE INSERT ON audit_log FOR EACH ROW EXECUTE FUNCTION reject_audit()")
+        .execute(&pool)
+        .await
+        .expect("trigger is created");
+
+    let app = app(pool.clone());
+    let batch = json!([
+        event_json("evt_before", 5),
+        event_json("evt_rejected", 5),
+        event_json("evt_after", 5)
+    ]);
+    let response = call(&app, "POST", "/api/v1/events/bulk", Some(&batch)).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["created"], 2);
+    assert_eq!(response.body["failed"], 1);
+    let rejected = &response.body["results"][1];
+    assert_eq!(rejected["status"], "error");
+    assert_eq!(rejected["errors"][0]["field"], "body");
+    // the cause is logged, not sent to the client
+    assert_eq!(
+        rejected["errors"][0]["message"],
+        "could not be stored, the cause was logged"
+    );
+
+    assert!(event_exists(&pool, "evt_before").await);
+    assert!(event_exists(&pool, "evt_after").await);
+    assert!(!event_exists(&pool, "evt_rejected").await);
+    assert_eq!(audit_entries(&pool, "evt_before").await, 1);
+    assert_eq!(audit_entries(&pool, "evt_rejected").await, 0);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn oversized_batches_are_refused(pool: PgPool) {
+    let app = app(pool.clone());
+    let batch = Value::Array((0..501).map(|i| event_json(&format!("evt_{i}"), 5)).collect());
+
+    let response = call(&app, "POST", "/api/v1/events/bulk", Some(&batch)).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["code"], "validation_failed");
+    assert!(!event_exists(&pool, "evt_0").await);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn overlapping_events_are_listed_and_optionally_refused(pool: PgPool) {
+    let app = app(pool.clone());
+
+    // evt_test runs a day from now for two hours
+    let batch = json!([event_json("evt_overlap", 24), event_json("evt_free", 48)]);
+    let warned = call(&app, "POST", "/api/v1/events/bulk", Some(&batch)).await;
+    assert_eq!(warned.body["created"], 2);
+    assert_eq!(warned.body["results"][0]["conflicts"][0]["vrc_event_id"], "evt_test");
+    assert!(warned.body["results"][1].get("conflicts").is_none());
+
+    // earlier events of the same batch count too, and unchanged events keep the overlaps they had
+    let batch = json!([
+        event_json("evt_later", 72),
+        event_json("evt_same_time", 72),
+        call(&app, "GET", "/api/v1/events/evt_overlap", None).await.body,
+    ]);
+    let refused = call(&app, "POST", "/api/v1/events/bulk?reject_conflicts=true", Some(&batch)).await;
+
+    assert_eq!(refused.status, StatusCode::OK);
+    assert_eq!(refused.body["created"], 1);
+    assert_eq!(refused.body["unchanged"], 1);
+    assert_eq!(refused.body["failed"], 1);
+    let result = &refused.body["results"][1];
+    assert_eq!(result["status"], "error");
+    assert_eq!(result["errors"][0]["field"], "starts_at");
+    assert_eq!(result["conflicts"][0]["vrc_event_id"], "evt_later");
+    assert!(!event_exists(&pool, "evt_same_time").await);
+}
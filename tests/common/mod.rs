@@ -0,0 +1,125 @@
+//! Helpers shared by the integration tests that send requests to the app.
+
+#![allow(dead_code)]
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{HeaderMap, Request, StatusCode},
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rust_vue_skeleton::{app::App, database::PostgresDatabase, oauth::OAuth, scheduler::SchedulerConfig};
+use serde_json::Value;
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+/// The API key of `tests/fixtures/calendar.sql`.
+pub const API_KEY: &str = "test-key";
+
+pub fn app(pool: PgPool) -> Router {
+    let oauth = OAuth::new(
+        "client".to_string(),
+        "secret".to_string(),
+        "http://localhost/auth/callback".to_string(),
+    );
+    let app_key = BASE64_STANDARD.encode([7; 64]);
+
+    App::new(
+        PostgresDatabase::from_pool(pool),
+        oauth,
+        app_key,
+        SchedulerConfig::default(),
+    )
+    .into_router()
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The body as JSON, or `Null` if it is empty or not JSON.
+    pub body: Value,
+    /// The body as text, for the responses that are not JSON.
+    pub text: String,
+}
+
+pub async fn send(app: &Router, request: Request<Body>) -> TestResponse {
+    let response = app.clone().oneshot(request).await.expect("app responds");
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX).await.expect("body is read");
+
+    TestResponse {
+        status: parts.status,
+        headers: parts.headers,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+        text: String::from_utf8_lossy(&body).into_owned(),
+    }
+}
+
+/// A request authenticated with [`API_KEY`], with `body` as JSON if given.
+pub fn api_request(method: &str, uri: &str, body: Option<&Value>) -> axum::http::request::Builder {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-api-key", API_KEY)
+        .header("user-agent", "test-bot");
+
+    if body.is_some() {
+        builder.header("content-type", "application/json")
+    } else {
+        builder
+    }
+}
+
+pub fn json_body(body: Option<&Value>) -> Body {
+    body.map_or_else(Body::empty, |body| Body::from(body.to_string()))
+}
+
+/// Sends an authenticated API request with an optional JSON body.
+pub async fn call(app: &Router, method: &str, uri: &str, body: Option<&Value>) -> TestResponse {
+    let request = api_request(method, uri, body)
+        .body(json_body(body))
+        .expect("request is valid");
+    send(app, request).await
+}
+
+/// Logs `user_id`, one of the `user_<n>` of `tests/fixtures/calendar.sql`, in to a new session, returning the
+/// session's cookie.
+pub async fn log_in(pool: &PgPool, user_id: &str) -> String {
+    let session_id = format!("session_{user_id}");
+    sqlx::query("INSERT INTO sessions (id, expires, store) VALUES ($1, now() + interval '1 day', $2)")
+        .bind(&session_id)
+        .bind(serde_json::json!({ "user_id": user_id }).to_string())
+        .execute(pool)
+        .await
+        .expect("session is created");
+
+    format!("__Host-Http-Session={session_id}")
+}
+
+/// Sends a request of the session behind `cookie`, see [`log_in`], with an optional JSON body.
+pub async fn call_as(app: &Router, cookie: &str, method: &str, uri: &str, body: Option<&Value>) -> TestResponse {
+    let mut request = Request::builder().method(method).uri(uri).header("cookie", cookie);
+    if body.is_some() {
+        request = request.header("content-type", "application/json");
+    }
+    send(app, request.body(json_body(body)).expect("request is valid")).await
+}
+
+/// A valid event of `grp_test` starting `in_hours` from now.
+pub fn event_json(vrc_event_id: &str, in_hours: i64) -> Value {
+    let starts_at = time::OffsetDateTime::now_utc() + time::Duration::hours(in_hours);
+    let ends_at = starts_at + time::Duration::hours(1);
+    let format = time::format_description::well_known::Rfc3339;
+
+    serde_json::json!({
+        "vrc_event_id": vrc_event_id,
+        "vrc_group_id": "grp_test",
+        "name": "Test event",
+        "description": "",
+        "category": "hangout",
+        "access_type": "public",
+        "platforms": ["standalonewindows"],
+        "starts_at": starts_at.format(&format).expect("time formats"),
+        "ends_at": ends_at.format(&format).expect("time formats"),
+    })
+}
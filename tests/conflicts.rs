@@ -0,0 +1,74 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, event_json};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn overlaps_are_listed_or_rejected(pool: PgPool) {
+    let app = app(pool);
+    // evt_test runs from 24 to 26 hours from now
+    let overlapping = event_json("evt_overlapping", 25);
+
+    let rejected = call(&app, "POST", "/api/v1/events?reject_conflicts=true", Some(&overlapping)).await;
+    assert_eq!(rejected.status, StatusCode::CONFLICT);
+    assert_eq!(rejected.body["code"], "schedule_conflict");
+    assert_eq!(rejected.body["conflicts"][0]["vrc_event_id"], "evt_test");
+    let missing = call(&app, "GET", "/api/v1/events/evt_overlapping", None).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let created = call(&app, "POST", "/api/v1/events", Some(&overlapping)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    assert_eq!(created.body["conflicts"][0]["vrc_event_id"], "evt_test");
+
+    // an event does not conflict with itself, only with the others
+    let mut moved = event_json("evt_overlapping", 48);
+    moved["name"] = json!("Moved");
+    let updated = call(
+        &app,
+        "PUT",
+        "/api/v1/events/evt_overlapping?reject_conflicts=true",
+        Some(&moved),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert!(updated.body.get("conflicts").is_none());
+
+    let back = call(
+        &app,
+        "PUT",
+        "/api/v1/events/evt_overlapping?reject_conflicts=true",
+        Some(&overlapping),
+    )
+    .await;
+    assert_eq!(back.status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn concurrent_rejecting_writes_cannot_double_book(pool: PgPool) {
+    let app = app(pool.clone());
+
+    for round in 0..10 {
+        let in_hours = 100 + round * 10;
+        let first = event_json(&format!("evt_{round}_a"), in_hours);
+        let second = event_json(&format!("evt_{round}_b"), in_hours);
+
+        let (first, second) = tokio::join!(
+            call(&app, "POST", "/api/v1/events?reject_conflicts=true", Some(&first)),
+            call(&app, "POST", "/api/v1/events?reject_conflicts=true", Some(&second)),
+        );
+        let mut statuses = [first.status, second.status];
+        statuses.sort();
+        assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT], "round {round}");
+    }
+
+    let overlaps: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM events a JOIN events b \
+         ON a.vrc_group_id = b.vrc_group_id AND a.vrc_event_id < b.vrc_event_id AND a.during && b.during",
+    )
+    .fetch_one(&pool)
+    .await
+    .expect("overlaps are counted");
+    assert_eq!(overlaps, 0);
+}
@@ -0,0 +1,150 @@
+use axum::{extract::FromRequestParts, http::Request};
+use rust_vue_skeleton::{
+    database::{Actor, AttendeeModel, AuditContext, CreateEvent, Event, EventModel, PostgresDatabase, RsvpStatus},
+    extractors::Preconditions,
+    routes::{collection_etag, event_etag, version_etag},
+};
+use sqlx::PgPool;
+
+async fn preconditions(header: &str, value: &str) -> Preconditions {
+    let (mut parts, ()) = Request::builder()
+        .header(header, value)
+        .body(())
+        .expect("request is valid")
+        .into_parts();
+    let Ok(preconditions) = Preconditions::from_request_parts(&mut parts, &()).await;
+    preconditions
+}
+
+fn audit() -> AuditContext {
+    AuditContext {
+        actor: Actor::System("test".to_string()),
+        request_id: None,
+    }
+}
+
+fn renamed(event: &Event, name: &str) -> CreateEvent {
+    CreateEvent {
+        vrc_event_id: event.vrc_event_id.clone(),
+        vrc_group_id: event.vrc_group_id.clone(),
+        name: name.to_string(),
+        description: event.description.clone(),
+        starts_at: event.starts_at,
+        ends_at: event.ends_at,
+        category: event.category.clone(),
+        access_type: event.access_type.clone(),
+        platforms: event.platforms.to_vec(),
+        image_url: event.image_url.clone(),
+        tags: event.tags.clone(),
+    }
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn rsvps_change_the_etag_but_not_the_version(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool);
+    let before = db
+        .get_event("evt_test")
+        .await
+        .expect("event loads")
+        .expect("event exists");
+
+    db.set_rsvp("evt_test", "user_1", RsvpStatus::Going)
+        .await
+        .expect("rsvp is stored");
+
+    let after = db
+        .get_event("evt_test")
+        .await
+        .expect("event loads")
+        .expect("event exists");
+    assert_eq!(after.version, before.version);
+    assert_ne!(event_etag(&after), event_etag(&before));
+
+    // an organizer holding the ETag from before the RSVP can still edit the event
+    let if_match = preconditions("if-match", &event_etag(&before)).await;
+    assert!(if_match.check_if_match_version(after.version).is_ok());
+    // and a cached copy is refreshed
+    let if_none_match = preconditions("if-none-match", &event_etag(&before)).await;
+    assert!(!if_none_match.is_not_modified(&event_etag(&after)));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn edits_at_a_stale_version_are_refused(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool);
+    let event = db
+        .get_event("evt_test")
+        .await
+        .expect("event loads")
+        .expect("event exists");
+
+    let updated = db
+        .update_event(
+            "evt_test",
+            renamed(&event, "First edit"),
+            Some(event.version),
+            false,
+            &audit(),
+        )
+        .await
+        .expect("update runs")
+        .expect("version matched")
+        .0;
+    assert_eq!(updated.version, event.version + 1);
+
+    let if_match = preconditions("if-match", &event_etag(&event)).await;
+    assert!(if_match.check_if_match_version(updated.version).is_err());
+    let stale = db
+        .update_event(
+            "evt_test",
+            renamed(&event, "Second edit"),
+            Some(event.version),
+            false,
+            &audit(),
+        )
+        .await
+        .expect("update runs");
+    assert!(stale.is_none());
+
+    let current = db
+        .get_event("evt_test")
+        .await
+        .expect("event loads")
+        .expect("event exists");
+    assert_eq!(current.name, "First edit");
+}
+
+/// Whether an `If-Match: {if_match}` holds for version 3.
+async fn matches_version_3(if_match: &str) -> bool {
+    preconditions("if-match", if_match)
+        .await
+        .check_if_match_version(3)
+        .is_ok()
+}
+
+#[tokio::test]
+async fn if_match_needs_a_strong_tag_at_the_version() {
+    assert!(matches_version_3("*").await);
+    assert!(matches_version_3("\"2.0.0\", \"3.1.0\"").await);
+    assert!(matches_version_3("\"3\"").await);
+    assert!(!matches_version_3("W/\"3.0.0\"").await);
+    assert!(!matches_version_3("\"31.0.0\"").await);
+    assert!(Preconditions::default().check_if_match_version(3).is_ok());
+}
+
+#[test]
+fn collection_etags_follow_their_items() {
+    let etag = collection_etag([("a", version_etag(1)), ("b", version_etag(1))]);
+
+    assert_eq!(etag, collection_etag([("a", version_etag(1)), ("b", version_etag(1))]));
+    assert_ne!(etag, collection_etag([("a", version_etag(1)), ("b", version_etag(2))]));
+    assert_ne!(etag, collection_etag([("a", version_etag(1))]));
+    assert!(etag.starts_with("W/\""));
+}
+
+#[test]
+fn collection_etags_do_not_change_between_builds() {
+    assert_eq!(
+        collection_etag([("a", version_etag(1)), ("b", version_etag(2))]),
+        "W/\"49e42a502c6d9b0e01ea7876bd4dddff\""
+    );
+}
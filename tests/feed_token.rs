@@ -0,0 +1,70 @@
+mod common;
+
+use axum::http::{StatusCode, header::CONTENT_TYPE};
+use common::{app, call_as, log_in};
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn feeds_exist_once_a_token_was_created(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+
+    let missing = call_as(&app, &cookie, "GET", "/api/v1/me/feed-token", None).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let created = call_as(&app, &cookie, "POST", "/api/v1/me/feed-token", None).await;
+    assert_eq!(created.status, StatusCode::OK);
+    let token = created.body["token"].as_str().expect("token is shown on creation");
+    assert_eq!(created.body["ics_path"], format!("/api/v1/feeds/{token}/events.ics"));
+
+    // only a hash is kept, so the token can't be shown again
+    let view = call_as(&app, &cookie, "GET", "/api/v1/me/feed-token", None).await;
+    assert_eq!(view.status, StatusCode::OK);
+    assert!(view.body["created_at"].is_string());
+    assert!(view.body.get("token").is_none());
+    let stored: Option<String> = sqlx::query_scalar("SELECT feed_token_hash FROM users WHERE id = 'user_1'")
+        .fetch_one(&pool)
+        .await
+        .expect("user exists");
+    assert_ne!(stored.as_deref(), Some(token));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn feeds_list_the_events_of_followed_groups(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+
+    let followed = call_as(&app, &cookie, "POST", "/api/v1/groups/grp_test/follow", None).await;
+    assert!(followed.status.is_success());
+    let created = call_as(&app, &cookie, "POST", "/api/v1/me/feed-token", None).await;
+    let ics_path = created.body["ics_path"].as_str().expect("feed has a path");
+
+    // calendar apps subscribe without a session
+    let feed = call_as(&app, "", "GET", ics_path, None).await;
+    assert_eq!(feed.status, StatusCode::OK);
+    assert_eq!(feed.headers[CONTENT_TYPE], "text/calendar; charset=utf-8");
+    assert!(feed.text.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(feed.text.contains("\r\nUID:evt_test\r\n"));
+    assert!(feed.text.contains("\r\nSUMMARY:Test event\r\n"));
+    assert!(feed.text.ends_with("END:VCALENDAR\r\n"));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn regenerating_the_token_revokes_the_old_links(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+
+    let first = call_as(&app, &cookie, "POST", "/api/v1/me/feed-token", None).await;
+    let old_path = first.body["json_path"].as_str().expect("feed has a path");
+    assert_eq!(call_as(&app, "", "GET", old_path, None).await.status, StatusCode::OK);
+
+    let second = call_as(&app, &cookie, "POST", "/api/v1/me/feed-token", None).await;
+    let new_path = second.body["json_path"].as_str().expect("feed has a path");
+    assert_ne!(new_path, old_path);
+
+    assert_eq!(
+        call_as(&app, "", "GET", old_path, None).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(call_as(&app, "", "GET", new_path, None).await.status, StatusCode::OK);
+}
@@ -0,0 +1,13 @@
+insert into api_users (api_key, user_agent) values ('test-key', 'test-bot');
+
+insert into groups (vrc_group_id, name) values ('grp_test', 'Test group'), ('grp_other', 'Other group');
+
+insert into events (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms)
+values
+    ('evt_test', 'grp_test', 'Test event', '', now() + interval '1 day', now() + interval '1 day 2 hours', 'hangout',
+     'public', '{standalonewindows}'),
+    ('evt_past', 'grp_test', 'Past event', '', now() - interval '2 days', now() - interval '2 days' + interval '1 hour',
+     'music', 'public', '{android}');
+
+insert into users (id, username)
+select 'user_' || n, 'user ' || n from generate_series(1, 20) n;
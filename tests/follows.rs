@@ -0,0 +1,75 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, call_as, event_json, log_in};
+use serde_json::Value;
+use sqlx::PgPool;
+
+fn event_ids(page: &Value) -> Vec<&str> {
+    page["events"]
+        .as_array()
+        .expect("page lists events")
+        .iter()
+        .map(|event| event["vrc_event_id"].as_str().expect("event has an id"))
+        .collect()
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn feeds_page_through_the_upcoming_events_of_followed_groups(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+    let mut other = event_json("evt_other", 3);
+    other["vrc_group_id"] = "grp_other".into();
+    for event in [event_json("evt_later", 48), other] {
+        let created = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+        assert_eq!(created.status, StatusCode::CREATED);
+    }
+
+    let followed = call_as(&app, &cookie, "POST", "/api/v1/groups/grp_test/follow", None).await;
+    assert_eq!(followed.status, StatusCode::OK);
+
+    let first = call_as(&app, &cookie, "GET", "/api/v1/me/feed?limit=1", None).await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(event_ids(&first.body), ["evt_test"]);
+    assert_eq!(first.body["next_offset"], 1);
+
+    let second = call_as(&app, &cookie, "GET", "/api/v1/me/feed?limit=1&offset=1", None).await;
+    assert_eq!(event_ids(&second.body), ["evt_later"]);
+    assert!(second.body["next_offset"].is_null());
+
+    let groups = call_as(&app, &cookie, "GET", "/api/v1/me/groups", None).await;
+    assert_eq!(groups.body[0]["vrc_group_id"], "grp_test");
+    assert_eq!(groups.body.as_array().map(Vec::len), Some(1));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn unfollowed_groups_leave_the_feed(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+    call_as(&app, &cookie, "POST", "/api/v1/groups/grp_test/follow", None).await;
+
+    let unfollowed = call_as(&app, &cookie, "DELETE", "/api/v1/groups/grp_test/follow", None).await;
+    assert_eq!(unfollowed.status, StatusCode::OK);
+
+    let feed = call_as(&app, &cookie, "GET", "/api/v1/me/feed", None).await;
+    assert!(event_ids(&feed.body).is_empty());
+    // other users' follows are their own
+    let other = log_in(&pool, "user_2").await;
+    call_as(&app, &other, "POST", "/api/v1/groups/grp_test/follow", None).await;
+    let feed = call_as(&app, &cookie, "GET", "/api/v1/me/feed", None).await;
+    assert!(event_ids(&feed.body).is_empty());
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn following_needs_a_session_and_an_existing_group(pool: PgPool) {
+    let app = app(pool.clone());
+    let cookie = log_in(&pool, "user_1").await;
+
+    let missing = call_as(&app, &cookie, "POST", "/api/v1/groups/grp_missing/follow", None).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let anonymous = call_as(&app, "", "POST", "/api/v1/groups/grp_test/follow", None).await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+    let anonymous = call_as(&app, "", "GET", "/api/v1/me/feed", None).await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+}
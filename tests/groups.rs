@@ -0,0 +1,124 @@
+mod common;
+
+use axum::http::{StatusCode, header::IF_MATCH};
+use common::{api_request, app, call, event_json, send};
+use rust_vue_skeleton::database::{Actor, AuditContext, GroupDeletion, GroupModel, PostgresDatabase};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn groups_with_events_are_only_deleted_with_cascade(pool: PgPool) {
+    let app = app(pool);
+
+    let refused = call(&app, "DELETE", "/api/v1/groups/grp_test", None).await;
+    assert_eq!(refused.status, StatusCode::CONFLICT);
+    assert_eq!(refused.body["code"], "in_use");
+    assert_eq!(refused.body["event_ids"], json!(["evt_past", "evt_test"]));
+
+    let deleted = call(&app, "DELETE", "/api/v1/groups/grp_test?cascade=true", None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+    let event = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(event.status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn group_profiles_are_validated_field_by_field(pool: PgPool) {
+    let app = app(pool);
+    let group = json!({
+        "vrc_group_id": "grp_new",
+        "name": "New group",
+        "icon_url": "ftp://example.com/icon.png",
+        "short_code": "x",
+        "discord_invite_url": "https://example.com/invite",
+        "timezone": "Mars/Olympus_Mons",
+        "language": "not a language",
+        "visibility": "public",
+    });
+
+    let refused = call(&app, "POST", "/api/v1/groups", Some(&group)).await;
+
+    assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let mut fields: Vec<_> = refused.body["errors"]
+        .as_array()
+        .expect("errors are a list")
+        .iter()
+        .map(|error| error["field"].as_str().expect("error names its field"))
+        .collect();
+    fields.sort_unstable();
+    assert_eq!(
+        fields,
+        ["discord_invite_url", "icon_url", "language", "short_code", "timezone"]
+    );
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn group_profiles_are_created_and_updated_in_part(pool: PgPool) {
+    let app = app(pool);
+    let group = json!({
+        "vrc_group_id": "grp_new",
+        "name": "New group",
+        "description": "We meet weekly",
+        "short_code": "NEW.01",
+        "discord_invite_url": "https://discord.gg/abc",
+        "timezone": "Europe/Berlin",
+        "language": "pt-BR",
+        "visibility": "unlisted",
+    });
+    let created = call(&app, "POST", "/api/v1/groups", Some(&group)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+
+    // absent and null fields are left unchanged by PUT
+    let update = json!({ "name": "Renamed group", "description": null, "language": "en" });
+    let updated = call(&app, "PUT", "/api/v1/groups/grp_new", Some(&update)).await;
+
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["name"], "Renamed group");
+    assert_eq!(updated.body["description"], "We meet weekly");
+    assert_eq!(updated.body["language"], "en");
+    assert_eq!(updated.body["timezone"], "Europe/Berlin");
+    assert_eq!(updated.body["visibility"], "unlisted");
+
+    let invalid = call(&app, "PUT", "/api/v1/groups/grp_new", Some(&json!({ "name": "" }))).await;
+    assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(invalid.body["errors"][0]["field"], "name");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn stale_versions_fail_before_missing_cascades(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let audit = AuditContext {
+        actor: Actor::System("test".to_string()),
+        request_id: None,
+    };
+
+    let deletion = db
+        .delete_group("grp_test", Some(99), false, &audit)
+        .await
+        .expect("group is looked up");
+    assert_eq!(deletion, GroupDeletion::NotFound);
+
+    let request = api_request("DELETE", "/api/v1/groups/grp_test", None)
+        .header(IF_MATCH, "\"99\"")
+        .body(axum::body::Body::empty())
+        .expect("request is valid");
+    let response = send(&app(pool), request).await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn deleted_groups_gain_no_events(pool: PgPool) {
+    let app = app(pool);
+    let deleted = call(&app, "DELETE", "/api/v1/groups/grp_other", None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+
+    let mut event = event_json("evt_new", 5);
+    event["vrc_group_id"] = json!("grp_other");
+    let refused = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+
+    assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(refused.body["code"], "invalid_reference");
+    assert_eq!(
+        refused.body["detail"],
+        "vrc_group_id refers to a deleted group, restore the group first"
+    );
+}
@@ -0,0 +1,446 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call};
+use rust_vue_skeleton::{
+    database::{EventAccessType, EventCategory, PostgresDatabase},
+    ical::{self, FEED_EVENT_ID_PREFIX},
+    scheduler::{Scheduler, SchedulerConfig},
+    validation::Validate,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime, UtcOffset, macros::datetime};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+const FEED: &str = include_str!("fixtures/feed.ics");
+
+#[test]
+fn parses_events_of_a_google_calendar_feed() {
+    let calendar = ical::parse_calendar(FEED).expect("fixture is a calendar");
+
+    let uids: Vec<&str> = calendar.events.iter().map(|event| event.uid.as_str()).collect();
+    assert_eq!(
+        uids,
+        [
+            "utc-event@google.com",
+            "berlin-event@google.com",
+            "all-day@google.com",
+            "cancelled@google.com"
+        ]
+    );
+    assert_eq!(calendar.skipped, ["event no-start@google.com: has no DTSTART"]);
+
+    let utc = &calendar.events[0];
+    assert_eq!(utc.summary, "Friday DJ set, live");
+    assert_eq!(utc.description, "Doors open at 8.\nBring friends!");
+    assert_eq!(utc.starts_at, datetime!(2026-10-24 20:00 UTC));
+    assert_eq!(utc.ends_at, datetime!(2026-10-24 23:00 UTC));
+    assert_eq!(utc.categories, ["Music", "DJ Set"]);
+    assert!(!utc.cancelled);
+
+    // after the switch back to CET, and ending by DURATION
+    let berlin = &calendar.events[1];
+    assert_eq!(
+        berlin.summary,
+        "Halloween hangout with a summary long enough that it has to be folded over two lines"
+    );
+    assert_eq!(berlin.starts_at, datetime!(2026-10-31 20:00 UTC));
+    assert_eq!(berlin.ends_at, datetime!(2026-10-31 21:30 UTC));
+
+    // a date is the whole day in the calendar's X-WR-TIMEZONE
+    let all_day = &calendar.events[2];
+    assert_eq!(all_day.starts_at, datetime!(2026-10-31 23:00 UTC));
+    assert_eq!(all_day.ends_at, datetime!(2026-11-01 23:00 UTC));
+
+    assert!(calendar.events[3].cancelled);
+}
+
+#[test]
+fn expands_recurring_events_with_their_overrides() {
+    let calendar = ical::parse_calendar(FEED).expect("fixture is a calendar");
+    assert_eq!(calendar.overrides.len(), 1);
+
+    let occurrences = calendar.occurrences(datetime!(2026-10-19 0:00 UTC), datetime!(2027-01-01 0:00 UTC));
+    let weekly: Vec<_> = occurrences
+        .iter()
+        .filter(|event| event.uid == "berlin-event@google.com")
+        .map(|event| (event.starts_at, event.summary.as_str()))
+        .collect();
+    let summary = calendar.events[1].summary.as_str();
+    // COUNT=4, with the second occurrence moved an hour later
+    assert_eq!(
+        weekly,
+        [
+            (datetime!(2026-10-31 20:00 UTC), summary),
+            (datetime!(2026-11-14 20:00 UTC), summary),
+            (datetime!(2026-11-21 20:00 UTC), summary),
+            (datetime!(2026-11-07 21:00 UTC), "Halloween hangout (moved)"),
+        ]
+    );
+
+    // every occurrence is an event of its own, and the moved one keeps the ID of the occurrence it replaces
+    let ids: Vec<_> = occurrences
+        .iter()
+        .filter(|event| event.uid == "berlin-event@google.com")
+        .map(|event| event.to_create_event("grp_feed").vrc_event_id)
+        .collect();
+    assert_eq!(ids.iter().collect::<std::collections::HashSet<_>>().len(), 4);
+    assert!(!ids.contains(&ical::feed_event_id("grp_feed", "berlin-event@google.com")));
+}
+
+#[test]
+fn expands_monthly_rules_within_the_window() {
+    let feed = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:monthly\r\n\
+        DTSTART;TZID=Europe/Berlin:20260113T190000\r\n\
+        DTEND;TZID=Europe/Berlin:20260113T210000\r\n\
+        RRULE:FREQ=MONTHLY;BYDAY=2TU;UNTIL=20261231T235959Z\r\n\
+        EXDATE;TZID=Europe/Berlin:20261110T190000\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:unsupported\r\n\
+        DTSTART:20260113T190000Z\r\n\
+        RRULE:FREQ=HOURLY\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+    let calendar = ical::parse_calendar(feed).expect("feed is a calendar");
+    assert_eq!(calendar.skipped, ["event unsupported: has an unsupported RRULE"]);
+
+    // the second Tuesdays from October on, without the one in November
+    let starts: Vec<_> = calendar
+        .occurrences(datetime!(2026-10-01 0:00 UTC), datetime!(2027-06-01 0:00 UTC))
+        .iter()
+        .map(|event| event.starts_at)
+        .collect();
+    assert_eq!(
+        starts,
+        [datetime!(2026-10-13 17:00 UTC), datetime!(2026-12-08 18:00 UTC)]
+    );
+}
+
+#[test]
+fn skips_events_that_would_run_past_the_end_of_the_calendar() {
+    let feed = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:endless\r\n\
+        DTSTART:20261019T000000Z\r\n\
+        DTEND:99991231T000000Z\r\n\
+        RRULE:FREQ=DAILY\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:last-week\r\n\
+        DTSTART:99991225T000000Z\r\n\
+        DTEND:99991231T000000Z\r\n\
+        RRULE:FREQ=DAILY\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+    let calendar = ical::parse_calendar(feed).expect("feed is a calendar");
+    assert_eq!(calendar.skipped, ["event endless: lasts longer than 7 days"]);
+
+    // only the first occurrence ends within the calendar, the later ones are left out rather than overflowing
+    let starts: Vec<_> = calendar
+        .occurrences(datetime!(2026-10-19 0:00 UTC), datetime!(9999-12-31 0:00 UTC))
+        .iter()
+        .map(|event| event.starts_at)
+        .collect();
+    assert_eq!(starts, [datetime!(9999-12-25 0:00 UTC)]);
+}
+
+#[test]
+fn rejects_input_that_is_not_a_calendar() {
+    assert!(ical::parse_calendar("<html>Not found</html>").is_err());
+}
+
+#[test]
+fn maps_feed_events_to_valid_events_with_stable_ids() {
+    let calendar = ical::parse_calendar(FEED).expect("fixture is a calendar");
+    let event = &calendar.events[0];
+
+    let create_event = event.to_create_event("grp_feed");
+    assert!(create_event.validate().is_ok());
+    assert!(create_event.vrc_event_id.starts_with(FEED_EVENT_ID_PREFIX));
+    assert_eq!(create_event.vrc_group_id, "grp_feed");
+    assert_eq!(create_event.name, "Friday DJ set, live");
+    assert_eq!(create_event.category, EventCategory::Music);
+    assert_eq!(create_event.access_type, EventAccessType::Group);
+    assert_eq!(create_event.tags.as_deref(), Some(&["dj-set".to_string()][..]));
+
+    // the same UID is the same event on every import, but not across groups
+    assert_eq!(
+        create_event.vrc_event_id,
+        ical::feed_event_id("grp_feed", "utc-event@google.com")
+    );
+    assert_ne!(
+        create_event.vrc_event_id,
+        ical::feed_event_id("grp_other", "utc-event@google.com")
+    );
+}
+
+/// The host of the stand-in servers, which is internal and so has to be allowed explicitly.
+const LOCAL_HOST: &str = "127.0.0.1";
+
+/// Serves one request with `status` and `body`, returning the feed's URL.
+async fn serve_once(status: &'static str, body: &str) -> String {
+    serve_once_with_headers(status, "", body).await
+}
+
+/// Redirects one request to `location`, returning the feed's URL.
+async fn redirect_once(location: &str) -> String {
+    serve_once_with_headers("302 Found", &format!("Location: {location}\r\n"), "").await
+}
+
+async fn serve_once_with_headers(status: &'static str, headers: &str, body: &str) -> String {
+    let (headers, body) = (headers.to_string(), body.to_string());
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind stand-in server");
+    let address = listener.local_addr().expect("stand-in server address");
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.expect("accept request");
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).await.expect("read request");
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        let response = format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Type: text/calendar\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.expect("write response");
+    });
+
+    format!("http://{address}/calendar/ical/basic.ics")
+}
+
+#[tokio::test]
+async fn fetches_a_feed_over_http() {
+    let url = serve_once("200 OK", FEED).await;
+
+    let body = ical::fetch_feed(&url, &[LOCAL_HOST.to_string()])
+        .await
+        .expect("feed is fetched");
+
+    assert_eq!(body, FEED);
+}
+
+#[tokio::test]
+async fn refuses_redirects_to_internal_addresses() {
+    let allowed = [LOCAL_HOST.to_string()];
+
+    // the same stand-in server, under a name that was not allowed
+    let feed = serve_once("200 OK", FEED).await;
+    let url = redirect_once(&feed.replace(LOCAL_HOST, "localhost")).await;
+    let error = ical::fetch_feed(&url, &allowed)
+        .await
+        .expect_err("a redirect to localhost is refused");
+    assert_eq!(error, "the feed's host is a private, loopback or link-local address");
+
+    let url = redirect_once("http://169.254.169.254/latest/meta-data").await;
+    let error = ical::fetch_feed(&url, &allowed)
+        .await
+        .expect_err("a redirect to the metadata service is refused");
+    assert_eq!(error, "the feed's host is a private, loopback or link-local address");
+
+    // nothing is allowed by default, not even the feed's own host
+    let error = ical::fetch_feed(&feed, &[])
+        .await
+        .expect_err("an internal feed is refused");
+    assert_eq!(error, "the feed's host is a private, loopback or link-local address");
+
+    let url = redirect_once(&feed).await;
+    let body = ical::fetch_feed(&url, &allowed)
+        .await
+        .expect("a redirect to an allowed host is followed");
+    assert_eq!(body, FEED);
+}
+
+#[tokio::test]
+async fn refuses_feed_addresses_that_are_not_urls() {
+    for url in ["not a url", "ftp://calendar.example/basic.ics", "webcal://"] {
+        let error = ical::fetch_feed(url, &[]).await.expect_err("the address is refused");
+        assert_eq!(error, "the feed address is not a valid URL", "{url}");
+    }
+}
+
+#[tokio::test]
+async fn reports_feeds_that_fail_to_load() {
+    let url = serve_once("404 Not Found", "not found").await;
+
+    let error = ical::fetch_feed(&url, &[LOCAL_HOST.to_string()])
+        .await
+        .expect_err("a missing feed is an error");
+
+    assert_eq!(error, "the server responded with 404 Not Found");
+}
+
+fn format_utc(datetime: OffsetDateTime) -> String {
+    let utc = datetime.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        utc.year(),
+        u8::from(utc.month()),
+        utc.day(),
+        utc.hour(),
+        utc.minute(),
+        utc.second()
+    )
+}
+
+/// A feed of `events`, each a `UID`, `SUMMARY`, start in hours from now and further properties.
+fn feed(events: &[(&str, &str, i64, &str)]) -> String {
+    let now = OffsetDateTime::now_utc();
+    let mut feed = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n".to_string();
+    for (uid, summary, in_hours, properties) in events {
+        let starts_at = now + Duration::hours(*in_hours);
+        feed.push_str(&format!(
+            "BEGIN:VEVENT\r\nUID:{uid}\r\nSUMMARY:{summary}\r\nDTSTART:{}\r\nDTEND:{}\r\n{properties}END:VEVENT\r\n",
+            format_utc(starts_at),
+            format_utc(starts_at + Duration::hours(1))
+        ));
+    }
+    feed.push_str("END:VCALENDAR\r\n");
+    feed
+}
+
+fn scheduler(pool: &PgPool) -> Scheduler {
+    let config = SchedulerConfig {
+        feed_allowed_hosts: vec![LOCAL_HOST.to_string()],
+        ..SchedulerConfig::default()
+    };
+    Scheduler::new(PostgresDatabase::from_pool(pool.clone()), config)
+}
+
+async fn register_feed(pool: &PgPool, vrc_group_id: &str) {
+    sqlx::query("INSERT INTO ical_feeds (vrc_group_id, url) VALUES ($1, 'https://calendar.example/basic.ics')")
+        .bind(vrc_group_id)
+        .execute(pool)
+        .await
+        .expect("feed is registered");
+}
+
+async fn feed_status(pool: &PgPool, vrc_group_id: &str) -> (Option<OffsetDateTime>, i32, Option<String>) {
+    sqlx::query_as("SELECT last_synced_at, event_count, last_error FROM ical_feeds WHERE vrc_group_id = $1")
+        .bind(vrc_group_id)
+        .fetch_one(pool)
+        .await
+        .expect("feed exists")
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn imports_updates_and_removes_feed_events(pool: PgPool) {
+    let app = app(pool.clone());
+    let scheduler = scheduler(&pool);
+    register_feed(&pool, "grp_other").await;
+    let event_id = |uid: &str| ical::feed_event_id("grp_other", uid);
+
+    let first = feed(&[
+        ("kept", "Meetup", 48, ""),
+        ("cancelled", "Workshop", 72, ""),
+        ("removed", "Weekly", 96, "RRULE:FREQ=WEEKLY;COUNT=3\r\n"),
+    ]);
+    let url = serve_once("200 OK", &first).await;
+    scheduler.sync_feed("grp_other", &url).await.expect("feed is synced");
+
+    let imported = call(&app, "GET", &format!("/api/v1/events/{}", event_id("kept")), None).await;
+    assert_eq!(imported.status, StatusCode::OK);
+    assert_eq!(imported.body["name"], "Meetup");
+    assert_eq!(imported.body["vrc_group_id"], "grp_other");
+    let (last_synced_at, event_count, last_error) = feed_status(&pool, "grp_other").await;
+    assert!(last_synced_at.is_some());
+    assert_eq!(event_count, 5);
+    assert_eq!(last_error, None);
+
+    // an event is updated in place, one is cancelled and the recurring one is gone from the feed
+    let second = feed(&[
+        ("kept", "Meetup, moved", 50, ""),
+        ("cancelled", "Workshop", 72, "STATUS:CANCELLED\r\n"),
+    ]);
+    let url = serve_once("200 OK", &second).await;
+    scheduler.sync_feed("grp_other", &url).await.expect("feed is synced");
+
+    let updated = call(&app, "GET", &format!("/api/v1/events/{}", event_id("kept")), None).await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["name"], "Meetup, moved");
+    let cancelled = call(&app, "GET", &format!("/api/v1/events/{}", event_id("cancelled")), None).await;
+    assert_eq!(cancelled.status, StatusCode::NOT_FOUND);
+
+    let remaining: Vec<String> =
+        sqlx::query_scalar("SELECT vrc_event_id FROM events WHERE vrc_group_id = 'grp_other' AND deleted_at IS NULL")
+            .fetch_all(&pool)
+            .await
+            .expect("events are listed");
+    assert_eq!(remaining, [event_id("kept")]);
+    assert_eq!(feed_status(&pool, "grp_other").await.1, 1);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn records_why_a_feed_failed_without_the_details(pool: PgPool) {
+    let scheduler = scheduler(&pool);
+    register_feed(&pool, "grp_other").await;
+
+    let url = serve_once("500 Internal Server Error", "boom").await;
+    scheduler
+        .sync_feed("grp_other", &url)
+        .await
+        .expect("a failing feed is recorded");
+    let (last_synced_at, _, last_error) = feed_status(&pool, "grp_other").await;
+    assert_eq!(last_synced_at, None);
+    assert_eq!(
+        last_error.as_deref(),
+        Some("the server responded with 500 Internal Server Error")
+    );
+
+    // nothing listens on a port that was just released
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind port");
+    let address = listener.local_addr().expect("port address");
+    drop(listener);
+    scheduler
+        .sync_feed("grp_other", &format!("http://{address}/basic.ics"))
+        .await
+        .expect("a failing feed is recorded");
+    let last_error = feed_status(&pool, "grp_other").await.2;
+    assert_eq!(last_error.as_deref(), Some("the server could not be reached"));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn refuses_feeds_on_internal_addresses(pool: PgPool) {
+    let app = app(pool);
+
+    for url in [
+        "http://127.0.0.1:8080/basic.ics",
+        "http://localhost/basic.ics",
+        "http://169.254.169.254/latest/meta-data",
+        "https://10.0.0.5/basic.ics",
+        "http://[::1]/basic.ics",
+        "http://[::ffff:192.168.0.1]/basic.ics",
+        "webcal://192.168.0.1/basic.ics",
+    ] {
+        let response = call(
+            &app,
+            "PUT",
+            "/api/v1/groups/grp_test/feed",
+            Some(&json!({ "url": url })),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{url}");
+        assert_eq!(response.body["errors"][0]["field"], "url", "{url}");
+    }
+
+    let response = call(
+        &app,
+        "PUT",
+        "/api/v1/groups/grp_test/feed",
+        Some(&json!({ "url": "https://93.184.215.14/basic.ics" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{API_KEY, api_request, app, call, event_json, json_body, send};
+use rust_vue_skeleton::database::{IdempotencyClaim, IdempotencyModel, PostgresDatabase};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+async fn post_event(app: &axum::Router, idempotency_key: &str, body: &Value) -> common::TestResponse {
+    let request = api_request("POST", "/api/v1/events", Some(body))
+        .header("idempotency-key", idempotency_key)
+        .body(json_body(Some(body)))
+        .expect("request is valid");
+    send(app, request).await
+}
+
+async fn claim_count(pool: &PgPool, idempotency_key: &str) -> i64 {
+    sqlx::query_scalar("SELECT count(*) FROM idempotency_keys WHERE idempotency_key = $1")
+        .bind(idempotency_key)
+        .fetch_one(pool)
+        .await
+        .expect("claims are counted")
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn retries_replay_the_first_response(pool: PgPool) {
+    let app = app(pool);
+    let event = event_json("evt_new", 5);
+
+    let first = post_event(&app, "retry-1", &event).await;
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert!(first.headers.get("idempotent-replayed").is_none());
+
+    let retry = post_event(&app, "retry-1", &event).await;
+    assert_eq!(retry.status, StatusCode::CREATED);
+    assert_eq!(retry.headers["idempotent-replayed"], "true");
+    assert_eq!(retry.headers["location"], first.headers["location"]);
+    assert_eq!(retry.body, first.body);
+
+    // without the key, the same request is a duplicate
+    let duplicate = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(duplicate.status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn keys_cannot_be_reused_for_other_requests(pool: PgPool) {
+    let app = app(pool);
+
+    post_event(&app, "reused", &event_json("evt_first", 5)).await;
+    let reused = post_event(&app, "reused", &event_json("evt_second", 5)).await;
+
+    assert_eq!(reused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(reused.body["code"], "idempotency_key_reused");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn claims_in_flight_answer_retries_with_a_conflict(pool: PgPool) {
+    let app = app(pool.clone());
+    let event = event_json("evt_new", 5);
+
+    // the first request claimed the key a moment ago and is still running
+    let first = post_event(&app, "in-flight", &event).await;
+    sqlx::query("UPDATE idempotency_keys SET response_status = NULL, locked_at = now()")
+        .execute(&pool)
+        .await
+        .expect("claim is reset");
+
+    let retry = post_event(&app, "in-flight", &event).await;
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(retry.status, StatusCode::CONFLICT);
+    assert_eq!(retry.body["code"], "idempotency_key_in_use");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn abandoned_claims_are_taken_over(pool: PgPool) {
+    let app = app(pool.clone());
+    let event = event_json("evt_new", 5);
+
+    // a request that claimed the key and then died without releasing it
+    sqlx::query(
+        "INSERT INTO idempotency_keys (api_key, idempotency_key, request_hash, locked_at) \
+         VALUES ('test-key', 'abandoned', 'whatever', $1)",
+    )
+    .bind(OffsetDateTime::now_utc() - time::Duration::minutes(5))
+    .execute(&pool)
+    .await
+    .expect("claim is inserted");
+
+    let retry = post_event(&app, "abandoned", &event).await;
+    assert_eq!(retry.status, StatusCode::CREATED);
+    assert!(retry.headers.get("idempotent-replayed").is_none());
+}
+
+async fn age_claims(pool: &PgPool) {
+    sqlx::query("UPDATE idempotency_keys SET heartbeat_at = now() - interval '5 minutes'")
+        .execute(pool)
+        .await
+        .expect("claims are aged");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn renewed_claims_are_not_taken_over(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let claim = || db.claim_idempotency_key(API_KEY, "long-running", "hash");
+
+    // a request still running past the lease, which renewed its claim in time
+    let Ok(IdempotencyClaim::Claimed { locked_at }) = claim().await else {
+        panic!("a new key is claimed");
+    };
+    age_claims(&pool).await;
+    db.renew_idempotency_key(API_KEY, "long-running", locked_at)
+        .await
+        .expect("claim is renewed");
+    assert!(matches!(claim().await, Ok(IdempotencyClaim::Used(_))));
+
+    // once the renewals stop, the claim is abandoned
+    age_claims(&pool).await;
+    assert!(matches!(claim().await, Ok(IdempotencyClaim::Claimed { .. })));
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn requests_dropped_mid_way_release_their_claim(pool: PgPool) {
+    let app = app(pool.clone());
+    let event = event_json("evt_new", 5);
+
+    // holds up the insert, so that the client gives up while the handler is still running
+    let mut lock = pool.begin().await.expect("transaction begins");
+    sqlx::query("LOCK TABLE events IN EXCLUSIVE MODE")
+        .execute(&mut *lock)
+        .await
+        .expect("events are locked");
+
+    let timed_out = tokio::time::timeout(Duration::from_millis(500), post_event(&app, "dropped", &event)).await;
+    assert!(timed_out.is_err());
+    lock.rollback().await.expect("lock is released");
+
+    for _ in 0..50 {
+        if claim_count(&pool, "dropped").await == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(claim_count(&pool, "dropped").await, 0);
+
+    let retry = post_event(&app, "dropped", &event).await;
+    assert_eq!(retry.status, StatusCode::CREATED);
+}
@@ -0,0 +1,190 @@
+use rust_vue_skeleton::database::{JobModel, NewJob, PostgresDatabase};
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+const LEASE: Duration = Duration::minutes(10);
+
+async fn schedule(db: &PostgresDatabase, dedupe_key: &str, run_at: OffsetDateTime, max_attempts: i32) {
+    let job = NewJob {
+        kind: "test",
+        dedupe_key: Some(dedupe_key.to_string()),
+        payload: "{}".to_string(),
+        run_at,
+        max_attempts,
+    };
+    db.schedule_job(job).await.expect("job is scheduled");
+}
+
+async fn job_state(pool: &PgPool, dedupe_key: &str) -> (String, i32, Option<String>) {
+    sqlx::query_as("SELECT status, attempts, last_error FROM jobs WHERE dedupe_key = $1")
+        .bind(dedupe_key)
+        .fetch_one(pool)
+        .await
+        .expect("job exists")
+}
+
+/// Pretends the worker running the job died `ago`.
+async fn abandon(pool: &PgPool, id: Uuid, ago: Duration) {
+    sqlx::query("UPDATE jobs SET locked_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(OffsetDateTime::now_utc() - ago)
+        .execute(pool)
+        .await
+        .expect("job is abandoned");
+}
+
+#[sqlx::test]
+async fn claims_only_due_jobs_once(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let now = OffsetDateTime::now_utc();
+    schedule(&db, "due", now - Duration::minutes(1), 3).await;
+    schedule(&db, "later", now + Duration::hours(1), 3).await;
+
+    let jobs = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed");
+    let keys: Vec<_> = jobs.iter().filter_map(|job| job.dedupe_key.as_deref()).collect();
+    assert_eq!(keys, ["due"]);
+    assert_eq!(jobs[0].attempts, 1);
+
+    // a running job with a fresh lease belongs to its worker
+    assert!(db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").is_empty());
+    assert_eq!(job_state(&pool, "later").await.0, "pending");
+}
+
+#[sqlx::test]
+async fn reclaims_abandoned_jobs_until_they_run_out_of_attempts(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    schedule(&db, "crashing", OffsetDateTime::now_utc(), 2).await;
+
+    let id = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed")[0].id;
+    abandon(&pool, id, LEASE + Duration::minutes(1)).await;
+
+    let reclaimed = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed");
+    assert_eq!(reclaimed.len(), 1);
+    assert_eq!(reclaimed[0].attempts, 2);
+
+    // the second attempt crashed as well, which was the last one
+    abandon(&pool, id, LEASE + Duration::minutes(1)).await;
+    assert!(db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").is_empty());
+
+    let (status, attempts, last_error) = job_state(&pool, "crashing").await;
+    assert_eq!(status, "dead");
+    assert_eq!(attempts, 2);
+    assert!(last_error.is_some());
+}
+
+#[sqlx::test]
+async fn renewed_leases_keep_long_jobs_from_being_reclaimed(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    schedule(&db, "long", OffsetDateTime::now_utc(), 3).await;
+
+    let id = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed")[0].id;
+    abandon(&pool, id, LEASE + Duration::minutes(1)).await;
+    db.renew_job_lease(id).await.expect("lease is renewed");
+
+    assert!(db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").is_empty());
+    assert_eq!(job_state(&pool, "long").await.0, "running");
+}
+
+#[sqlx::test]
+async fn failed_jobs_are_retried_then_dead_lettered(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    schedule(&db, "failing", OffsetDateTime::now_utc(), 2).await;
+
+    let job = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").remove(0);
+    db.fail_job(job.id, job.run_at, "boom", OffsetDateTime::now_utc())
+        .await
+        .expect("failure is recorded");
+    assert_eq!(job_state(&pool, "failing").await.0, "pending");
+
+    let job = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").remove(0);
+    db.fail_job(job.id, job.run_at, "boom again", OffsetDateTime::now_utc())
+        .await
+        .expect("failure is recorded");
+
+    let (status, _, last_error) = job_state(&pool, "failing").await;
+    assert_eq!(status, "dead");
+    assert_eq!(last_error.as_deref(), Some("boom again"));
+}
+
+#[sqlx::test]
+async fn jobs_scheduled_again_while_running_run_again_afterwards(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let now = OffsetDateTime::now_utc();
+    schedule(&db, "once", now - Duration::minutes(2), 3).await;
+    schedule(&db, "twice", now - Duration::minutes(1), 3).await;
+
+    let jobs = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed");
+    assert_eq!(jobs.len(), 2);
+
+    // e.g. a manual sync requested while the previous one is still running
+    schedule(&db, "twice", now, 3).await;
+    assert_eq!(job_state(&pool, "twice").await.0, "running");
+    assert!(db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").is_empty());
+
+    for job in &jobs {
+        db.complete_job(job.id, job.run_at).await.expect("job is completed");
+    }
+    assert_eq!(job_state(&pool, "once").await, ("done".to_string(), 1, None));
+    assert_eq!(job_state(&pool, "twice").await, ("pending".to_string(), 0, None));
+
+    let rerun = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed");
+    assert_eq!(rerun.len(), 1);
+    assert_eq!(rerun[0].dedupe_key.as_deref(), Some("twice"));
+}
+
+#[sqlx::test]
+async fn failed_jobs_scheduled_again_while_running_keep_the_later_run(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let now = OffsetDateTime::now_utc();
+    schedule(&db, "moved", now - Duration::minutes(1), 1).await;
+
+    let job = db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed").remove(0);
+    schedule(&db, "moved", now + Duration::hours(2), 1).await;
+    db.fail_job(job.id, job.run_at, "boom", now + Duration::minutes(1))
+        .await
+        .expect("failure is recorded");
+
+    // the failed attempt was its last, but the job was scheduled again and starts over
+    assert_eq!(
+        job_state(&pool, "moved").await,
+        ("pending".to_string(), 0, Some("boom".to_string()))
+    );
+    let run_at: OffsetDateTime = sqlx::query_scalar("SELECT run_at FROM jobs WHERE dedupe_key = 'moved'")
+        .fetch_one(&pool)
+        .await
+        .expect("job exists");
+    assert!(run_at > now + Duration::hours(1));
+}
+
+#[sqlx::test]
+async fn purges_jobs_that_finished_before_the_retention(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool.clone());
+    let now = OffsetDateTime::now_utc();
+    schedule(&db, "old", now - Duration::days(10), 1).await;
+    schedule(&db, "recent", now - Duration::minutes(1), 1).await;
+    schedule(&db, "dead", now - Duration::days(10), 1).await;
+    schedule(&db, "pending", now + Duration::hours(1), 1).await;
+
+    for job in db.claim_due_jobs(10, LEASE).await.expect("jobs are claimed") {
+        if job.dedupe_key.as_deref() == Some("dead") {
+            db.fail_job(job.id, job.run_at, "boom", now)
+                .await
+                .expect("failure is recorded");
+        } else {
+            db.complete_job(job.id, job.run_at).await.expect("job is completed");
+        }
+    }
+
+    let purged = db
+        .purge_finished_jobs(now - Duration::days(7))
+        .await
+        .expect("jobs are purged");
+
+    assert_eq!(purged, 1);
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT dedupe_key FROM jobs ORDER BY dedupe_key")
+        .fetch_all(&pool)
+        .await
+        .expect("jobs are listed");
+    assert_eq!(remaining, ["dead", "pending", "recent"]);
+}
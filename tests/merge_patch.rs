@@ -0,0 +1,112 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{api_request, app, call, send};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+fn error_fields(body: &Value) -> Vec<&str> {
+    body["errors"]
+        .as_array()
+        .expect("errors are a list")
+        .iter()
+        .map(|error| error["field"].as_str().expect("error names its field"))
+        .collect()
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn patches_change_only_the_given_fields_and_null_removes(pool: PgPool) {
+    let app = app(pool);
+
+    let patch = json!({ "name": "Renamed event", "image_url": "https://example.com/event.png" });
+    let patched = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&patch)).await;
+    assert_eq!(patched.status, StatusCode::OK);
+    assert_eq!(patched.body["name"], "Renamed event");
+    assert_eq!(patched.body["image_url"], "https://example.com/event.png");
+    assert_eq!(patched.body["category"], "hangout");
+    assert_eq!(patched.body["version"], 2);
+
+    let removal = json!({ "image_url": null });
+    let removed = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&removal)).await;
+    assert_eq!(removed.status, StatusCode::OK);
+    assert!(removed.body["image_url"].is_null());
+    assert_eq!(removed.body["name"], "Renamed event");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn group_patches_clear_profile_fields(pool: PgPool) {
+    let app = app(pool);
+    let update = json!({ "description": "We meet weekly" });
+    call(&app, "PUT", "/api/v1/groups/grp_test", Some(&update)).await;
+
+    let removal = json!({ "description": null });
+    let patched = call(&app, "PATCH", "/api/v1/groups/grp_test", Some(&removal)).await;
+
+    assert_eq!(patched.status, StatusCode::OK);
+    assert!(patched.body["description"].is_null());
+    assert_eq!(patched.body["name"], "Test group");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn immutable_fields_can_only_be_repeated(pool: PgPool) {
+    let app = app(pool);
+
+    let same = json!({ "vrc_group_id": "grp_test", "name": "Renamed event" });
+    let repeated = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&same)).await;
+    assert_eq!(repeated.status, StatusCode::OK);
+
+    let moved = json!({ "vrc_group_id": "grp_other", "going_count": 100 });
+    let refused = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&moved)).await;
+    assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(&refused.body), ["going_count", "vrc_group_id"]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn required_fields_cannot_be_removed(pool: PgPool) {
+    let app = app(pool);
+
+    let removal = json!({ "name": null });
+    let event = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&removal)).await;
+    assert_eq!(event.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(&event.body), ["name"]);
+
+    let removal = json!({ "visibility": null });
+    let group = call(&app, "PATCH", "/api/v1/groups/grp_test", Some(&removal)).await;
+    assert_eq!(group.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(&group.body), ["visibility"]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn unknown_fields_are_refused(pool: PgPool) {
+    let app = app(pool);
+
+    let misspelled = json!({ "nmae": "Renamed event" });
+    let refused = call(&app, "PATCH", "/api/v1/events/evt_test", Some(&misspelled)).await;
+    assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(&refused.body), ["nmae"]);
+
+    let unknown = json!({ "colour": "red" });
+    let refused = call(&app, "PATCH", "/api/v1/groups/grp_test", Some(&unknown)).await;
+    assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(&refused.body), ["colour"]);
+
+    let event = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(event.body["name"], "Test event");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn patches_must_be_json_objects(pool: PgPool) {
+    let app = app(pool);
+
+    let request = api_request("PATCH", "/api/v1/events/evt_test", None)
+        .header("content-type", "text/plain")
+        .body("name=Renamed".into())
+        .expect("request is valid");
+    assert_eq!(send(&app, request).await.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let request = api_request("PATCH", "/api/v1/events/evt_test", None)
+        .header("content-type", "application/merge-patch+json")
+        .body("[]".into())
+        .expect("request is valid");
+    assert_eq!(send(&app, request).await.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use axum::http::{StatusCode, header::LOCATION};
+use common::{app, call, event_json};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn creates_answer_201_with_the_location_of_the_resource(pool: PgPool) {
+    let app = app(pool);
+
+    let event = call(&app, "POST", "/api/v1/events", Some(&event_json("evt_new", 5))).await;
+    assert_eq!(event.status, StatusCode::CREATED);
+    assert_eq!(event.headers[LOCATION], "/api/v1/events/evt_new");
+    let location = event.headers[LOCATION].to_str().expect("location is text");
+    assert_eq!(call(&app, "GET", location, None).await.status, StatusCode::OK);
+
+    let group = json!({ "vrc_group_id": "grp_new", "name": "New group" });
+    let group = call(&app, "POST", "/api/v1/groups", Some(&group)).await;
+    assert_eq!(group.status, StatusCode::CREATED);
+    assert_eq!(group.headers[LOCATION], "/api/v1/groups/grp_new");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn taken_ids_and_unknown_groups_are_client_errors(pool: PgPool) {
+    let app = app(pool);
+
+    let taken = call(&app, "POST", "/api/v1/events", Some(&event_json("evt_test", 5))).await;
+    assert_eq!(taken.status, StatusCode::CONFLICT);
+    assert_eq!(taken.body["code"], "already_exists");
+
+    let mut orphan = event_json("evt_orphan", 5);
+    orphan["vrc_group_id"] = json!("grp_missing");
+    let orphan = call(&app, "POST", "/api/v1/events", Some(&orphan)).await;
+    assert_eq!(orphan.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(orphan.body["code"], "invalid_reference");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn mutations_of_missing_rows_answer_404(pool: PgPool) {
+    let app = app(pool);
+    let event = event_json("evt_missing", 5);
+
+    let updated = call(&app, "PUT", "/api/v1/events/evt_missing", Some(&event)).await;
+    assert_eq!(updated.status, StatusCode::NOT_FOUND);
+    assert_eq!(updated.body["code"], "not_found");
+    let deleted = call(&app, "DELETE", "/api/v1/events/evt_missing", None).await;
+    assert_eq!(deleted.status, StatusCode::NOT_FOUND);
+
+    let group = json!({ "name": "Renamed group" });
+    let updated = call(&app, "PUT", "/api/v1/groups/grp_missing", Some(&group)).await;
+    assert_eq!(updated.status, StatusCode::NOT_FOUND);
+
+    let deleted = call(&app, "DELETE", "/api/v1/events/evt_test", None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+    let again = call(&app, "DELETE", "/api/v1/events/evt_test", None).await;
+    assert_eq!(again.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{
+        StatusCode,
+        header::{ETAG, IF_NONE_MATCH},
+    },
+};
+use common::{app, call, send};
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// Adds an event of `grp_test` that starts and ends at the given offsets from now, soft deleted if `deleted`.
+async fn insert_event(pool: &PgPool, vrc_event_id: &str, starts_in: &str, ends_in: &str, deleted: bool) {
+    sqlx::query(
+        r"INSERT INTO events
+          (vrc_event_id, vrc_group_id, name, description, starts_at, ends_at, category, access_type, platforms, deleted_at)
+        VALUES
+          ($1, 'grp_test', $1, '', now() + $2::interval, now() + $3::interval, 'hangout', 'public',
+           '{standalonewindows}', CASE WHEN $4 THEN now() END)",
+    )
+    .bind(vrc_event_id)
+    .bind(starts_in)
+    .bind(ends_in)
+    .bind(deleted)
+    .execute(pool)
+    .await
+    .expect("event is inserted");
+}
+
+fn event_ids(events: &Value) -> Vec<&str> {
+    events
+        .as_array()
+        .expect("events are a list")
+        .iter()
+        .map(|event| event["vrc_event_id"].as_str().expect("event has an id"))
+        .collect()
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn now_lists_the_events_in_progress(pool: PgPool) {
+    insert_event(&pool, "evt_ending", "-2 hours", "10 minutes", false).await;
+    insert_event(&pool, "evt_running", "-10 minutes", "1 hour", false).await;
+    insert_event(&pool, "evt_deleted", "-10 minutes", "1 hour", true).await;
+    insert_event(&pool, "evt_soon", "1 hour", "2 hours", false).await;
+    let app = app(pool);
+
+    let current = call(&app, "GET", "/api/v1/events/now", None).await;
+
+    assert_eq!(current.status, StatusCode::OK);
+    // ending soonest first
+    assert_eq!(event_ids(&current.body), ["evt_ending", "evt_running"]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn upcoming_lists_the_events_starting_within_the_window(pool: PgPool) {
+    insert_event(&pool, "evt_running", "-10 minutes", "1 hour", false).await;
+    insert_event(&pool, "evt_soon", "1 hour", "2 hours", false).await;
+    insert_event(&pool, "evt_later", "3 hours", "4 hours", false).await;
+    let app = app(pool);
+
+    // two hours by default
+    let upcoming = call(&app, "GET", "/api/v1/events/upcoming", None).await;
+    assert_eq!(upcoming.status, StatusCode::OK);
+    assert_eq!(event_ids(&upcoming.body), ["evt_soon"]);
+
+    let upcoming = call(&app, "GET", "/api/v1/events/upcoming?within=2d", None).await;
+    assert_eq!(event_ids(&upcoming.body), ["evt_soon", "evt_later", "evt_test"]);
+
+    let upcoming = call(&app, "GET", "/api/v1/events/upcoming?within=2d&limit=2", None).await;
+    assert_eq!(event_ids(&upcoming.body), ["evt_soon", "evt_later"]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn upcoming_windows_must_be_a_duration_of_at_most_a_week(pool: PgPool) {
+    let app = app(pool);
+
+    for within in ["8d", "-1h", "2w", "soon"] {
+        let response = call(&app, "GET", &format!("/api/v1/events/upcoming?within={within}"), None).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "within={within}");
+    }
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn unchanged_polls_are_answered_with_304(pool: PgPool) {
+    insert_event(&pool, "evt_running", "-10 minutes", "1 hour", false).await;
+    let app = app(pool);
+
+    for uri in ["/api/v1/events/now", "/api/v1/events/upcoming?within=2d"] {
+        let first = call(&app, "GET", uri, None).await;
+        let etag = first.headers[ETAG].clone();
+
+        let request = axum::http::Request::get(uri)
+            .header(IF_NONE_MATCH, etag)
+            .body(Body::empty())
+            .expect("request is valid");
+        assert_eq!(send(&app, request).await.status, StatusCode::NOT_MODIFIED, "{uri}");
+    }
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call};
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn documents_the_routes_it_serves(pool: PgPool) {
+    let app = app(pool);
+
+    let response = call(&app, "GET", "/api/v1/openapi.json", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let paths = &response.body["paths"];
+
+    let event = &paths["/api/v1/events/{id}"];
+    for method in ["get", "put", "patch", "delete"] {
+        assert!(event.get(method).is_some(), "{method} /api/v1/events/{{id}}");
+    }
+    assert!(paths["/api/v1/groups/{id}/feed/sync"].get("post").is_some());
+    assert!(paths["/api/v1/auth/me"].get("get").is_some());
+
+    // invalid filters are answered with 422, 400 is left for malformed parameters
+    let responses = &paths["/api/v1/events"]["get"]["responses"];
+    assert!(responses.get("422").is_some());
+    assert_eq!(
+        responses["400"]["description"],
+        "`include_past` is not `true` or `false`"
+    );
+}
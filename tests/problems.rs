@@ -0,0 +1,85 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header::CONTENT_LENGTH},
+    middleware,
+    routing::get,
+};
+use common::{app, call, event_json, send};
+use rust_vue_skeleton::middleware::problem_details;
+use serde_json::json;
+use sqlx::PgPool;
+
+/// An `/api` route answering with `status` and a plain-text body, behind the problem details middleware.
+fn plain_error(status: StatusCode, body: String) -> Router {
+    let length = body.len().to_string();
+    Router::new()
+        .route(
+            "/api/v1/failing",
+            get(move || async move { (status, [(CONTENT_LENGTH, length)], body) }),
+        )
+        .layer(middleware::from_fn(problem_details))
+}
+
+async fn get_failing(app: &Router) -> common::TestResponse {
+    let request = Request::get("/api/v1/failing")
+        .body(Body::empty())
+        .expect("request is valid");
+    send(app, request).await
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn values_the_database_cannot_store_fail_validation(pool: PgPool) {
+    let app = app(pool);
+    let mut event = event_json("evt_new", 5);
+    event["description"] = json!("before\u{0}after");
+
+    let response = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["code"], "validation_failed");
+    assert_eq!(response.body["errors"][0]["field"], "body");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn malformed_audit_bounds_fail_validation(pool: PgPool) {
+    let app = app(pool);
+
+    let response = call(&app, "GET", "/api/v1/audit?since=yesterday", None).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["code"], "validation_failed");
+    assert_eq!(response.body["errors"][0]["field"], "since");
+}
+
+#[tokio::test]
+async fn the_code_matches_the_status() {
+    for (status, code) in [
+        (StatusCode::CONFLICT, "conflict"),
+        (StatusCode::GONE, "client_error"),
+        (StatusCode::BAD_REQUEST, "bad_request"),
+    ] {
+        let response = get_failing(&plain_error(status, "nope".to_string())).await;
+
+        assert_eq!(response.status, status);
+        assert_eq!(response.body["status"], status.as_u16());
+        assert_eq!(response.body["code"], code);
+    }
+}
+
+#[tokio::test]
+async fn oversized_error_bodies_are_replaced_by_a_generic_problem() {
+    let response = get_failing(&plain_error(StatusCode::CONFLICT, "x".repeat(100 * 1024))).await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.body["code"], "conflict");
+    assert!(response.body.get("detail").is_none());
+    // axum sets the length of the problem, the one of the dropped body must not be kept
+    let length = response
+        .headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok());
+    assert_ne!(length, Some("102400"));
+}
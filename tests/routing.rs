@@ -0,0 +1,75 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, event_json};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn fixed_event_paths_are_not_taken_for_ids(pool: PgPool) {
+    let app = app(pool);
+
+    for prefix in ["/api/v1/events", "/api/events"] {
+        for path in ["now", "upcoming"] {
+            let response = call(&app, "GET", &format!("{prefix}/{path}"), None).await;
+            assert_eq!(response.status, StatusCode::OK, "{prefix}/{path}");
+            assert!(response.body.is_array(), "{prefix}/{path}");
+        }
+
+        let archive = call(&app, "GET", &format!("{prefix}/archive"), None).await;
+        assert_eq!(archive.status, StatusCode::OK, "{prefix}/archive");
+        assert!(archive.body["events"].is_array(), "{prefix}/archive");
+
+        let bulk = call(
+            &app,
+            "POST",
+            &format!("{prefix}/bulk"),
+            Some(&json!([event_json("evt_bulk", 5)])),
+        )
+        .await;
+        assert_eq!(bulk.status, StatusCode::OK, "{prefix}/bulk");
+        assert!(bulk.body["results"].is_array(), "{prefix}/bulk");
+    }
+
+    let event = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(event.body["vrc_event_id"], "evt_test");
+    let event = call(&app, "GET", "/api/event/evt_test", None).await;
+    assert_eq!(event.body["vrc_event_id"], "evt_test");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn group_feeds_are_not_taken_for_a_group(pool: PgPool) {
+    let app = app(pool);
+
+    for path in ["/api/v1/groups/feeds", "/api/feeds"] {
+        let feeds = call(&app, "GET", path, None).await;
+        assert_eq!(feeds.status, StatusCode::OK, "{path}");
+        assert!(feeds.body.is_array(), "{path}");
+    }
+
+    for path in ["/api/v1/groups/grp_test", "/api/group/grp_test"] {
+        let group = call(&app, "GET", path, None).await;
+        assert_eq!(group.body["vrc_group_id"], "grp_test", "{path}");
+    }
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn counts_calls_of_deprecated_routes(pool: PgPool) {
+    let app = app(pool);
+
+    for _ in 0..3 {
+        let response = call(&app, "GET", "/api/events/now", None).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.headers.contains_key("deprecation"));
+    }
+    call(&app, "GET", "/api/v1/events/now", None).await;
+
+    let usage = call(&app, "GET", "/api/v1/legacy-routes", None).await;
+    assert_eq!(usage.status, StatusCode::OK);
+    let usage = usage.body.as_array().expect("usage is a list");
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0]["method"], "GET");
+    assert_eq!(usage[0]["route"], "/api/events/now");
+    assert_eq!(usage[0]["user_agent"], "test-bot");
+    assert_eq!(usage[0]["request_count"], 3);
+}
@@ -0,0 +1,57 @@
+use rust_vue_skeleton::database::{AttendeeModel, EventModel, PostgresDatabase, RsvpStatus};
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn concurrent_rsvps_are_all_counted(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool);
+
+    let rsvps = (1..=20).map(|n| {
+        let db = db.clone();
+        tokio::spawn(async move {
+            let status = if n % 4 == 0 {
+                RsvpStatus::Interested
+            } else {
+                RsvpStatus::Going
+            };
+            db.set_rsvp("evt_test", &format!("user_{n}"), status).await
+        })
+    });
+    for rsvp in rsvps.collect::<Vec<_>>() {
+        rsvp.await.expect("task finished").expect("rsvp is stored");
+    }
+
+    let event = db
+        .get_event("evt_test")
+        .await
+        .expect("event loads")
+        .expect("event exists");
+    assert_eq!(event.going_count, 15);
+    assert_eq!(event.interested_count, 5);
+
+    let removals = (1..=10).map(|n| {
+        let db = db.clone();
+        tokio::spawn(async move { db.remove_rsvp("evt_test", &format!("user_{n}")).await })
+    });
+    for removal in removals.collect::<Vec<_>>() {
+        removal.await.expect("task finished").expect("rsvp is removed");
+    }
+
+    let counts = db
+        .set_rsvp("evt_test", "user_20", RsvpStatus::Interested)
+        .await
+        .expect("rsvp is stored")
+        .expect("event exists");
+    assert_eq!(counts.going_count, 7);
+    assert_eq!(counts.interested_count, 3);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn rsvps_to_missing_events_are_not_stored(pool: PgPool) {
+    let db = PostgresDatabase::from_pool(pool);
+
+    let counts = db
+        .set_rsvp("evt_missing", "user_1", RsvpStatus::Going)
+        .await
+        .expect("rsvp is attempted");
+    assert!(counts.is_none());
+}
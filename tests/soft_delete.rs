@@ -0,0 +1,61 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call};
+use rust_vue_skeleton::database::{AttendeeModel, PostgresDatabase, RsvpStatus};
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn deleted_events_are_hidden_until_restored(pool: PgPool) {
+    let app = app(pool.clone());
+    let db = PostgresDatabase::from_pool(pool);
+
+    let deleted = call(&app, "DELETE", "/api/v1/events/evt_test", None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+
+    let view = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(view.status, StatusCode::NOT_FOUND);
+    let listing = call(&app, "GET", "/api/v1/events?group_id=grp_test", None).await;
+    assert!(
+        listing
+            .body
+            .as_array()
+            .expect("events are a list")
+            .iter()
+            .all(|event| event["vrc_event_id"] != "evt_test")
+    );
+    let rsvp = db
+        .set_rsvp("evt_test", "user_1", RsvpStatus::Going)
+        .await
+        .expect("rsvp runs");
+    assert!(rsvp.is_none());
+
+    // the ID stays taken while the event can still be restored
+    let recreated = call(&app, "POST", "/api/v1/events", Some(&common::event_json("evt_test", 5))).await;
+    assert_eq!(recreated.status, StatusCode::CONFLICT);
+
+    let restored = call(&app, "POST", "/api/v1/events/evt_test/restore", None).await;
+    assert_eq!(restored.status, StatusCode::OK);
+    let view = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(view.status, StatusCode::OK);
+    assert_eq!(view.body["name"], "Test event");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn deleted_groups_refuse_new_events(pool: PgPool) {
+    let app = app(pool);
+
+    let deleted = call(&app, "DELETE", "/api/v1/groups/grp_other", None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+
+    let mut event = common::event_json("evt_new", 5);
+    event["vrc_group_id"] = "grp_other".into();
+    let created = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(created.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(created.body["code"], "invalid_reference");
+
+    let restored = call(&app, "POST", "/api/v1/groups/grp_other/restore", None).await;
+    assert_eq!(restored.status, StatusCode::OK);
+    let created = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, event_json};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+async fn create_tagged(app: &axum::Router, vrc_event_id: &str, tags: &[&str]) {
+    let mut event = event_json(vrc_event_id, 5);
+    event["tags"] = json!(tags);
+    let created = call(app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+}
+
+fn slugs(body: &Value) -> Vec<&str> {
+    body.as_array()
+        .expect("tags are a list")
+        .iter()
+        .filter_map(|tag| tag["slug"].as_str())
+        .collect()
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn tags_count_their_live_events(pool: PgPool) {
+    let app = app(pool);
+    create_tagged(&app, "evt_a", &["music", "dj"]).await;
+    create_tagged(&app, "evt_b", &["music"]).await;
+    create_tagged(&app, "evt_c", &["music"]).await;
+    call(&app, "DELETE", "/api/v1/events/evt_c", None).await;
+
+    let tags = call(&app, "GET", "/api/v1/tags", None).await;
+    assert_eq!(slugs(&tags.body), ["music", "dj"]);
+    assert_eq!(tags.body[0]["event_count"], 2);
+    assert_eq!(tags.body[1]["event_count"], 1);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn tag_search_matches_prefixes_literally(pool: PgPool) {
+    let app = app(pool);
+    create_tagged(&app, "evt_a", &["a_b", "axb", "a%c"]).await;
+
+    let underscore = call(&app, "GET", "/api/v1/tags?q=a_", None).await;
+    assert_eq!(slugs(&underscore.body), ["a_b"]);
+    let percent = call(&app, "GET", "/api/v1/tags?q=%25", None).await;
+    assert!(slugs(&percent.body).is_empty());
+    let prefix = call(&app, "GET", "/api/v1/tags?q=A", None).await;
+    assert_eq!(slugs(&prefix.body), ["a%c", "a_b", "axb"]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn tag_paths_are_normalized(pool: PgPool) {
+    let app = app(pool.clone());
+    create_tagged(&app, "evt_a", &["music", "dj-set"]).await;
+
+    let view = call(&app, "GET", "/api/v1/tags/%23Music", None).await;
+    assert_eq!(view.status, StatusCode::OK);
+    assert_eq!(view.body["slug"], "music");
+
+    let updated = call(
+        &app,
+        "PUT",
+        "/api/v1/tags/MUSIC",
+        Some(&json!({ "description": "Live and recorded" })),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["description"], "Live and recorded");
+
+    let merged = call(
+        &app,
+        "POST",
+        "/api/v1/tags/DJ-Set/merge",
+        Some(&json!({ "into": "#Music" })),
+    )
+    .await;
+    assert_eq!(merged.status, StatusCode::OK);
+    assert_eq!(merged.body["tag"]["aliases"], json!(["dj-set"]));
+
+    // updates are audited like the events they merge into
+    let (actor, diff): (String, Value) =
+        sqlx::query_as("SELECT actor, diff FROM audit_log WHERE entity_type = 'tag' AND entity_id = 'music'")
+            .fetch_one(&pool)
+            .await
+            .expect("tag update is audited");
+    assert_eq!(actor, "test-bot");
+    assert_eq!(diff["description"]["after"], "Live and recorded");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn merges_into_overlong_tags_are_refused(pool: PgPool) {
+    let app = app(pool);
+    create_tagged(&app, "evt_a", &["music"]).await;
+
+    let merged = call(
+        &app,
+        "POST",
+        "/api/v1/tags/music/merge",
+        Some(&json!({ "into": "x".repeat(33) })),
+    )
+    .await;
+    assert_eq!(merged.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(merged.body["errors"][0]["field"], "into");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, call, event_json};
+use serde_json::json;
+use sqlx::PgPool;
+
+#[sqlx::test(fixtures("calendar"))]
+async fn event_names_are_stored_trimmed(pool: PgPool) {
+    let app = app(pool);
+    let mut event = event_json("evt_new", 5);
+    event["name"] = json!("  Padded name \n");
+
+    let created = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let view = call(&app, "GET", "/api/v1/events/evt_new", None).await;
+    assert_eq!(view.body["name"], "Padded name");
+
+    event["name"] = json!("   ");
+    let blank = call(&app, "PUT", "/api/v1/events/evt_new", Some(&event)).await;
+    assert_eq!(blank.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(blank.body["errors"][0]["field"], "name");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn platforms_are_accepted_by_the_names_players_use(pool: PgPool) {
+    let app = app(pool);
+    let mut event = event_json("evt_new", 5);
+    event["platforms"] = json!(["Quest", "pc"]);
+
+    let created = call(&app, "POST", "/api/v1/events", Some(&event)).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let view = call(&app, "GET", "/api/v1/events/evt_new", None).await;
+    assert_eq!(view.body["platforms"], json!(["android", "standalonewindows"]));
+
+    let filtered = call(&app, "GET", "/api/v1/events?platforms=quest", None).await;
+    let ids: Vec<_> = filtered
+        .body
+        .as_array()
+        .expect("events are a list")
+        .iter()
+        .map(|event| event["vrc_event_id"].clone())
+        .collect();
+    assert_eq!(ids, [json!("evt_new")]);
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn values_added_by_newer_releases_are_loaded_unchanged(pool: PgPool) {
+    // what a newer release's migration would do
+    sqlx::query("INSERT INTO event_categories (name) VALUES ('karaoke')")
+        .execute(&pool)
+        .await
+        .expect("category is added");
+    sqlx::query("UPDATE events SET category = 'karaoke' WHERE vrc_event_id = 'evt_test'")
+        .execute(&pool)
+        .await
+        .expect("event is recategorized");
+    let unknown = sqlx::query("UPDATE events SET category = 'unheard_of' WHERE vrc_event_id = 'evt_test'")
+        .execute(&pool)
+        .await;
+    assert!(unknown.is_err());
+
+    let app = app(pool);
+    let view = call(&app, "GET", "/api/v1/events/evt_test", None).await;
+    assert_eq!(view.status, StatusCode::OK);
+    assert_eq!(view.body["category"], "karaoke");
+}
+
+#[sqlx::test(fixtures("calendar"))]
+async fn invalid_filters_name_their_parameter(pool: PgPool) {
+    let app = app(pool);
+
+    for path in ["/api/v1/events", "/api/v1/events/archive"] {
+        let response = call(&app, "GET", &format!("{path}?from=yesterday&tz=Mars/Olympus"), None).await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{path}");
+        assert_eq!(response.body["code"], "validation_failed");
+        let fields: Vec<_> = response.body["errors"]
+            .as_array()
+            .expect("errors are a list")
+            .iter()
+            .map(|error| error["field"].clone())
+            .collect();
+        assert_eq!(fields, [json!("tz")], "{path}");
+
+        let response = call(&app, "GET", &format!("{path}?starts_at=2026-13-01&to=soon"), None).await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{path}");
+        assert_eq!(response.body["errors"][0]["field"], "starts_at");
+        assert_eq!(response.body["errors"][1]["field"], "to");
+    }
+
+    let valid = call(&app, "GET", "/api/v1/events?from=2026-10-19&tz=Europe/Berlin", None).await;
+    assert_eq!(valid.status, StatusCode::OK);
+}
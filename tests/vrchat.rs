@@ -0,0 +1,15 @@
+use rust_vue_skeleton::vrchat::VrchatClient;
+
+#[tokio::test]
+async fn refuses_group_ids_that_would_change_the_request_path() {
+    // nothing listens on the discard port, so a request that was sent would fail differently
+    let client = VrchatClient::new(reqwest::Client::new(), "http://127.0.0.1:9/api/1", None);
+
+    for vrc_group_id in ["grp_1/../../auth/user", "grp_1?n=1000", "grp_1#", "../grp_1", ""] {
+        let error = client
+            .get_group_events(vrc_group_id)
+            .await
+            .expect_err("the group ID is refused");
+        assert_eq!(error, "the group ID is not a valid group ID", "{vrc_group_id}");
+    }
+}